@@ -0,0 +1,28 @@
+use super::RangeError;
+
+/////////////////////////////////////////////////////////////////////////////
+
+/// Round `value` up to the next multiple of `align`.
+pub fn align_up(value: usize, align: usize) -> usize {
+    value.next_multiple_of(align)
+}
+
+/// Smallest alignment in units of `U` whose byte offset is a multiple of `byte_align`.
+///
+/// Vulkan alignments (like `minStorageBufferOffsetAlignment`) are in bytes, suballocators work in units.
+///
+/// Fail if :
+/// - `byte_align` is not a power of two (0 included).
+pub fn unit_align_from_byte_align<U>(byte_align: usize) -> Result<usize, RangeError> {
+    if !byte_align.is_power_of_two() {
+        return Err(RangeError::InvalidAlignment { align: byte_align });
+    }
+    Ok(byte_align / gcd(byte_align, size_of::<U>()))
+}
+
+fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
@@ -0,0 +1,207 @@
+use std::fmt::Debug;
+
+use super::{RangeError, RangeOf, move_within_memory};
+
+type Result<T> = std::result::Result<T, RangeError>;
+
+/////////////////////////////////////////////////////////////////////////////
+// Structures
+/////////////////////////////////////////////////////////////////////////////
+
+/// Batch of copies between two memories (or within one), coalesced as they're pushed.
+///
+/// Destination ranges never overlap, so copies can be executed in any order (as `vk::BufferCopy` regions).
+pub struct CopyPlan<Unit = u8> {
+    copies: Vec<(RangeOf<Unit>, RangeOf<Unit>)>, // (src, dst), sorted by src offset
+}
+
+/// Copy in bytes, ready to become a `vk::BufferCopy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopyRegion {
+    pub src_offset: usize,
+    pub dst_offset: usize,
+    pub size: usize,
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////////
+
+/// New
+impl<Unit> CopyPlan<Unit> {
+    pub fn new() -> Self {
+        Self { copies: Vec::new() }
+    }
+}
+
+/// Plan
+impl<Unit> CopyPlan<Unit> {
+    /// Merged with copies adjacent in both source & destination.
+    ///
+    /// Fail if :
+    /// - Sizes differ.
+    /// - `dst_range` overlaps the destination of another copy.
+    pub fn push(
+        &mut self,
+        mut src_range: RangeOf<Unit>,
+        mut dst_range: RangeOf<Unit>,
+    ) -> Result<()> {
+        if src_range.size != dst_range.size {
+            return Err(RangeError::SizeMismatch {
+                src_size: src_range.size,
+                dst_size: dst_range.size,
+            });
+        }
+        if src_range.is_empty() {
+            return Ok(());
+        }
+        if let Some((_, dst)) = self.copies.iter().find(|(_, dst)| dst.overlaps(&dst_range)) {
+            return Err(RangeError::Overlapping {
+                first_offset: dst.offset,
+                second_offset: dst_range.offset,
+            });
+        }
+
+        let mut position = self
+            .copies
+            .partition_point(|(src, _)| src.offset < src_range.offset);
+
+        // merge with next
+        if let Some((next_src, next_dst)) = self.copies.get(position)
+            && src_range.end() == next_src.offset
+            && dst_range.end() == next_dst.offset
+        {
+            src_range = src_range.hull(next_src);
+            dst_range = dst_range.hull(next_dst);
+            self.copies.remove(position);
+        }
+
+        // merge with previous
+        if let Some((previous_src, previous_dst)) = position
+            .checked_sub(1)
+            .and_then(|previous| self.copies.get(previous))
+            && previous_src.end() == src_range.offset
+            && previous_dst.end() == dst_range.offset
+        {
+            src_range = src_range.hull(previous_src);
+            dst_range = dst_range.hull(previous_dst);
+            position -= 1;
+            self.copies.remove(position);
+        }
+
+        self.copies.insert(position, (src_range, dst_range));
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.copies.clear();
+    }
+
+    /// Count of copies (after coalescing).
+    pub fn len(&self) -> usize {
+        self.copies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.copies.is_empty()
+    }
+
+    /// (src, dst) sorted by source offset.
+    pub fn iter(&self) -> impl Iterator<Item = (RangeOf<Unit>, RangeOf<Unit>)> + '_ {
+        self.copies.iter().copied()
+    }
+
+    /// Copies in bytes, sorted by source offset.
+    pub fn byte_regions(&self) -> impl Iterator<Item = CopyRegion> + '_ {
+        self.copies.iter().map(|(src, dst)| CopyRegion {
+            src_offset: src.byte_offset(),
+            dst_offset: dst.byte_offset(),
+            size: src.byte_size(),
+        })
+    }
+}
+
+/// Host copies
+impl<Unit: Copy> CopyPlan<Unit> {
+    /// Fail if :
+    /// - A copy is not contained in `src` or `dst` (nothing copied).
+    pub fn copy(&self, src: &[Unit], dst: &mut [Unit]) -> Result<()> {
+        let (src_memory, dst_memory): (RangeOf<Unit>, RangeOf<Unit>) =
+            (RangeOf::new(0, src.len()), RangeOf::new(0, dst.len()));
+        for (src_range, dst_range) in self.iter() {
+            src_memory.subrange(src_range.offset, src_range.size)?;
+            dst_memory.subrange(dst_range.offset, dst_range.size)?;
+        }
+
+        for (src_range, dst_range) in self.iter() {
+            dst[dst_range].copy_from_slice(&src[src_range]);
+        }
+        Ok(())
+    }
+
+    /// A copy may overlap its own source.
+    ///
+    /// Fail if (nothing copied) :
+    /// - A copy is not contained in `memory`.
+    /// - A copy overwrites the source of another (result would depend on order).
+    pub fn copy_within(&self, memory: &mut [Unit]) -> Result<()> {
+        let memory_range: RangeOf<Unit> = RangeOf::new(0, memory.len());
+        for (i, (src_range, dst_range)) in self.copies.iter().enumerate() {
+            memory_range.subrange(src_range.offset, src_range.size)?;
+            memory_range.subrange(dst_range.offset, dst_range.size)?;
+            if let Some((other_src, _)) = self
+                .copies
+                .iter()
+                .enumerate()
+                .find(|(j, (other_src, _))| i != *j && dst_range.overlaps(other_src))
+                .map(|(_, copy)| copy)
+            {
+                return Err(RangeError::Overlapping {
+                    first_offset: other_src.offset,
+                    second_offset: dst_range.offset,
+                });
+            }
+        }
+
+        for (src_range, dst_range) in self.iter() {
+            move_within_memory(memory, src_range, dst_range);
+        }
+        Ok(())
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Trivial implementations : Debug, Default, Clone, PartialEq, Eq
+/////////////////////////////////////////////////////////////////////////////
+// Can't be derived because it would require U to implement them as well (even if phantom).
+
+/// Debug
+impl<U> Debug for CopyPlan<U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Default
+impl<U> Default for CopyPlan<U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Clone
+impl<U> Clone for CopyPlan<U> {
+    fn clone(&self) -> Self {
+        Self {
+            copies: self.copies.clone(),
+        }
+    }
+}
+
+/// PartialEq & Eq
+impl<U> PartialEq for CopyPlan<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.copies == other.copies
+    }
+}
+impl<U> Eq for CopyPlan<U> {}
@@ -1,15 +1,86 @@
 use std::ptr;
 
-use super::RangeOf;
+use super::{RangeError, RangeOf};
+
+type Result<T> = std::result::Result<T, RangeError>;
 
 /////////////////////////////////////////////////////////////////////////////
 
 /// Copy size is necessary minimal.
-pub fn copy_within_memory_nonoverlapping<U>(memory: &mut [U], src_range: RangeOf<U>, dst_range: RangeOf<U>) {
+///
+/// Unchecked (in release), see `copy_within_memory_checked`.
+///
+/// # Safety
+///
+/// Ranges (truncated to the copy size) must be in `memory` and must not overlap.
+pub unsafe fn copy_within_memory_nonoverlapping<U: Copy>(
+    memory: &mut [U],
+    src_range: RangeOf<U>,
+    dst_range: RangeOf<U>,
+) {
     let copy_size = usize::min(src_range.size, dst_range.size);
+    debug_assert!(src_range.offset + copy_size <= memory.len());
+    debug_assert!(dst_range.offset + copy_size <= memory.len());
+    debug_assert!(
+        !RangeOf::<U>::new(src_range.offset, copy_size)
+            .overlaps(&RangeOf::new(dst_range.offset, copy_size))
+    );
     unsafe {
         let src = memory.as_ptr().add(src_range.offset);
         let dst = memory.as_mut_ptr().add(dst_range.offset);
         ptr::copy_nonoverlapping(src, dst, copy_size);
     }
-}
\ No newline at end of file
+}
+
+/// Fail if :
+/// - Sizes differ.
+/// - A range is not contained in `memory`.
+/// - Ranges overlap (use `move_within_memory`).
+pub fn copy_within_memory_checked<U: Copy>(
+    memory: &mut [U],
+    src_range: RangeOf<U>,
+    dst_range: RangeOf<U>,
+) -> Result<()> {
+    check_copy(memory.len(), src_range, dst_range)?;
+    if src_range.overlaps(&dst_range) {
+        return Err(RangeError::Overlapping {
+            first_offset: src_range.offset,
+            second_offset: dst_range.offset,
+        });
+    }
+
+    unsafe { copy_within_memory_nonoverlapping(memory, src_range, dst_range) }; // checked above
+    Ok(())
+}
+
+/// Overlap-safe copy (like `memmove`).
+///
+/// Panics if sizes differ or a range is not contained in `memory`.
+pub fn move_within_memory<U: Copy>(memory: &mut [U], src_range: RangeOf<U>, dst_range: RangeOf<U>) {
+    if let Err(error) = check_copy(memory.len(), src_range, dst_range) {
+        panic!("{error}");
+    }
+    memory.copy_within(src_range.to_std_range(), dst_range.offset);
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Utils
+/////////////////////////////////////////////////////////////////////////////
+
+/// Same sizes, both ranges in `[0;bound[`.
+pub(crate) fn check_copy<U>(
+    bound: usize,
+    src_range: RangeOf<U>,
+    dst_range: RangeOf<U>,
+) -> Result<()> {
+    if src_range.size != dst_range.size {
+        return Err(RangeError::SizeMismatch {
+            src_size: src_range.size,
+            dst_size: dst_range.size,
+        });
+    }
+    let memory_range = RangeOf::<U>::new(0, bound);
+    memory_range.subrange(src_range.offset, src_range.size)?;
+    memory_range.subrange(dst_range.offset, dst_range.size)?;
+    Ok(())
+}
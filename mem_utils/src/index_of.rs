@@ -1,22 +1,30 @@
 use std::{
+    cmp::Ordering,
     fmt::{Debug, Display},
+    hash::{Hash, Hasher},
     marker::PhantomData,
 };
 
-use super::RangeOf;
+use super::{RangeError, RangeOf, range_of::units_from_bytes};
 
 /////////////////////////////////////////////////////////////////////////////
-/// Structure
+// Structure
 /////////////////////////////////////////////////////////////////////////////
 
 /// Index aware of its unit.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct IndexOf<Unit = u8> {
     pub index: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
     unit: PhantomData<Unit>,
 }
 
 /////////////////////////////////////////////////////////////////////////////
-/// Implementations
+// Implementations
 /////////////////////////////////////////////////////////////////////////////
 
 /// New
@@ -48,6 +56,17 @@ impl<Unit> IndexOf<Unit> {
     }
 }
 
+/// Cast
+impl<Unit> IndexOf<Unit> {
+    /// Unit of type `T` starting at the same byte.
+    ///
+    /// Fail if :
+    /// - Byte offset is not a multiple of `size_of::<T>()` (or `T` is zero-sized).
+    pub fn try_cast<T>(&self) -> Result<IndexOf<T>, RangeError> {
+        Ok(IndexOf::new(units_from_bytes::<T>(self.byte_offset())?))
+    }
+}
+
 /// Display
 impl<U> Display for IndexOf<U> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -56,9 +75,9 @@ impl<U> Display for IndexOf<U> {
 }
 
 /////////////////////////////////////////////////////////////////////////////
-/// Trivial implementations : Debug, Clone, Copy, PartialEq, Eq
+// Trivial implementations : Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash
 /////////////////////////////////////////////////////////////////////////////
-/// Can't be derived because it would require U to implement them as well (even if phantom).
+// Can't be derived because it would require U to implement them as well (even if phantom).
 
 /// Debug
 impl<U> Debug for IndexOf<U> {
@@ -84,3 +103,22 @@ impl<U> PartialEq for IndexOf<U> {
     }
 }
 impl<U> Eq for IndexOf<U> {}
+
+/// PartialOrd & Ord
+impl<U> PartialOrd for IndexOf<U> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<U> Ord for IndexOf<U> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+/// Hash
+impl<U> Hash for IndexOf<U> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
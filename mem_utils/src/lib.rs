@@ -1,16 +1,34 @@
+mod align;
+mod copy_plan;
+mod copy_within_memory;
 mod index_of;
+mod range_error;
 mod range_of;
-mod copy_within_memory;
+mod range_set;
+mod slice_of;
 
+pub use align::{align_up, unit_align_from_byte_align};
+pub use copy_plan::{CopyPlan, CopyRegion};
+pub use copy_within_memory::{
+    copy_within_memory_checked, copy_within_memory_nonoverlapping, move_within_memory,
+};
 pub use index_of::IndexOf;
+pub use range_error::RangeError;
 pub use range_of::RangeOf;
-pub use copy_within_memory::copy_within_memory_nonoverlapping;
+pub use range_set::RangeSet;
+pub use slice_of::SliceOf;
 
 #[cfg(test)]
 mod tests {
-    use super::RangeOf;
+    use std::collections::{HashMap, HashSet};
+
+    use super::{
+        CopyPlan, CopyRegion, IndexOf, RangeError, RangeOf, RangeSet, SliceOf,
+        copy_within_memory_checked, move_within_memory, unit_align_from_byte_align,
+    };
 
     #[allow(unused)]
+    #[repr(C)]
     struct Test(u32);
 
     #[test]
@@ -27,5 +45,317 @@ mod tests {
 
         assert_eq!(points_range.to_std_range(), (2..6));
         assert_eq!(points_range.as_range_of_bytes().to_std_range(), (8..24));
+
+        assert_eq!(points_range.subrange(1, 3), Ok(RangeOf::new(3, 3)));
+        assert_eq!(
+            points_range.subrange(2, 3),
+            Err(RangeError::OutOfBounds { end: 5, bound: 4 })
+        );
+    }
+
+    #[test]
+    fn alignment() {
+        let points_range: RangeOf<Test> = RangeOf::new(6, 4);
+        assert!(points_range.is_aligned(3));
+        assert!(!points_range.is_aligned(4));
+        assert!(points_range.is_byte_aligned(8));
+        assert_eq!(points_range.align_up(4), RangeOf::new(8, 4));
+        assert_eq!(points_range.align_up(2), points_range);
+
+        // 16 bytes => 4 units of 4 bytes, 16 units of 1 byte, 4 units of 12 bytes
+        assert_eq!(unit_align_from_byte_align::<Test>(16), Ok(4));
+        assert_eq!(unit_align_from_byte_align::<u8>(16), Ok(16));
+        assert_eq!(unit_align_from_byte_align::<[u32; 3]>(16), Ok(4));
+        assert_eq!(unit_align_from_byte_align::<[u32; 8]>(16), Ok(1));
+
+        // invalid byte alignments
+        assert_eq!(
+            unit_align_from_byte_align::<Test>(0),
+            Err(RangeError::InvalidAlignment { align: 0 })
+        );
+        assert_eq!(
+            unit_align_from_byte_align::<Test>(12),
+            Err(RangeError::InvalidAlignment { align: 12 })
+        );
+    }
+
+    #[test]
+    fn interval_algebra() {
+        let range: RangeOf = RangeOf::new(2, 6); // [2;8[
+
+        // intersection & overlap
+        assert_eq!(
+            range.intersection(&RangeOf::new(6, 4)),
+            Some(RangeOf::new(6, 2))
+        );
+        assert_eq!(range.intersection(&RangeOf::new(8, 2)), None);
+        assert!(!range.overlaps(&RangeOf::new(8, 2)));
+        assert!(range.is_adjacent_to(&RangeOf::new(8, 2)));
+        assert!(range.contains(7) && !range.contains(8));
+
+        // union (merge) & hull
+        assert_eq!(range.union(&RangeOf::new(8, 2)), Some(RangeOf::new(2, 8)));
+        assert_eq!(range.union(&RangeOf::new(9, 2)), None);
+        assert_eq!(range.union(&RangeOf::new(20, 0)), Some(range));
+        assert_eq!(range.hull(&RangeOf::new(9, 2)), RangeOf::new(2, 9));
+
+        // difference & split
+        assert_eq!(
+            range.difference(&RangeOf::new(4, 2)),
+            (Some(RangeOf::new(2, 2)), Some(RangeOf::new(6, 2)))
+        );
+        assert_eq!(
+            range.difference(&RangeOf::new(0, 4)),
+            (None, Some(RangeOf::new(4, 4)))
+        );
+        assert_eq!(range.difference(&RangeOf::new(0, 10)), (None, None));
+        assert_eq!(
+            range.split_at(2),
+            Ok((RangeOf::new(2, 2), RangeOf::new(4, 4)))
+        );
+        assert_eq!(
+            range.split_at(7),
+            Err(RangeError::OutOfBounds { end: 7, bound: 6 })
+        );
+    }
+
+    #[test]
+    fn range_set() {
+        // normalized : overlapping & adjacent ranges merged
+        let mut set: RangeSet = [RangeOf::new(0, 2), RangeOf::new(4, 2), RangeOf::new(10, 2)]
+            .into_iter()
+            .collect();
+        set.insert(RangeOf::new(2, 2));
+        set.insert(RangeOf::new(11, 4));
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![RangeOf::new(0, 6), RangeOf::new(10, 5)]
+        );
+        assert_eq!((set.len(), set.size()), (2, 11));
+
+        // queries
+        assert!(set.contains(5) && !set.contains(6));
+        assert!(set.contains_range(RangeOf::new(1, 5)));
+        assert!(!set.contains_range(RangeOf::new(4, 7)));
+        assert!(set.overlaps(RangeOf::new(5, 6)) && !set.overlaps(RangeOf::new(6, 4)));
+        assert_eq!(
+            set.iter_overlapping(RangeOf::new(5, 6)).collect::<Vec<_>>(),
+            vec![RangeOf::new(0, 6), RangeOf::new(10, 5)]
+        );
+        assert_eq!(set.first_fit(6), Some(RangeOf::new(0, 6)));
+        assert_eq!(set.first_fit(7), None);
+
+        // remove cuts ranges
+        set.remove(RangeOf::new(2, 10));
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![RangeOf::new(0, 2), RangeOf::new(12, 3)]
+        );
+
+        // set operations
+        let other: RangeSet = [RangeOf::new(1, 12)].into_iter().collect();
+        assert_eq!(
+            set.union(&other).iter().collect::<Vec<_>>(),
+            vec![RangeOf::new(0, 15)]
+        );
+        assert_eq!(
+            set.intersection(&other).iter().collect::<Vec<_>>(),
+            vec![RangeOf::new(1, 1), RangeOf::new(12, 1)]
+        );
+        assert_eq!(
+            set.difference(&other).iter().collect::<Vec<_>>(),
+            vec![RangeOf::new(0, 1), RangeOf::new(13, 2)]
+        );
+        set.clear();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn slice_of() {
+        let mut memory: Vec<Test> = (0..6).map(Test).collect();
+        let range: RangeOf<Test> = RangeOf::new(2, 3);
+
+        // indexing (through `Vec` deref)
+        memory[IndexOf::new(1)] = Test(10);
+        assert_eq!(memory[range].len(), 3);
+        memory[range][0] = Test(20);
+        assert_eq!(memory[IndexOf::<Test>::new(2)].0, 20);
+
+        // checked
+        assert!(memory.get_range(range).is_some());
+        assert!(memory.get_range(RangeOf::new(4, 3)).is_none());
+        assert_eq!(
+            memory.get_index(IndexOf::new(1)).map(|test| test.0),
+            Some(10)
+        );
+        assert!(memory.get_index_mut(IndexOf::new(6)).is_none());
+
+        // bytes (`Test` has no padding)
+        let bytes = unsafe { memory.as_bytes() };
+        assert_eq!(bytes.len(), 6 * 4);
+        assert_eq!(bytes[range.as_range_of_bytes()][..4], 20u32.to_ne_bytes());
+        let bytes = unsafe { memory.as_bytes_mut() };
+        bytes[range.as_range_of_bytes()][4..8].copy_from_slice(&30u32.to_ne_bytes());
+        assert_eq!(memory[IndexOf::<Test>::new(3)].0, 30);
+    }
+
+    #[test]
+    fn copies() {
+        // move (overlapping)
+        let mut memory: Vec<u32> = (0..8).collect();
+        move_within_memory(&mut memory, RangeOf::new(0, 4), RangeOf::new(2, 4));
+        assert_eq!(memory, [0, 1, 0, 1, 2, 3, 6, 7]);
+
+        // checked
+        assert_eq!(
+            copy_within_memory_checked(&mut memory, RangeOf::new(0, 4), RangeOf::new(2, 4)),
+            Err(RangeError::Overlapping {
+                first_offset: 0,
+                second_offset: 2
+            })
+        );
+        assert_eq!(
+            copy_within_memory_checked(&mut memory, RangeOf::new(0, 2), RangeOf::new(7, 2)),
+            Err(RangeError::OutOfBounds { end: 9, bound: 8 })
+        );
+        assert_eq!(
+            copy_within_memory_checked(&mut memory, RangeOf::new(0, 2), RangeOf::new(4, 3)),
+            Err(RangeError::SizeMismatch {
+                src_size: 2,
+                dst_size: 3
+            })
+        );
+        assert_eq!(
+            copy_within_memory_checked(&mut memory, RangeOf::new(6, 2), RangeOf::new(0, 2)),
+            Ok(())
+        );
+        assert_eq!(memory, [6, 7, 0, 1, 2, 3, 6, 7]);
+
+        // plan : adjacent copies coalesced, in any push order
+        let mut plan: CopyPlan<u32> = CopyPlan::new();
+        plan.push(RangeOf::new(4, 2), RangeOf::new(12, 2)).unwrap();
+        plan.push(RangeOf::new(0, 2), RangeOf::new(8, 2)).unwrap();
+        plan.push(RangeOf::new(2, 2), RangeOf::new(10, 2)).unwrap();
+        plan.push(RangeOf::new(6, 1), RangeOf::new(0, 1)).unwrap();
+        assert_eq!(plan.len(), 2);
+        assert_eq!(
+            plan.byte_regions().collect::<Vec<_>>(),
+            [
+                CopyRegion {
+                    src_offset: 0,
+                    dst_offset: 8 * 4,
+                    size: 6 * 4
+                },
+                CopyRegion {
+                    src_offset: 6 * 4,
+                    dst_offset: 0,
+                    size: 4
+                },
+            ]
+        );
+        assert_eq!(
+            plan.push(RangeOf::new(7, 2), RangeOf::new(13, 2)),
+            Err(RangeError::Overlapping {
+                first_offset: 8,
+                second_offset: 13
+            })
+        );
+
+        // host copies
+        let src: Vec<u32> = (0..8).collect();
+        let mut dst = vec![0; 16];
+        plan.copy(&src, &mut dst).unwrap();
+        assert_eq!(dst[8..14], [0, 1, 2, 3, 4, 5]);
+        assert_eq!(dst[0], 6);
+        assert!(plan.copy(&src, &mut [0; 8]).is_err());
+
+        // within : a copy overwrites the source of another
+        let mut memory: Vec<u32> = (0..16).collect();
+        assert!(plan.copy_within(&mut memory).is_err());
+        let mut plan: CopyPlan<u32> = CopyPlan::new();
+        plan.push(RangeOf::new(0, 4), RangeOf::new(2, 4)).unwrap();
+        plan.push(RangeOf::new(8, 2), RangeOf::new(12, 2)).unwrap();
+        plan.copy_within(&mut memory).unwrap();
+        assert_eq!(memory[..6], [0, 1, 0, 1, 2, 3]);
+        assert_eq!(memory[12..14], [8, 9]);
+    }
+
+    #[test]
+    fn casts() {
+        // bytes => units
+        let bytes_range: RangeOf = RangeOf::new(8, 16);
+        assert_eq!(bytes_range.try_cast::<Test>(), Ok(RangeOf::new(2, 4)));
+        assert_eq!(
+            RangeOf::<u8>::new(6, 16).try_cast::<Test>(),
+            Err(RangeError::Misaligned {
+                bytes: 6,
+                unit_size: 4
+            })
+        );
+        assert_eq!(
+            RangeOf::<u8>::new(8, 10).try_cast::<Test>(),
+            Err(RangeError::Misaligned {
+                bytes: 10,
+                unit_size: 4
+            })
+        );
+
+        // units => units (round trip)
+        let points_range: RangeOf<[u32; 3]> = RangeOf::new(2, 4);
+        let range = points_range.try_cast::<Test>().unwrap();
+        assert_eq!(range, RangeOf::new(6, 12));
+        assert_eq!(range.try_cast::<[u32; 3]>(), Ok(points_range));
+        assert!(RangeOf::<Test>::new(1, 3).try_cast::<[u32; 3]>().is_err());
+        assert!(bytes_range.try_cast::<()>().is_err());
+
+        // indices
+        assert_eq!(
+            IndexOf::<u8>::new(12).try_cast::<Test>(),
+            Ok(IndexOf::new(3))
+        );
+        assert_eq!(
+            IndexOf::<Test>::new(3).try_cast::<[u32; 3]>(),
+            Ok(IndexOf::new(1))
+        );
+        assert_eq!(
+            IndexOf::<Test>::new(2).try_cast::<[u32; 3]>(),
+            Err(RangeError::Misaligned {
+                bytes: 8,
+                unit_size: 12
+            })
+        );
+    }
+
+    #[test]
+    fn order_and_hash() {
+        // `Test` is neither `Ord` nor `Hash`
+        let mut ranges: Vec<RangeOf<Test>> =
+            vec![RangeOf::new(4, 2), RangeOf::new(0, 3), RangeOf::new(0, 1)];
+        ranges.sort();
+        assert_eq!(
+            ranges,
+            [RangeOf::new(0, 1), RangeOf::new(0, 3), RangeOf::new(4, 2)]
+        );
+        assert!(IndexOf::<Test>::new(1) < IndexOf::new(2));
+
+        let mut names: HashMap<IndexOf<Test>, &str> = HashMap::new();
+        names.insert(IndexOf::new(3), "cube");
+        assert_eq!(names.get(&IndexOf::new(3)), Some(&"cube"));
+        let ranges: HashSet<RangeOf<Test>> = ranges.into_iter().collect();
+        assert!(ranges.contains(&RangeOf::new(0, 3)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let range: RangeOf<Test> = RangeOf::new(2, 4);
+        let json = serde_json::to_string(&range).unwrap();
+        assert_eq!(json, r#"{"offset":2,"size":4}"#);
+        assert_eq!(serde_json::from_str::<RangeOf<Test>>(&json).unwrap(), range);
+
+        let index: IndexOf<Test> = IndexOf::new(7);
+        let json = serde_json::to_string(&index).unwrap();
+        assert_eq!(json, r#"{"index":7}"#);
+        assert_eq!(serde_json::from_str::<IndexOf<Test>>(&json).unwrap(), index);
     }
 }
@@ -0,0 +1,62 @@
+use std::fmt::Display;
+
+/////////////////////////////////////////////////////////////////////////////
+// Structure
+/////////////////////////////////////////////////////////////////////////////
+
+/// Failures of range operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeError {
+    /// Range would end at `end`, past `bound`.
+    OutOfBounds { end: usize, bound: usize },
+    /// Ranges at `first_offset` & `second_offset` share units.
+    Overlapping {
+        first_offset: usize,
+        second_offset: usize,
+    },
+    /// Source & destination of a copy differ in size.
+    SizeMismatch { src_size: usize, dst_size: usize },
+    /// Byte offset or size `bytes` isn't a multiple of the target `unit_size` (cast).
+    Misaligned { bytes: usize, unit_size: usize },
+    /// Alignment `align` is not a power of two.
+    InvalidAlignment { align: usize },
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////////
+
+/// Display
+impl Display for RangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RangeError::OutOfBounds { end, bound } => {
+                write!(f, "range end {end} is out of bounds (bound {bound})")
+            }
+            RangeError::Overlapping {
+                first_offset,
+                second_offset,
+            } => {
+                write!(f, "ranges at {first_offset} and {second_offset} overlap")
+            }
+            RangeError::SizeMismatch { src_size, dst_size } => {
+                write!(
+                    f,
+                    "source size {src_size} differs from destination size {dst_size}"
+                )
+            }
+            RangeError::Misaligned { bytes, unit_size } => {
+                write!(
+                    f,
+                    "{bytes} bytes is not a multiple of the unit size {unit_size}"
+                )
+            }
+            RangeError::InvalidAlignment { align } => {
+                write!(f, "alignment {align} is not a power of two")
+            }
+        }
+    }
+}
+
+/// Error
+impl std::error::Error for RangeError {}
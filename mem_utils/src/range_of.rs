@@ -1,24 +1,33 @@
 use std::{
+    cmp::Ordering,
     fmt::{Debug, Display},
+    hash::{Hash, Hasher},
     marker::PhantomData,
 };
 
-type Error = Box<dyn std::error::Error>;
-type Result<T> = std::result::Result<T, Error>;
+use super::{RangeError, align_up};
+
+type Result<T> = std::result::Result<T, RangeError>;
 
 /////////////////////////////////////////////////////////////////////////////
-/// Structure
+// Structure
 /////////////////////////////////////////////////////////////////////////////
 
 /// Range aware of its unit.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct RangeOf<Unit = u8> {
     pub offset: usize,
     pub size: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) unit: PhantomData<Unit>,
 }
 
 /////////////////////////////////////////////////////////////////////////////
-/// Implementations
+// Implementations
 /////////////////////////////////////////////////////////////////////////////
 
 /// New
@@ -44,7 +53,10 @@ impl<Unit> RangeOf<Unit> {
         if inner_offset + size <= self.size {
             Ok(Self::new(self.offset + inner_offset, size))
         } else {
-            Err("sub range too big".into())
+            Err(RangeError::OutOfBounds {
+                end: inner_offset + size,
+                bound: self.size,
+            })
         }
     }
 
@@ -58,6 +70,97 @@ impl<Unit> RangeOf<Unit> {
     }
 }
 
+/// Interval algebra
+///
+/// Returned `Some(range)` are never empty (size 0).
+impl<Unit> RangeOf<Unit> {
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        self.offset <= offset && offset < self.end()
+    }
+
+    /// Share at least one unit.
+    pub fn overlaps(&self, range: &Self) -> bool {
+        self.intersection(range).is_some()
+    }
+
+    /// One ends where the other starts.
+    pub fn is_adjacent_to(&self, range: &Self) -> bool {
+        self.end() == range.offset || range.end() == self.offset
+    }
+
+    pub fn intersection(&self, range: &Self) -> Option<Self> {
+        let offset = usize::max(self.offset, range.offset);
+        let end = usize::min(self.end(), range.end());
+        (offset < end).then(|| Self::new(offset, end - offset))
+    }
+
+    /// Merge of overlapping or adjacent ranges, `None` if there's a gap between them (see `RangeSet`).
+    pub fn union(&self, range: &Self) -> Option<Self> {
+        if self.is_empty() || range.is_empty() {
+            return [self, range]
+                .into_iter()
+                .find(|range| !range.is_empty())
+                .copied();
+        }
+        if !self.overlaps(range) && !self.is_adjacent_to(range) {
+            return None;
+        }
+        Some(self.hull(range))
+    }
+
+    /// Smallest range covering both.
+    pub fn hull(&self, range: &Self) -> Self {
+        let offset = usize::min(self.offset, range.offset);
+        let end = usize::max(self.end(), range.end());
+        Self::new(offset, end - offset)
+    }
+
+    /// Parts of `self` before & after `range`.
+    pub fn difference(&self, range: &Self) -> (Option<Self>, Option<Self>) {
+        let Some(intersection) = self.intersection(range) else {
+            return (Some(*self).filter(|range| !range.is_empty()), None);
+        };
+        let before = Self::new(self.offset, intersection.offset - self.offset);
+        let after = Self::new(intersection.end(), self.end() - intersection.end());
+        (
+            Some(before).filter(|range| !range.is_empty()),
+            Some(after).filter(|range| !range.is_empty()),
+        )
+    }
+
+    /// `self` cut in `[offset;offset+inner_offset[` & the rest.
+    ///
+    /// Fail if :
+    /// - `inner_offset` > `self.size`.
+    pub fn split_at(&self, inner_offset: usize) -> Result<(Self, Self)> {
+        let before = self.subrange(0, inner_offset)?;
+        let after = Self::new(before.end(), self.size - inner_offset);
+        Ok((before, after))
+    }
+}
+
+/// Alignment
+impl<Unit> RangeOf<Unit> {
+    /// Offset is a multiple of `align` (in units).
+    pub fn is_aligned(&self, align: usize) -> bool {
+        self.offset.is_multiple_of(align)
+    }
+
+    /// Byte offset is a multiple of `byte_align`.
+    pub fn is_byte_aligned(&self, byte_align: usize) -> bool {
+        self.byte_offset().is_multiple_of(byte_align)
+    }
+
+    /// Same size, offset rounded up to the next multiple of `align` (in units).
+    pub fn align_up(&self, align: usize) -> Self {
+        Self::new(align_up(self.offset, align), self.size)
+    }
+}
+
 /// Byte
 impl<Unit> RangeOf<Unit> {
     pub fn byte_offset(&self) -> usize {
@@ -77,6 +180,20 @@ impl<Unit> RangeOf<Unit> {
     }
 }
 
+/// Cast
+impl<Unit> RangeOf<Unit> {
+    /// Same bytes, in units of `T` (e.g. a byte range from Vulkan or a file as a range of vertices).
+    ///
+    /// Fail if :
+    /// - Byte offset or byte size is not a multiple of `size_of::<T>()` (or `T` is zero-sized).
+    pub fn try_cast<T>(&self) -> Result<RangeOf<T>> {
+        Ok(RangeOf::new(
+            units_from_bytes::<T>(self.byte_offset())?,
+            units_from_bytes::<T>(self.byte_size())?,
+        ))
+    }
+}
+
 /// Display
 impl<U> Display for RangeOf<U> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -91,9 +208,23 @@ impl<U> Display for RangeOf<U> {
 }
 
 /////////////////////////////////////////////////////////////////////////////
-/// Trivial implementations : Debug, Clone, Copy, PartialEq, Eq
+// Utils
+/////////////////////////////////////////////////////////////////////////////
+
+/// Fail if `bytes` is not a multiple of `size_of::<T>()`.
+pub(crate) fn units_from_bytes<T>(bytes: usize) -> Result<usize> {
+    let unit_size = size_of::<T>();
+    if unit_size != 0 && bytes.is_multiple_of(unit_size) {
+        Ok(bytes / unit_size)
+    } else {
+        Err(RangeError::Misaligned { bytes, unit_size })
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Trivial implementations : Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash
 /////////////////////////////////////////////////////////////////////////////
-/// Can't be derived because it would require U to implement them as well (even if phantom).
+// Can't be derived because it would require U to implement them as well (even if phantom).
 
 /// Debug
 impl<U> Debug for RangeOf<U> {
@@ -121,3 +252,23 @@ impl<U> PartialEq for RangeOf<U> {
     }
 }
 impl<U> Eq for RangeOf<U> {}
+
+/// PartialOrd & Ord (by offset, then size)
+impl<U> PartialOrd for RangeOf<U> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<U> Ord for RangeOf<U> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.offset, self.size).cmp(&(other.offset, other.size))
+    }
+}
+
+/// Hash
+impl<U> Hash for RangeOf<U> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.offset.hash(state);
+        self.size.hash(state);
+    }
+}
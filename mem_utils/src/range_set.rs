@@ -0,0 +1,225 @@
+use std::{collections::BTreeMap, fmt::Debug, marker::PhantomData};
+
+use super::RangeOf;
+
+/////////////////////////////////////////////////////////////////////////////
+// Structure
+/////////////////////////////////////////////////////////////////////////////
+
+/// Set of units, stored as sorted disjoint ranges (dirty regions, free space, ...).
+///
+/// Normalized : ranges are non empty, and neither overlap nor touch (adjacent ranges are merged).
+pub struct RangeSet<Unit = u8> {
+    ends: BTreeMap<usize, usize>, // offset => end
+    unit: PhantomData<Unit>,
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////////
+
+/// New
+impl<Unit> RangeSet<Unit> {
+    pub fn new() -> Self {
+        Self {
+            ends: BTreeMap::new(),
+            unit: PhantomData,
+        }
+    }
+}
+
+/// Query
+impl<Unit> RangeSet<Unit> {
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Count of disjoint ranges.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Count of units.
+    pub fn size(&self) -> usize {
+        self.ends.iter().map(|(offset, end)| end - offset).sum()
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        self.range_before(offset + 1)
+            .is_some_and(|range| range.contains(offset))
+    }
+
+    /// All units of `range` are in the set.
+    pub fn contains_range(&self, range: RangeOf<Unit>) -> bool {
+        range.is_empty()
+            || self
+                .range_before(range.offset + 1)
+                .is_some_and(|set_range| range.is_subrange_of(&set_range))
+    }
+
+    /// At least one unit of `range` is in the set.
+    pub fn overlaps(&self, range: RangeOf<Unit>) -> bool {
+        self.iter_overlapping(range).next().is_some()
+    }
+
+    /// Sorted by offset.
+    pub fn iter(&self) -> impl Iterator<Item = RangeOf<Unit>> + '_ {
+        self.ends
+            .iter()
+            .map(|(offset, end)| RangeOf::new(*offset, end - offset))
+    }
+
+    /// Ranges of the set sharing units with `range` (not clipped), sorted by offset.
+    pub fn iter_overlapping(&self, range: RangeOf<Unit>) -> impl Iterator<Item = RangeOf<Unit>> {
+        // the range starting before `range` might overlap it, then all ranges starting inside it
+        let first_offset = self
+            .range_before(range.offset + 1)
+            .filter(|set_range| set_range.end() > range.offset)
+            .map_or(range.offset, |set_range| set_range.offset);
+        self.ends
+            .range(first_offset..range.end())
+            .map(|(offset, end)| RangeOf::new(*offset, end - offset))
+            .filter(move |set_range| set_range.overlaps(&range))
+    }
+
+    /// Lowest range of at least `size` units (free space bookkeeping).
+    pub fn first_fit(&self, size: usize) -> Option<RangeOf<Unit>> {
+        self.iter().find(|range| range.size >= size)
+    }
+
+    /// Last range starting before `offset`.
+    fn range_before(&self, offset: usize) -> Option<RangeOf<Unit>> {
+        self.ends
+            .range(..offset)
+            .next_back()
+            .map(|(offset, end)| RangeOf::new(*offset, end - offset))
+    }
+}
+
+/// Insert & Remove
+impl<Unit> RangeSet<Unit> {
+    /// Merged with overlapping & adjacent ranges.
+    pub fn insert(&mut self, range: RangeOf<Unit>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // absorb overlapping & adjacent ranges (touching `range` ends included)
+        let mut merged = range;
+        let touching: Vec<RangeOf<Unit>> = self
+            .iter_overlapping(RangeOf::new(
+                range.offset.saturating_sub(1),
+                range.end() + 1 - range.offset.saturating_sub(1),
+            ))
+            .collect();
+        for set_range in touching {
+            self.ends.remove(&set_range.offset);
+            merged = merged.hull(&set_range);
+        }
+
+        self.ends.insert(merged.offset, merged.end());
+    }
+
+    /// Overlapping ranges are cut.
+    pub fn remove(&mut self, range: RangeOf<Unit>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let overlapping: Vec<RangeOf<Unit>> = self.iter_overlapping(range).collect();
+        for set_range in overlapping {
+            self.ends.remove(&set_range.offset);
+            let (before, after) = set_range.difference(&range);
+            for part in [before, after].into_iter().flatten() {
+                self.ends.insert(part.offset, part.end());
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.ends.clear();
+    }
+}
+
+/// Set operations
+impl<Unit> RangeSet<Unit> {
+    pub fn union(&self, set: &Self) -> Self {
+        let mut union = self.clone();
+        for range in set.iter() {
+            union.insert(range);
+        }
+        union
+    }
+
+    pub fn intersection(&self, set: &Self) -> Self {
+        let mut intersection = Self::new();
+        for range in self.iter() {
+            for set_range in set.iter_overlapping(range) {
+                // UNWRAP: overlapping
+                intersection.insert(range.intersection(&set_range).unwrap());
+            }
+        }
+        intersection
+    }
+
+    pub fn difference(&self, set: &Self) -> Self {
+        let mut difference = self.clone();
+        for range in set.iter() {
+            difference.remove(range);
+        }
+        difference
+    }
+}
+
+/// FromIterator & Extend
+impl<Unit> FromIterator<RangeOf<Unit>> for RangeSet<Unit> {
+    fn from_iter<I: IntoIterator<Item = RangeOf<Unit>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+impl<Unit> Extend<RangeOf<Unit>> for RangeSet<Unit> {
+    fn extend<I: IntoIterator<Item = RangeOf<Unit>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Trivial implementations : Debug, Default, Clone, PartialEq, Eq
+/////////////////////////////////////////////////////////////////////////////
+// Can't be derived because it would require U to implement them as well (even if phantom).
+
+/// Debug
+impl<U> Debug for RangeSet<U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Default
+impl<U> Default for RangeSet<U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Clone
+impl<U> Clone for RangeSet<U> {
+    fn clone(&self) -> Self {
+        Self {
+            ends: self.ends.clone(),
+            unit: PhantomData,
+        }
+    }
+}
+
+/// PartialEq & Eq
+impl<U> PartialEq for RangeSet<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ends == other.ends
+    }
+}
+impl<U> Eq for RangeSet<U> {}
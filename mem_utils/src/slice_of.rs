@@ -0,0 +1,114 @@
+use std::ops::{Index, IndexMut};
+
+use super::{IndexOf, RangeOf};
+
+/////////////////////////////////////////////////////////////////////////////
+// Trait
+/////////////////////////////////////////////////////////////////////////////
+
+/// Checked access to a slice of `Unit` by unit-aware ranges & indices.
+///
+/// Panicking access is plain indexing : `memory[range]`, `memory[index]` (units must match), for slices & `Vec`s.
+pub trait SliceOf<Unit> {
+    fn get_range(&self, range: RangeOf<Unit>) -> Option<&[Unit]>;
+    fn get_range_mut(&mut self, range: RangeOf<Unit>) -> Option<&mut [Unit]>;
+    fn get_index(&self, index: IndexOf<Unit>) -> Option<&Unit>;
+    fn get_index_mut(&mut self, index: IndexOf<Unit>) -> Option<&mut Unit>;
+
+    /// Whole slice as bytes, to be indexed by `range.as_range_of_bytes()`.
+    ///
+    /// # Safety
+    ///
+    /// `Unit` must have no padding bytes (uninitialized).
+    unsafe fn as_bytes(&self) -> &[u8];
+
+    /// # Safety
+    ///
+    /// Same as `as_bytes`, and written bytes must form valid `Unit`s.
+    unsafe fn as_bytes_mut(&mut self) -> &mut [u8];
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////////
+
+/// Checked
+impl<U> SliceOf<U> for [U] {
+    fn get_range(&self, range: RangeOf<U>) -> Option<&[U]> {
+        self.get(range.to_std_range())
+    }
+
+    fn get_range_mut(&mut self, range: RangeOf<U>) -> Option<&mut [U]> {
+        self.get_mut(range.to_std_range())
+    }
+
+    fn get_index(&self, index: IndexOf<U>) -> Option<&U> {
+        self.get(index.index)
+    }
+
+    fn get_index_mut(&mut self, index: IndexOf<U>) -> Option<&mut U> {
+        self.get_mut(index.index)
+    }
+
+    unsafe fn as_bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.as_ptr() as *const u8, size_of_val(self)) }
+    }
+
+    unsafe fn as_bytes_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.as_mut_ptr() as *mut u8, size_of_val(self)) }
+    }
+}
+
+/// Index by range
+impl<U> Index<RangeOf<U>> for [U] {
+    type Output = [U];
+
+    fn index(&self, range: RangeOf<U>) -> &[U] {
+        &self[range.to_std_range()]
+    }
+}
+impl<U> IndexMut<RangeOf<U>> for [U] {
+    fn index_mut(&mut self, range: RangeOf<U>) -> &mut [U] {
+        &mut self[range.to_std_range()]
+    }
+}
+
+/// Index by index
+impl<U> Index<IndexOf<U>> for [U] {
+    type Output = U;
+
+    fn index(&self, index: IndexOf<U>) -> &U {
+        &self[index.index]
+    }
+}
+impl<U> IndexMut<IndexOf<U>> for [U] {
+    fn index_mut(&mut self, index: IndexOf<U>) -> &mut U {
+        &mut self[index.index]
+    }
+}
+
+/// Index `Vec` (not reached by deref, `Vec` indexing is generic)
+impl<U> Index<RangeOf<U>> for Vec<U> {
+    type Output = [U];
+
+    fn index(&self, range: RangeOf<U>) -> &[U] {
+        &self.as_slice()[range]
+    }
+}
+impl<U> IndexMut<RangeOf<U>> for Vec<U> {
+    fn index_mut(&mut self, range: RangeOf<U>) -> &mut [U] {
+        &mut self.as_mut_slice()[range]
+    }
+}
+impl<U> Index<IndexOf<U>> for Vec<U> {
+    type Output = U;
+
+    fn index(&self, index: IndexOf<U>) -> &U {
+        &self.as_slice()[index]
+    }
+}
+impl<U> IndexMut<IndexOf<U>> for Vec<U> {
+    fn index_mut(&mut self, index: IndexOf<U>) -> &mut U {
+        &mut self.as_mut_slice()[index]
+    }
+}
@@ -0,0 +1,74 @@
+//! Shared by benchmarks : configurations & `tetra`-like sizes.
+
+#![allow(dead_code)] // each benchmark uses a part of it
+
+use mem_utils::RangeOf;
+use suballocation::{
+    harness::Rng,
+    segregated_slab::{FallbackPolicy, SegregatedSlabConfiguration, SlabGroupConfiguration},
+};
+
+/// Smallest & biggest shapes (vertices per cloud in `tetra`).
+pub const MIN_SHAPE_SIZE: usize = 4;
+pub const MAX_SHAPE_SIZE: usize = 256;
+
+/////////////////////////////////////////////////////////////////////////////
+// Fonctions
+/////////////////////////////////////////////////////////////////////////////
+
+/// Log-uniform in `MIN_SHAPE_SIZE..=MAX_SHAPE_SIZE` (as many shapes of 4-8 vertices as of 128-256 vertices).
+pub fn shape_size(rng: &mut Rng) -> usize {
+    let (min, max) = (MIN_SHAPE_SIZE as f64, MAX_SHAPE_SIZE as f64);
+    let exponent = min.log2() + (max.log2() - min.log2()) * rng.next_f64();
+    (exponent.exp2() as usize).clamp(MIN_SHAPE_SIZE, MAX_SHAPE_SIZE)
+}
+
+/// Shape sizes from a deterministic `seed`, so that runs are comparable.
+pub fn shape_sizes(seed: u64, count: usize) -> Vec<usize> {
+    let mut rng = Rng::new(seed);
+    (0..count).map(|_| shape_size(&mut rng)).collect()
+}
+
+pub type ConfigurationFrom = Box<dyn Fn() -> SegregatedSlabConfiguration<u32>>;
+
+/// Configurations compared, over `0..range_size` (multiple of 512).
+pub fn configurations(range_size: usize) -> Vec<(&'static str, ConfigurationFrom)> {
+    let range = RangeOf::new(0, range_size);
+    let pot = move |fallback_policy| -> ConfigurationFrom {
+        Box::new(move || {
+            SegregatedSlabConfiguration::pot(range, MAX_SHAPE_SIZE, MIN_SHAPE_SIZE)
+                .unwrap()
+                .with_fallback_policy(fallback_policy)
+        })
+    };
+    vec![
+        ("pot", pot(FallbackPolicy::Strict)),
+        (
+            "pot_next_larger_class",
+            pot(FallbackPolicy::NextLargerClass),
+        ),
+        ("pot_any_larger_class", pot(FallbackPolicy::AnyLargerClass)),
+        (
+            "multi_slab",
+            Box::new(move || {
+                // half the range in small slabs for small classes
+                SegregatedSlabConfiguration::multi_slab(
+                    range,
+                    vec![
+                        SlabGroupConfiguration {
+                            slab: 64,
+                            slab_count: range_size / 2 / 64,
+                            classes: vec![4, 8, 16, 32, 64],
+                        },
+                        SlabGroupConfiguration {
+                            slab: 256,
+                            slab_count: range_size / 2 / 256,
+                            classes: vec![128, 256],
+                        },
+                    ],
+                )
+                .unwrap()
+            }),
+        ),
+    ]
+}
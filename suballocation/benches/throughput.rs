@@ -0,0 +1,129 @@
+//! Allocate/deallocate/reallocate throughput.
+//!
+//! Run "cargo bench --bench throughput".
+//!
+//! Sizes follow `tetra` shapes : 4 to 256 vertices, small shapes more frequent.
+
+mod common;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use mem_utils::{IndexOf, RangeOf};
+use suballocation::{
+    ArrayOfUnitSuballocation, UnitRunSuballocation, UnitSuballocation,
+    segregated_slab::SegregatedSlabSuballocator, table::TableSuballocator,
+};
+
+use common::{configurations, shape_sizes};
+
+const RANGE_SIZE: usize = 16384;
+const ALLOCATION_COUNT: usize = 64; // fits in `RANGE_SIZE` with any configuration
+
+/////////////////////////////////////////////////////////////////////////////
+// Benchmarks
+/////////////////////////////////////////////////////////////////////////////
+
+fn segregated_slab(c: &mut Criterion) {
+    let sizes = shape_sizes(0, ALLOCATION_COUNT);
+    let new_sizes = shape_sizes(1, ALLOCATION_COUNT);
+    let mut group = c.benchmark_group("segregated_slab");
+    group.throughput(Throughput::Elements(ALLOCATION_COUNT as u64));
+
+    for (name, configuration_from) in configurations(RANGE_SIZE) {
+        // allocate all, then deallocate all
+        group.bench_function(BenchmarkId::new("allocate_deallocate", name), |b| {
+            b.iter_batched_ref(
+                || {
+                    SegregatedSlabSuballocator::<u32>::new_from_configuration(configuration_from())
+                        .unwrap()
+                },
+                |allocator| {
+                    let ranges: Vec<_> = sizes
+                        .iter()
+                        .map(|size| allocator.allocate(*size).unwrap())
+                        .collect();
+                    for range in ranges {
+                        allocator.deallocate(range).unwrap();
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+
+        // reallocate all to other sizes
+        group.bench_function(BenchmarkId::new("reallocate", name), |b| {
+            b.iter_batched_ref(
+                || {
+                    let mut allocator = SegregatedSlabSuballocator::<u32>::new_from_configuration(
+                        configuration_from(),
+                    )
+                    .unwrap();
+                    let ranges: Vec<_> = sizes
+                        .iter()
+                        .map(|size| allocator.allocate(*size).unwrap())
+                        .collect();
+                    (allocator, ranges)
+                },
+                |(allocator, ranges)| {
+                    for (range, new_size) in ranges.iter_mut().zip(&new_sizes) {
+                        // failure counted by `workload`, only cost is measured here
+                        if let Ok(new_range) = allocator.reallocate(*range, *new_size) {
+                            *range = new_range;
+                        }
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn table(c: &mut Criterion) {
+    let mut group = c.benchmark_group("table");
+    group.throughput(Throughput::Elements(RANGE_SIZE as u64));
+
+    // allocate all, then deallocate all
+    group.bench_function("allocate_deallocate", |b| {
+        b.iter_batched_ref(
+            || TableSuballocator::<u32>::new(RangeOf::new(0, RANGE_SIZE)),
+            |allocator| {
+                let indices: Vec<IndexOf<u32>> = (0..RANGE_SIZE)
+                    .map(|_| allocator.allocate().unwrap())
+                    .collect();
+                for index in indices {
+                    allocator.deallocate(index).unwrap();
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    // runs in a table whose first half is fragmented (every other slot allocated)
+    group.throughput(Throughput::Elements(ALLOCATION_COUNT as u64));
+    group.bench_function("allocate_run", |b| {
+        b.iter_batched_ref(
+            || {
+                let mut allocator = TableSuballocator::<u32>::new(RangeOf::new(0, RANGE_SIZE));
+                for _ in 0..RANGE_SIZE / 2 {
+                    allocator.allocate().unwrap();
+                }
+                for index in (0..RANGE_SIZE / 2).step_by(2) {
+                    allocator.deallocate(IndexOf::new(index)).unwrap();
+                }
+                allocator
+            },
+            |allocator| {
+                for _ in 0..ALLOCATION_COUNT {
+                    allocator.allocate_run(8).unwrap();
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, segregated_slab, table);
+criterion_main!(benches);
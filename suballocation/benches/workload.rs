@@ -0,0 +1,222 @@
+//! Workload simulator : replays `tetra`-like shapes and reports how each configuration copes.
+//!
+//! Run "cargo bench --bench workload".
+//!
+//! 1. Fill : allocate shapes until the first failure (first-failure point).
+//! 2. Churn : random allocations, deallocations & reallocations (shapes edited) from that state.
+//!
+//! Reported :
+//! - First failure : shapes allocated & occupancy when the first allocation failed.
+//! - Occupancy : allocated units / range size (averaged over churn).
+//! - Internal fragmentation : padding units / reserved units (averaged over churn).
+//! - Failures : failed allocations & reallocations / attempts during churn.
+
+mod common;
+
+use mem_utils::RangeOf;
+use suballocation::{
+    ArrayOfUnitSuballocation, MemoryMap, UnitRunSuballocation, harness::Rng,
+    segregated_slab::SegregatedSlabSuballocator, table::TableSuballocator,
+};
+
+use common::{configurations, shape_size};
+
+const RANGE_SIZE: usize = 4096; // `MAX_VERTICES` in `tetra`
+const MAX_SHAPES: usize = 256; // `MAX_SHAPES` in `tetra`
+const CHURN_STEPS: usize = 20_000;
+const SEED_COUNT: u64 = 8;
+
+/////////////////////////////////////////////////////////////////////////////
+// Structure
+/////////////////////////////////////////////////////////////////////////////
+
+/// Averaged over seeds.
+#[derive(Default)]
+struct Report {
+    first_failure_allocations: f64,
+    first_failure_occupancy: f64,
+    occupancy: f64,
+    internal_fragmentation: f64,
+    failure_rate: f64,
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Main
+/////////////////////////////////////////////////////////////////////////////
+
+fn main() {
+    println!(
+        "clouds : {RANGE_SIZE} vertices, shapes of {} to {} vertices (log-uniform), {CHURN_STEPS} churn steps, {SEED_COUNT} seeds\n",
+        common::MIN_SHAPE_SIZE,
+        common::MAX_SHAPE_SIZE
+    );
+    print_header("configuration");
+    for (name, configuration_from) in configurations(RANGE_SIZE) {
+        let report = average(|seed| {
+            let allocator =
+                SegregatedSlabSuballocator::<u32>::new_from_configuration(configuration_from())
+                    .unwrap();
+            simulate_array(allocator, seed, shape_size)
+        });
+        print_row(name, &report);
+    }
+
+    // shapes made of several parts, with adjacent slots (draw commands)
+    println!("\nshapes : {MAX_SHAPES} slots, runs of 1 to 8 slots\n");
+    print_header("allocator");
+    let report = average(|seed| {
+        simulate_runs(
+            TableSuballocator::<u32>::new(RangeOf::new(0, MAX_SHAPES)),
+            seed,
+        )
+    });
+    print_row("table", &report);
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Simulations
+/////////////////////////////////////////////////////////////////////////////
+
+fn simulate_array<S: ArrayOfUnitSuballocation<u32> + MemoryMap<u32>>(
+    mut allocator: S,
+    seed: u64,
+    size_from: impl Fn(&mut Rng) -> usize,
+) -> Report {
+    let mut rng = Rng::new(seed);
+    let mut report = Report::default();
+    let mut ranges = Vec::new();
+
+    // fill
+    while let Ok(range) = allocator.allocate(size_from(&mut rng)) {
+        ranges.push(range);
+    }
+    report.first_failure_allocations = ranges.len() as f64;
+    report.first_failure_occupancy = usage_from(&allocator).0;
+
+    // churn : allocations slightly more frequent than deallocations, to stay around the first-failure point
+    let (mut attempts, mut failures) = (0, 0);
+    for _ in 0..CHURN_STEPS {
+        let roll = rng.next_f64();
+        if roll < 0.2 && !ranges.is_empty() {
+            // edit a shape
+            attempts += 1;
+            let position = rng.below(ranges.len());
+            match allocator.reallocate(ranges[position], size_from(&mut rng)) {
+                Ok(new_range) => ranges[position] = new_range,
+                Err(_) => failures += 1,
+            }
+        } else if roll < 0.55 && !ranges.is_empty() {
+            allocator
+                .deallocate(ranges.swap_remove(rng.below(ranges.len())))
+                .unwrap();
+        } else {
+            attempts += 1;
+            match allocator.allocate(size_from(&mut rng)) {
+                Ok(range) => ranges.push(range),
+                Err(_) => failures += 1,
+            }
+        }
+
+        let (occupancy, internal_fragmentation) = usage_from(&allocator);
+        report.occupancy += occupancy / CHURN_STEPS as f64;
+        report.internal_fragmentation += internal_fragmentation / CHURN_STEPS as f64;
+    }
+    report.failure_rate = failures as f64 / attempts as f64;
+
+    report
+}
+
+fn simulate_runs<S: UnitRunSuballocation<u32> + MemoryMap<u32>>(
+    mut allocator: S,
+    seed: u64,
+) -> Report {
+    let mut rng = Rng::new(seed);
+    let mut report = Report::default();
+    let mut runs = Vec::new();
+    let run_count_from = |rng: &mut Rng| 1 + rng.below(8);
+
+    // fill
+    while let Ok(run) = allocator.allocate_run(run_count_from(&mut rng)) {
+        runs.push(run);
+    }
+    report.first_failure_allocations = runs.len() as f64;
+    report.first_failure_occupancy = usage_from(&allocator).0;
+
+    // churn : same as `simulate_array`, without edits
+    let (mut attempts, mut failures) = (0, 0);
+    for _ in 0..CHURN_STEPS {
+        if rng.next_f64() < 0.45 && !runs.is_empty() {
+            allocator
+                .deallocate_run(runs.swap_remove(rng.below(runs.len())))
+                .unwrap();
+        } else {
+            attempts += 1;
+            match allocator.allocate_run(run_count_from(&mut rng)) {
+                Ok(run) => runs.push(run),
+                Err(_) => failures += 1,
+            }
+        }
+        report.occupancy += usage_from(&allocator).0 / CHURN_STEPS as f64;
+    }
+    report.failure_rate = failures as f64 / attempts as f64;
+
+    report
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Utils
+/////////////////////////////////////////////////////////////////////////////
+
+/// (occupancy, internal fragmentation) from the memory map.
+fn usage_from<U>(allocator: &impl MemoryMap<U>) -> (f64, f64) {
+    let (mut range_size, mut allocated_size, mut reserved_size) = (0, 0, 0);
+    for row in allocator.memory_map() {
+        range_size += row.range.size;
+        for allocation in row.allocations {
+            allocated_size += allocation.range.size;
+            reserved_size += allocation.reserved_size;
+        }
+    }
+    let internal_fragmentation = if reserved_size == 0 {
+        0.0
+    } else {
+        (reserved_size - allocated_size) as f64 / reserved_size as f64
+    };
+    (
+        allocated_size as f64 / range_size as f64,
+        internal_fragmentation,
+    )
+}
+
+fn average(simulate: impl Fn(u64) -> Report) -> Report {
+    let mut report = Report::default();
+    for seed in 0..SEED_COUNT {
+        let seed_report = simulate(seed);
+        let n = SEED_COUNT as f64;
+        report.first_failure_allocations += seed_report.first_failure_allocations / n;
+        report.first_failure_occupancy += seed_report.first_failure_occupancy / n;
+        report.occupancy += seed_report.occupancy / n;
+        report.internal_fragmentation += seed_report.internal_fragmentation / n;
+        report.failure_rate += seed_report.failure_rate / n;
+    }
+    report
+}
+
+fn print_header(name: &str) {
+    println!(
+        "{name:<24} | {:>13} | {:>13} | {:>9} | {:>13} | {:>8}",
+        "first failure", "at occupancy", "occupancy", "internal frag", "failures"
+    );
+    println!("{}", "-".repeat(24 + 13 + 13 + 9 + 13 + 8 + 5 * 3));
+}
+
+fn print_row(name: &str, report: &Report) {
+    println!(
+        "{name:<24} | {:>13.1} | {:>12.1}% | {:>8.1}% | {:>12.1}% | {:>7.1}%",
+        report.first_failure_allocations,
+        report.first_failure_occupancy * 100.0,
+        report.occupancy * 100.0,
+        report.internal_fragmentation * 100.0,
+        report.failure_rate * 100.0,
+    )
+}
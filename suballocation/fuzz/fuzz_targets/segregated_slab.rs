@@ -0,0 +1,33 @@
+//! Run with "cargo +nightly fuzz run segregated_slab" (from `suballocation/`).
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mem_utils::RangeOf;
+use suballocation::{
+    harness::{self, ArrayOperation},
+    segregated_slab::{FallbackPolicy, SegregatedSlabConfiguration, SegregatedSlabSuballocator},
+};
+
+fuzz_target!(|bytes: &[u8]| {
+    // first byte picks fallback policy
+    let Some((policy, bytes)) = bytes.split_first() else {
+        return;
+    };
+    let fallback_policy = match policy % 3 {
+        0 => FallbackPolicy::Strict,
+        1 => FallbackPolicy::NextLargerClass,
+        _ => FallbackPolicy::AnyLargerClass,
+    };
+
+    let new_allocator = || {
+        let configuration = SegregatedSlabConfiguration::pot(RangeOf::new(0, 256), 32, 1)
+            .unwrap()
+            .with_fallback_policy(fallback_policy);
+        SegregatedSlabSuballocator::<u32>::new_from_configuration(configuration).unwrap()
+    };
+    let operations = ArrayOperation::sequence_from_bytes(bytes, 40);
+    if let Err((step, reason)) = harness::run_array(new_allocator, &operations) {
+        panic!("step {step} : {reason}\n{operations:#?}");
+    }
+});
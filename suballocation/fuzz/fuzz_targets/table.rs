@@ -0,0 +1,18 @@
+//! Run with "cargo +nightly fuzz run table" (from `suballocation/`).
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mem_utils::RangeOf;
+use suballocation::{
+    harness::{self, UnitOperation},
+    table::TableSuballocator,
+};
+
+fuzz_target!(|bytes: &[u8]| {
+    let new_allocator = || TableSuballocator::<u32>::new(RangeOf::new(1, 64));
+    let operations = UnitOperation::sequence_from_bytes(bytes);
+    if let Err((step, reason)) = harness::run_unit(new_allocator, &operations) {
+        panic!("step {step} : {reason}\n{operations:#?}");
+    }
+});
@@ -0,0 +1,703 @@
+//! Model-based testing of suballocators (feature `harness`).
+//!
+//! Random operations are applied both to a suballocator and to a reference model (shadow memory) :
+//! - Allocations never overlap and stay in range.
+//! - Contents survive `reallocate` : each allocation writes its own pattern, checked at the old range before the call.
+//!   The returned range must not cover another live allocation, nor overlap the old one after a move
+//!   (the harness then copies contents forward unit by unit, as a user would).
+//! - `allocations` matches the model.
+//!
+//! A failing sequence is shrunk (operations removed while it still fails) before being reported.
+//! Sequences can also be decoded from raw bytes, for fuzzing (see `fuzz/`).
+
+use std::fmt::{Debug, Display};
+
+use mem_utils::{IndexOf, RangeOf};
+
+use super::{ArrayOfUnitSuballocation, SuballocationError, UnitSuballocation};
+
+/////////////////////////////////////////////////////////////////////////////
+// Structures
+/////////////////////////////////////////////////////////////////////////////
+
+/// Operation on an `ArrayOfUnitSuballocation`.
+///
+/// `allocation` refers to a live allocation (modulo their count), ignored if there is none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayOperation {
+    Allocate {
+        size: usize,
+        align: usize,
+    },
+    Reallocate {
+        allocation: usize,
+        size: usize,
+        align: usize,
+    },
+    Deallocate {
+        allocation: usize,
+    },
+}
+
+/// Operation on a `UnitSuballocation`.
+///
+/// `allocation` refers to a live allocation (modulo their count), ignored if there is none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitOperation {
+    Allocate { align: usize },
+    Deallocate { allocation: usize },
+}
+
+/// Shrunk failing sequence, `operations[step]` is the failing one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure<Operation> {
+    pub operations: Vec<Operation>,
+    pub step: usize,
+    pub reason: String,
+}
+
+/// Small deterministic generator (SplitMix64), so that a seed reproduces a sequence.
+pub struct Rng {
+    state: u64,
+}
+
+/// Reference model : owner of each unit, and memory written through allocations.
+struct Model {
+    units: Vec<Option<usize>>, // allocation id, indexed by absolute offset
+    memory: Vec<u64>, // written as a user would (see `pattern`), indexed by absolute offset
+    next_id: usize,
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////////
+
+/// Rng
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// In 0..1.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// In `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+/// Generate
+impl ArrayOperation {
+    /// Sizes in `0..=max_size` (0 checks `SizeZero`), mostly unaligned.
+    pub fn random_sequence(seed: u64, count: usize, max_size: usize) -> Vec<ArrayOperation> {
+        let mut rng = Rng::new(seed);
+        (0..count)
+            .map(|_| {
+                let byte = rng.next_u64() as u8;
+                Self::from_byte(byte, &mut || rng.next_u64() as u8 as usize, max_size)
+            })
+            .collect()
+    }
+
+    /// Decode raw bytes (fuzzer input), 3 bytes per operation.
+    pub fn sequence_from_bytes(bytes: &[u8], max_size: usize) -> Vec<ArrayOperation> {
+        bytes
+            .chunks_exact(3)
+            .map(|chunk| {
+                let mut arguments = chunk[1..].iter().map(|byte| *byte as usize);
+                Self::from_byte(chunk[0], &mut || arguments.next().unwrap_or(0), max_size)
+            })
+            .collect()
+    }
+
+    fn from_byte(byte: u8, next: &mut impl FnMut() -> usize, max_size: usize) -> ArrayOperation {
+        let align = [1, 1, 1, 1, 2, 3, 4, 8][(byte >> 5) as usize];
+        match byte % 8 {
+            0..=3 => ArrayOperation::Allocate {
+                size: next() % (max_size + 1),
+                align,
+            },
+            4 | 5 => ArrayOperation::Reallocate {
+                allocation: next(),
+                size: next() % (max_size + 1),
+                align,
+            },
+            _ => ArrayOperation::Deallocate { allocation: next() },
+        }
+    }
+}
+
+/// Generate
+impl UnitOperation {
+    pub fn random_sequence(seed: u64, count: usize) -> Vec<UnitOperation> {
+        let mut rng = Rng::new(seed);
+        (0..count)
+            .map(|_| Self::from_bytes([rng.next_u64() as u8, rng.next_u64() as u8]))
+            .collect()
+    }
+
+    /// Decode raw bytes (fuzzer input), 2 bytes per operation.
+    pub fn sequence_from_bytes(bytes: &[u8]) -> Vec<UnitOperation> {
+        bytes
+            .chunks_exact(2)
+            .map(|chunk| Self::from_bytes([chunk[0], chunk[1]]))
+            .collect()
+    }
+
+    fn from_bytes([byte, argument]: [u8; 2]) -> UnitOperation {
+        if byte % 2 == 0 {
+            UnitOperation::Allocate {
+                align: [1, 1, 1, 1, 2, 3, 4, 8][(byte >> 5) as usize],
+            }
+        } else {
+            UnitOperation::Deallocate {
+                allocation: argument as usize,
+            }
+        }
+    }
+}
+
+/// Model
+impl Model {
+    fn new(end: usize) -> Model {
+        Model {
+            units: vec![None; end],
+            memory: vec![0; end],
+            next_id: 0,
+        }
+    }
+
+    /// Own `range` units (must be free) :
+    /// - `kept` first units keep their memory value.
+    /// - Others are written with the pattern of `id`.
+    fn own(&mut self, range: std::ops::Range<usize>, id: usize, kept: usize) -> Result<(), String> {
+        for (i, offset) in range.enumerate() {
+            let unit = self
+                .units
+                .get_mut(offset)
+                .ok_or(format!("unit {offset} out of range"))?;
+            if let Some(other_id) = unit {
+                return Err(format!(
+                    "unit {offset} given to {id} is owned by {other_id}"
+                ));
+            }
+            if i >= kept {
+                self.memory[offset] = pattern(id, i);
+            }
+            *unit = Some(id);
+        }
+        Ok(())
+    }
+
+    /// First unit of `range` owned by another allocation than `id`, with its owner.
+    fn owned_by_other(&self, range: RangeOf<impl Sized>, id: usize) -> Option<(usize, usize)> {
+        range.to_std_range().find_map(|offset| {
+            self.units[offset]
+                .filter(|owner| *owner != id)
+                .map(|owner| (offset, owner))
+        })
+    }
+
+    fn new_id(&mut self) -> usize {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+/// Display
+impl<Operation: Debug> Display for Failure<Operation> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "step {} : {}", self.step, self.reason)?;
+        for (step, operation) in self.operations.iter().enumerate() {
+            let marker = if step == self.step { ">" } else { " " };
+            writeln!(f, "{marker} {step:>4} {operation:?}")?;
+        }
+        Ok(())
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Fonctions
+/////////////////////////////////////////////////////////////////////////////
+
+/// Value written by allocation `id` at its `i`-th unit (distinct per allocation).
+fn pattern(id: usize, i: usize) -> u64 {
+    ((id as u64) << 32) | i as u64
+}
+
+//------------// array //------------//
+
+/// Run `operations` on a suballocator from `new_suballocator`, checking it against the model.
+///
+/// Returns the failing step and reason (not shrunk).
+pub fn run_array<U, S: ArrayOfUnitSuballocation<U>>(
+    new_suballocator: impl Fn() -> S,
+    operations: &[ArrayOperation],
+) -> Result<(), (usize, String)> {
+    let mut suballocator = new_suballocator();
+    let range = suballocator.range();
+    let mut model = Model::new(range.end());
+    let mut allocations: Vec<(usize, RangeOf<U>, Vec<u64>)> = Vec::new(); // (id, range, contents)
+
+    for (step, operation) in operations.iter().copied().enumerate() {
+        let fail = |reason: String| (step, reason);
+        match operation {
+            ArrayOperation::Allocate { size, align } => {
+                match suballocator.allocate_aligned(size, align) {
+                    Ok(allocated_range) => {
+                        check_range(allocated_range, size, align, range).map_err(fail)?;
+                        let id = model.new_id();
+                        model
+                            .own(allocated_range.to_std_range(), id, 0)
+                            .map_err(fail)?;
+                        let contents = model.memory[allocated_range.to_std_range()].to_vec();
+                        allocations.push((id, allocated_range, contents));
+                    }
+                    Err(error) => check_error(error, size, align).map_err(fail)?,
+                }
+            }
+            ArrayOperation::Reallocate {
+                allocation,
+                size,
+                align,
+            } => {
+                if allocations.is_empty() {
+                    continue;
+                }
+                let allocation = allocation % allocations.len();
+                let (id, old_range, contents) = &allocations[allocation];
+                let (id, old_range) = (*id, *old_range);
+
+                // old contents still there (written by `id`, never by another allocation)
+                if model.memory[old_range.to_std_range()] != contents[..] {
+                    return Err(fail(format!("{old_range} of {id} was overwritten")));
+                }
+
+                match suballocator.reallocate_aligned(old_range, size, align) {
+                    Ok(new_range) => {
+                        check_range(new_range, size, align, range).map_err(fail)?;
+
+                        // never over another live allocation
+                        if let Some((offset, other_id)) = model.owned_by_other(new_range, id) {
+                            return Err(fail(format!(
+                                "{new_range} of {id} covers unit {offset} of {other_id}"
+                            )));
+                        }
+
+                        // moved : copied by user (forward, as if nonoverlapping)
+                        let kept = usize::min(size, old_range.size);
+                        if new_range.offset != old_range.offset {
+                            if new_range.overlaps(&old_range) {
+                                return Err(fail(format!(
+                                    "{new_range} moved over the old range {old_range} of {id}"
+                                )));
+                            }
+                            for i in 0..kept {
+                                model.memory[new_range.offset + i] =
+                                    model.memory[old_range.offset + i];
+                            }
+                        }
+
+                        for offset in old_range.to_std_range() {
+                            model.units[offset] = None;
+                        }
+                        let new_id = model.new_id();
+                        model
+                            .own(new_range.to_std_range(), new_id, kept)
+                            .map_err(fail)?;
+                        let contents = model.memory[new_range.to_std_range()].to_vec();
+                        allocations[allocation] = (new_id, new_range, contents);
+                    }
+                    Err(error) => {
+                        check_error(error, size, align).map_err(fail)?;
+                        if !suballocator.is_allocated(old_range) {
+                            return Err(fail(format!(
+                                "{old_range} of {id} isn't allocated after failed reallocation"
+                            )));
+                        }
+                    }
+                }
+            }
+            ArrayOperation::Deallocate { allocation } => {
+                if allocations.is_empty() {
+                    continue;
+                }
+                let (_, deallocated_range, _) =
+                    allocations.swap_remove(allocation % allocations.len());
+                suballocator
+                    .deallocate(deallocated_range)
+                    .map_err(|error| fail(format!("deallocate {deallocated_range} : {error}")))?;
+                if suballocator.deallocate(deallocated_range)
+                    != Err(SuballocationError::NotAllocated)
+                {
+                    return Err(fail(format!("{deallocated_range} deallocated twice")));
+                }
+                for offset in deallocated_range.to_std_range() {
+                    model.units[offset] = None;
+                }
+            }
+        }
+
+        // allocations match model
+        let mut expected: Vec<_> = allocations
+            .iter()
+            .map(|(_, range, _)| (range.offset, range.size))
+            .collect();
+        let mut actual: Vec<_> = suballocator
+            .allocations()
+            .iter()
+            .map(|range| (range.offset, range.size))
+            .collect();
+        expected.sort_unstable();
+        actual.sort_unstable();
+        if expected != actual {
+            return Err(fail(format!(
+                "allocations {actual:?} should be {expected:?}"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Run random sequences from `seeds`, returning the first failure shrunk.
+pub fn check_array<U, S: ArrayOfUnitSuballocation<U>>(
+    new_suballocator: impl Fn() -> S,
+    seeds: std::ops::Range<u64>,
+    count: usize,
+    max_size: usize,
+) -> Result<(), Failure<ArrayOperation>> {
+    for seed in seeds {
+        let operations = ArrayOperation::random_sequence(seed, count, max_size);
+        if run_array(&new_suballocator, &operations).is_err() {
+            let operations = shrink(operations, |operations| {
+                run_array(&new_suballocator, operations).is_err()
+            });
+            let (step, reason) = run_array(&new_suballocator, &operations).unwrap_err(); // UNWRAP: shrunk sequence still fails
+            return Err(Failure {
+                operations,
+                step,
+                reason: format!("(seed {seed}) {reason}"),
+            });
+        }
+    }
+    Ok(())
+}
+
+//------------// unit //------------//
+
+/// Run `operations` on a suballocator from `new_suballocator`, checking it against the model.
+///
+/// Returns the failing step and reason (not shrunk).
+pub fn run_unit<U, S: UnitSuballocation<U>>(
+    new_suballocator: impl Fn() -> S,
+    operations: &[UnitOperation],
+) -> Result<(), (usize, String)> {
+    let mut suballocator = new_suballocator();
+    let range = suballocator.range();
+    let mut model = Model::new(range.end());
+    let mut allocations: Vec<IndexOf<U>> = Vec::new();
+
+    for (step, operation) in operations.iter().copied().enumerate() {
+        let fail = |reason: String| (step, reason);
+        match operation {
+            UnitOperation::Allocate { align } => match suballocator.allocate_aligned(align) {
+                Ok(index) => {
+                    check_range(RangeOf::<U>::new(index.index, 1), 1, align, range)
+                        .map_err(fail)?;
+                    let id = model.new_id();
+                    model
+                        .own(index.index..index.index + 1, id, 0)
+                        .map_err(fail)?;
+                    allocations.push(index);
+                }
+                Err(error) => check_error(error, 1, align).map_err(fail)?,
+            },
+            UnitOperation::Deallocate { allocation } => {
+                if allocations.is_empty() {
+                    continue;
+                }
+                let index = allocations.swap_remove(allocation % allocations.len());
+                suballocator
+                    .deallocate(index)
+                    .map_err(|error| fail(format!("deallocate {index} : {error}")))?;
+                if suballocator.deallocate(index) != Err(SuballocationError::NotAllocated) {
+                    return Err(fail(format!("{index} deallocated twice")));
+                }
+                model.units[index.index] = None;
+            }
+        }
+
+        // allocations match model
+        let mut expected: Vec<_> = allocations.iter().map(|index| index.index).collect();
+        let mut actual: Vec<_> = suballocator
+            .allocations()
+            .iter()
+            .map(|index| index.index)
+            .collect();
+        expected.sort_unstable();
+        actual.sort_unstable();
+        if expected != actual {
+            return Err(fail(format!(
+                "allocations {actual:?} should be {expected:?}"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Run random sequences from `seeds`, returning the first failure shrunk.
+pub fn check_unit<U, S: UnitSuballocation<U>>(
+    new_suballocator: impl Fn() -> S,
+    seeds: std::ops::Range<u64>,
+    count: usize,
+) -> Result<(), Failure<UnitOperation>> {
+    for seed in seeds {
+        let operations = UnitOperation::random_sequence(seed, count);
+        if run_unit(&new_suballocator, &operations).is_err() {
+            let operations = shrink(operations, |operations| {
+                run_unit(&new_suballocator, operations).is_err()
+            });
+            let (step, reason) = run_unit(&new_suballocator, &operations).unwrap_err(); // UNWRAP: shrunk sequence still fails
+            return Err(Failure {
+                operations,
+                step,
+                reason: format!("(seed {seed}) {reason}"),
+            });
+        }
+    }
+    Ok(())
+}
+
+//------------// shrink //------------//
+
+/// Remove chunks of operations (halving chunk size) while `fails`.
+pub fn shrink<Operation: Clone>(
+    mut operations: Vec<Operation>,
+    mut fails: impl FnMut(&[Operation]) -> bool,
+) -> Vec<Operation> {
+    let mut chunk_size = operations.len().div_ceil(2);
+    while chunk_size > 0 {
+        let mut start = 0;
+        while start < operations.len() {
+            // try without `start..start + chunk_size`
+            let end = usize::min(start + chunk_size, operations.len());
+            let mut candidate = operations[..start].to_vec();
+            candidate.extend_from_slice(&operations[end..]);
+            if fails(&candidate) {
+                operations = candidate;
+            } else {
+                start += chunk_size;
+            }
+        }
+        chunk_size /= 2;
+    }
+    operations
+}
+
+//------------// checks //------------//
+
+fn check_range<U>(
+    range: RangeOf<U>,
+    size: usize,
+    align: usize,
+    bound: RangeOf<U>,
+) -> Result<(), String> {
+    if range.size != size {
+        return Err(format!("{range} should have size {size}"));
+    }
+    if !range.is_aligned(align) {
+        return Err(format!("{range} should be aligned to {align}"));
+    }
+    if !range.is_subrange_of(&bound) {
+        return Err(format!("{range} should be in {bound}"));
+    }
+    Ok(())
+}
+
+/// Allocations only return `InvalidConfiguration` for a null alignment.
+fn check_error(error: SuballocationError, size: usize, align: usize) -> Result<(), String> {
+    match error {
+        SuballocationError::SizeZero if size == 0 => Ok(()),
+        SuballocationError::InvalidConfiguration { .. } if align == 0 => Ok(()),
+        SuballocationError::OutOfMemory | SuballocationError::SizeTooLarge { .. } if size != 0 => {
+            Ok(())
+        }
+        _ => Err(format!(
+            "unexpected error for size {size} (align {align}) : {error}"
+        )),
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shrink_to_culprit() {
+        let operations: Vec<usize> = (0..100).collect();
+
+        // fails when 42 follows 7
+        let shrunk = shrink(operations, |operations| {
+            operations
+                .iter()
+                .position(|operation| *operation == 7)
+                .is_some_and(|position| operations[position..].contains(&42))
+        });
+        assert_eq!(shrunk, vec![7, 42]);
+    }
+
+    /// Bump allocator, reallocating one unit further (over the old range).
+    struct Faulty {
+        allocations: Vec<RangeOf<u32>>,
+        head: usize,
+        invalid_configuration: bool, // fail every allocation with `InvalidConfiguration`
+    }
+
+    impl ArrayOfUnitSuballocation<u32> for Faulty {
+        fn range(&self) -> RangeOf<u32> {
+            RangeOf::new(0, 64)
+        }
+        fn can_allocate(&self, _: usize) -> bool {
+            true
+        }
+        fn is_allocated(&self, range: RangeOf<u32>) -> bool {
+            self.allocations.contains(&range)
+        }
+        fn can_reallocate(&self, _: RangeOf<u32>, _: usize) -> bool {
+            true
+        }
+        fn allocate(&mut self, size: usize) -> Result<RangeOf<u32>, SuballocationError> {
+            self.allocate_aligned(size, 1)
+        }
+        fn deallocate(&mut self, range: RangeOf<u32>) -> Result<(), SuballocationError> {
+            let position = self
+                .allocations
+                .iter()
+                .position(|allocation| *allocation == range)
+                .ok_or(SuballocationError::NotAllocated)?;
+            self.allocations.remove(position);
+            Ok(())
+        }
+        fn reallocate(
+            &mut self,
+            range: RangeOf<u32>,
+            size: usize,
+        ) -> Result<RangeOf<u32>, SuballocationError> {
+            self.reallocate_aligned(range, size, 1)
+        }
+        fn allocate_aligned(
+            &mut self,
+            size: usize,
+            _: usize,
+        ) -> Result<RangeOf<u32>, SuballocationError> {
+            if self.invalid_configuration {
+                return Err(SuballocationError::invalid_configuration("faulty"));
+            }
+            let range = RangeOf::new(self.head, size);
+            self.head += size + 1;
+            self.allocations.push(range);
+            Ok(range)
+        }
+        fn reallocate_aligned(
+            &mut self,
+            range: RangeOf<u32>,
+            size: usize,
+            _: usize,
+        ) -> Result<RangeOf<u32>, SuballocationError> {
+            self.deallocate(range)?;
+            let new_range = RangeOf::new(range.offset + 1, size);
+            self.allocations.push(new_range);
+            Ok(new_range)
+        }
+        fn grow(&mut self, _: RangeOf<u32>) -> Result<(), SuballocationError> {
+            Ok(())
+        }
+        fn shrink_to_fit(&mut self) -> RangeOf<u32> {
+            self.range()
+        }
+        fn allocations(&self) -> Vec<RangeOf<u32>> {
+            self.allocations.clone()
+        }
+    }
+
+    #[test]
+    fn faulty_allocators_fail() {
+        let new_faulty = |invalid_configuration| {
+            move || Faulty {
+                allocations: Vec::new(),
+                head: 0,
+                invalid_configuration,
+            }
+        };
+
+        // moved over old range
+        let operations = [
+            ArrayOperation::Allocate { size: 4, align: 1 },
+            ArrayOperation::Reallocate {
+                allocation: 0,
+                size: 4,
+                align: 1,
+            },
+        ];
+        let (step, reason) = run_array(new_faulty(false), &operations).unwrap_err();
+        assert_eq!(step, 1);
+        assert!(reason.contains("moved over the old range"));
+
+        // moved over another live allocation (0..1 => 1..4, over 2..3)
+        let covering_operations = [
+            ArrayOperation::Allocate { size: 1, align: 1 },
+            ArrayOperation::Allocate { size: 1, align: 1 },
+            ArrayOperation::Reallocate {
+                allocation: 0,
+                size: 3,
+                align: 1,
+            },
+        ];
+        let (step, reason) = run_array(new_faulty(false), &covering_operations).unwrap_err();
+        assert_eq!(step, 2);
+        assert!(reason.contains("covers unit 2 of"));
+
+        // `InvalidConfiguration` for a valid alignment
+        let (step, _) = run_array(new_faulty(true), &operations).unwrap_err();
+        assert_eq!(step, 0);
+    }
+
+    #[test]
+    fn decode_bytes() {
+        assert_eq!(
+            ArrayOperation::sequence_from_bytes(&[0, 9, 0, 4, 1, 20, 6, 0], 16),
+            vec![
+                ArrayOperation::Allocate { size: 9, align: 1 },
+                ArrayOperation::Reallocate {
+                    allocation: 1,
+                    size: 3,
+                    align: 1
+                },
+            ]
+        );
+        assert_eq!(
+            UnitOperation::sequence_from_bytes(&[128, 0, 1, 5, 2]),
+            vec![
+                UnitOperation::Allocate { align: 2 },
+                UnitOperation::Deallocate { allocation: 5 },
+            ]
+        );
+    }
+}
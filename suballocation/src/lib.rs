@@ -16,13 +16,26 @@
 //! 1. First VMA allocate a memory chunk of 256Mb and suballocate from it to back up a requested buffer (as vulkan recommend).
 //! 2. Second, I suballocate *from the buffer memory* to manage dynamically-sized objects.
 
+#[cfg(any(test, feature = "harness"))]
+pub mod harness;
+pub mod linear;
+mod memory_map;
+pub mod paged;
+pub mod ring;
 pub mod segregated_slab;
+mod suballocation_error;
 pub mod table;
+mod tagged;
+mod transaction;
 
 use mem_utils::{IndexOf, RangeOf};
 
-pub type Error = Box<dyn std::error::Error>;
-pub type Result<T> = std::result::Result<T, Error>;
+pub use memory_map::{MemoryMap, MemoryMapAllocation, MemoryMapRow};
+pub use suballocation_error::SuballocationError;
+pub use tagged::{TaggedSuballocator, TaggedUnitSuballocator};
+pub use transaction::{Participant, Transaction};
+
+pub type Result<T> = std::result::Result<T, SuballocationError>;
 
 /////////////////////////////////////////////////////////////////////////////
 // Traits
@@ -30,6 +43,7 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 pub trait ArrayOfUnitSuballocation<Unit> {
     //------------// query //------------//
+    fn range(&self) -> RangeOf<Unit>;
     fn can_allocate(&self, size: usize) -> bool;
     fn is_allocated(&self, range: RangeOf<Unit>) -> bool;
     fn can_reallocate(&self, range: RangeOf<Unit>, size: usize) -> bool;
@@ -39,12 +53,30 @@ pub trait ArrayOfUnitSuballocation<Unit> {
     fn deallocate(&mut self, range: RangeOf<Unit>) -> Result<()>;
     fn reallocate(&mut self, range: RangeOf<Unit>, size: usize) -> Result<RangeOf<Unit>>;
 
+    //------------// suballocate aligned //------------//
+    /// Offset of allocated range is a multiple of `align` (in units, see `mem_utils::unit_align_from_byte_align`).
+    fn allocate_aligned(&mut self, size: usize, align: usize) -> Result<RangeOf<Unit>>;
+    /// Offset of reallocated range is a multiple of `align` (in units).
+    fn reallocate_aligned(
+        &mut self,
+        range: RangeOf<Unit>,
+        size: usize,
+        align: usize,
+    ) -> Result<RangeOf<Unit>>;
+
+    //------------// resize //------------//
+    /// Extend managed range to `new_range` (same offset, bigger size). Existing allocations stay valid.
+    fn grow(&mut self, new_range: RangeOf<Unit>) -> Result<()>;
+    /// Release trailing empty space and return the new managed range.
+    fn shrink_to_fit(&mut self) -> RangeOf<Unit>;
+
     //------------// debug //------------//
     fn allocations(&self) -> Vec<RangeOf<Unit>>;
 }
 
 pub trait UnitSuballocation<Unit> {
     //------------// query //------------//
+    fn range(&self) -> RangeOf<Unit>;
     fn can_allocate(&self) -> bool;
     fn is_allocated(&self, index: IndexOf<Unit>) -> bool;
 
@@ -52,6 +84,41 @@ pub trait UnitSuballocation<Unit> {
     fn allocate(&mut self) -> Result<IndexOf<Unit>>;
     fn deallocate(&mut self, index: IndexOf<Unit>) -> Result<()>;
 
+    //------------// suballocate aligned //------------//
+    /// Allocated index is a multiple of `align`.
+    fn allocate_aligned(&mut self, align: usize) -> Result<IndexOf<Unit>>;
+
+    //------------// resize //------------//
+    /// Extend managed range to `new_range` (same offset, bigger size). Existing allocations stay valid.
+    fn grow(&mut self, new_range: RangeOf<Unit>) -> Result<()>;
+    /// Release trailing empty space and return the new managed range.
+    fn shrink_to_fit(&mut self) -> RangeOf<Unit>;
+
     //------------// debug //------------//
     fn allocations(&self) -> Vec<IndexOf<Unit>>;
 }
+
+/// Contiguous units (runs) from a `UnitSuballocation`.
+pub trait UnitRunSuballocation<Unit>: UnitSuballocation<Unit> {
+    //------------// query //------------//
+    fn can_allocate_run(&self, count: usize) -> bool;
+
+    //------------// suballocate //------------//
+    /// `count` consecutive units, as a range.
+    fn allocate_run(&mut self, count: usize) -> Result<RangeOf<Unit>>;
+    /// All units of `range` must be allocated (by a run or one by one).
+    fn deallocate_run(&mut self, range: RangeOf<Unit>) -> Result<()>;
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Utils
+/////////////////////////////////////////////////////////////////////////////
+
+pub(crate) fn check_align(align: usize) -> Result<()> {
+    if align == 0 {
+        return Err(SuballocationError::invalid_configuration(
+            "alignment should be non zero",
+        ));
+    }
+    Ok(())
+}
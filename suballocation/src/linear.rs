@@ -0,0 +1,156 @@
+//! Linear (bump pointer) allocator, for data living one frame.
+//!
+//! - `#` := free unit.
+//!
+//! `################`
+//!
+//! - Allocate `aaa`, `bb`, `cccc` : allocations are packed one after the other.
+//!
+//! `aaabbcccc#######`
+//!
+//! - Reset (once the frame using them is done) : everything is free again.
+//!
+//! `################`
+//!
+//! No deallocation of a single allocation, so no bookkeeping apart from the head.
+
+use mem_utils::{RangeOf, align_up};
+
+use super::{
+    MemoryMap, MemoryMapAllocation, MemoryMapRow, Result, SuballocationError, check_align,
+};
+
+/////////////////////////////////////////////////////////////////////////////
+// Structure
+/////////////////////////////////////////////////////////////////////////////
+
+pub struct LinearSuballocator<U> {
+    range: RangeOf<U>,
+    head: usize, // absolute offset of first free unit
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////////
+
+/// New
+impl<U> LinearSuballocator<U> {
+    pub fn new(range: RangeOf<U>) -> LinearSuballocator<U> {
+        LinearSuballocator {
+            range,
+            head: range.offset,
+        }
+    }
+}
+
+/// Query
+impl<U> LinearSuballocator<U> {
+    pub fn range(&self) -> RangeOf<U> {
+        self.range
+    }
+
+    /// Allocated units, including alignment padding.
+    pub fn used(&self) -> usize {
+        self.head - self.range.offset
+    }
+
+    pub fn can_allocate(&self, size: usize) -> bool {
+        self.aligned_offset_from(size, 1).is_ok()
+    }
+}
+
+/// Suballocate
+impl<U> LinearSuballocator<U> {
+    pub fn allocate(&mut self, size: usize) -> Result<RangeOf<U>> {
+        self.allocate_aligned(size, 1)
+    }
+
+    pub fn allocate_aligned(&mut self, size: usize, align: usize) -> Result<RangeOf<U>> {
+        let offset = self.aligned_offset_from(size, align)?;
+        self.head = offset + size;
+        Ok(RangeOf::new(offset, size))
+    }
+
+    /// Free all allocations.
+    pub fn reset(&mut self) {
+        self.head = self.range.offset;
+    }
+}
+
+/// Memory map
+impl<U> MemoryMap<U> for LinearSuballocator<U> {
+    /// Allocations aren't tracked : used units are shown as a single allocation.
+    fn memory_map(&self) -> Vec<MemoryMapRow<U>> {
+        let used = self.head - self.range.offset;
+        let allocations = (used > 0)
+            .then(|| MemoryMapAllocation {
+                range: RangeOf::new(self.range.offset, used),
+                reserved_size: used,
+            })
+            .into_iter()
+            .collect();
+        vec![MemoryMapRow {
+            range: self.range,
+            label: None,
+            class: None,
+            allocations,
+        }]
+    }
+}
+
+/// Utils
+impl<U> LinearSuballocator<U> {
+    fn aligned_offset_from(&self, size: usize, align: usize) -> Result<usize> {
+        // check
+        if size == 0 {
+            return Err(SuballocationError::SizeZero);
+        }
+        if size > self.range.size {
+            return Err(SuballocationError::SizeTooLarge {
+                max: self.range.size,
+            });
+        }
+        check_align(align)?;
+
+        // fit after head ?
+        let offset = align_up(self.head, align);
+        if offset + size > self.range.end() {
+            return Err(SuballocationError::OutOfMemory);
+        }
+        Ok(offset)
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MemoryMap;
+
+    #[test]
+    fn allocate_and_reset() {
+        let mut allocator = LinearSuballocator::<u32>::new(RangeOf::new(2, 8));
+
+        assert_eq!(allocator.allocate(0), Err(SuballocationError::SizeZero));
+        assert_eq!(
+            allocator.allocate(9),
+            Err(SuballocationError::SizeTooLarge { max: 8 })
+        );
+
+        assert_eq!(allocator.allocate(3).unwrap(), RangeOf::new(2, 3));
+        assert_eq!(allocator.to_ascii(), "aaa#####");
+        assert_eq!(
+            allocator.allocate_aligned(2, 4).unwrap(),
+            RangeOf::new(8, 2)
+        );
+        assert_eq!(allocator.used(), 8);
+        assert_eq!(allocator.allocate(1), Err(SuballocationError::OutOfMemory));
+
+        allocator.reset();
+        assert_eq!(allocator.used(), 0);
+        assert_eq!(allocator.allocate(8).unwrap(), RangeOf::new(2, 8));
+    }
+}
@@ -0,0 +1,168 @@
+//! Render suballocators state, in the notation of `segregated_slab` documentation.
+//!
+//! - `|x|` := class of the slab at its right (`||` if empty).
+//! - `#` := free unit.
+//! - `~` := padding unit (reserved by an allocation but not used).
+//! - `a`, `b`, ... := allocated units, one letter per allocation.
+//!
+//! `|4| aaa~bbbb |2| cc###### || ########`
+//!
+//! One character (or SVG cell) per unit, so keep it for small ranges (tests, bug reports of a few slabs).
+
+use std::fmt::Write;
+
+use mem_utils::RangeOf;
+
+/////////////////////////////////////////////////////////////////////////////
+// Structures
+/////////////////////////////////////////////////////////////////////////////
+
+/// A slab, or the whole range for suballocators without slabs.
+pub struct MemoryMapRow<Unit> {
+    pub range: RangeOf<Unit>,
+    pub label: Option<String>, // `|label|` before units, `None` for suballocators without slabs
+    pub class: Option<usize>,  // colour in SVG
+    pub allocations: Vec<MemoryMapAllocation<Unit>>, // sorted by offset
+}
+
+pub struct MemoryMapAllocation<Unit> {
+    pub range: RangeOf<Unit>,
+    pub reserved_size: usize, // >= `range.size`, difference is padding
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Trait
+/////////////////////////////////////////////////////////////////////////////
+
+pub trait MemoryMap<Unit> {
+    /// Rows sorted by offset.
+    fn memory_map(&self) -> Vec<MemoryMapRow<Unit>>;
+
+    /// Allocations are lettered `a` to `z` in offset order (cycling).
+    fn to_ascii(&self) -> String {
+        self.to_ascii_with(|allocation_index, _| letter_from(allocation_index))
+    }
+
+    /// Allocations are lettered by `letter(allocation index in offset order, allocation range)`.
+    fn to_ascii_with(&self, letter: impl FnMut(usize, RangeOf<Unit>) -> char) -> String {
+        ascii_from(&self.memory_map(), letter)
+    }
+
+    /// One row per slab, coloured by class, darker for allocated units.
+    fn to_svg(&self) -> String {
+        svg_from(&self.memory_map())
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Fonctions
+/////////////////////////////////////////////////////////////////////////////
+
+pub fn letter_from(allocation_index: usize) -> char {
+    (b'a' + (allocation_index % 26) as u8) as char
+}
+
+pub fn ascii_from<Unit>(
+    rows: &[MemoryMapRow<Unit>],
+    mut letter: impl FnMut(usize, RangeOf<Unit>) -> char,
+) -> String {
+    let mut allocation_index = 0;
+    let mut row_strings = Vec::new();
+    for row in rows {
+        let mut row_string = String::new();
+
+        // label
+        if let Some(ref label) = row.label {
+            row_string += &format!("|{label}| ");
+        }
+
+        // units
+        let mut offset = row.range.offset;
+        for allocation in &row.allocations {
+            let allocation_letter = letter(allocation_index, allocation.range);
+            allocation_index += 1;
+
+            row_string.extend(std::iter::repeat_n('#', allocation.range.offset - offset));
+            row_string.extend(std::iter::repeat_n(
+                allocation_letter,
+                allocation.range.size,
+            ));
+            row_string.extend(std::iter::repeat_n(
+                '~',
+                allocation.reserved_size - allocation.range.size,
+            ));
+            offset = allocation.range.offset + allocation.reserved_size;
+        }
+        row_string.extend(std::iter::repeat_n('#', row.range.end() - offset));
+
+        row_strings.push(row_string);
+    }
+    row_strings.join(" ")
+}
+
+pub fn svg_from<Unit>(rows: &[MemoryMapRow<Unit>]) -> String {
+    // layout
+    const UNIT_WIDTH: usize = 12;
+    const ROW_HEIGHT: usize = 16;
+    const LABEL_WIDTH: usize = 48;
+    let unit_count = rows.iter().map(|row| row.range.size).max().unwrap_or(0);
+    let width = LABEL_WIDTH + unit_count * UNIT_WIDTH;
+    let height = rows.len() * ROW_HEIGHT;
+
+    // fill per unit state (hue per class)
+    let hue_from = |class: Option<usize>| class.map_or(210, |class| (class * 47) % 360);
+    let free = "#eeeeee".to_string();
+
+    let mut svg = String::new();
+    // UNWRAP: writing to a `String` never fails
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" font-family="monospace" font-size="10">"#
+    )
+    .unwrap();
+    for (row_index, row) in rows.iter().enumerate() {
+        let y = row_index * ROW_HEIGHT;
+        let hue = hue_from(row.class);
+
+        // label : class & occupancy
+        let allocated_size: usize = row
+            .allocations
+            .iter()
+            .map(|allocation| allocation.range.size)
+            .sum();
+        let label = row.label.as_deref().unwrap_or("");
+        writeln!(
+            svg,
+            r#"  <text x="2" y="{}">|{label}| {allocated_size}/{}</text>"#,
+            y + ROW_HEIGHT - 4,
+            row.range.size
+        )
+        .unwrap();
+
+        // units
+        let mut fills = vec![free.clone(); row.range.size];
+        for allocation in &row.allocations {
+            let start = allocation.range.offset - row.range.offset;
+            for (i, fill) in fills[start..start + allocation.reserved_size]
+                .iter_mut()
+                .enumerate()
+            {
+                let lightness = if i < allocation.range.size { 45 } else { 80 };
+                *fill = format!("hsl({hue},70%,{lightness}%)");
+            }
+        }
+        for (unit_index, fill) in fills.iter().enumerate() {
+            writeln!(
+                svg,
+                r##"  <rect x="{}" y="{}" width="{}" height="{}" fill="{fill}" stroke="#ffffff"/>"##,
+                LABEL_WIDTH + unit_index * UNIT_WIDTH,
+                y,
+                UNIT_WIDTH,
+                ROW_HEIGHT,
+            )
+            .unwrap();
+        }
+    }
+    svg += "</svg>\n";
+    svg
+}
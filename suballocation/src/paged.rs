@@ -0,0 +1,395 @@
+//! Suballocator spanning several pages (one backing buffer per page), created on demand.
+//!
+//! - `|n|` := page index.
+//!
+//! `|0| aaaabbbb |1| cc######`
+//!
+//! - Allocate `dddd` : page 0 is full, so it lands in page 1.
+//!
+//! `|0| aaaabbbb |1| ccdddd##`
+//!
+//! - Allocate `eeee` : all pages are full, so page 2 is created by the factory.
+//!
+//! `|0| aaaabbbb |1| ccdddd## |2| eeee####`
+//!
+//! - Deallocate `cc`, `dddd` & release empty pages : page 1 is handed back to the caller (to destroy its buffer).
+//!
+//! `|0| aaaabbbb |2| eeee####`
+//!
+//! Index of released pages is reused by the next created page.
+//!
+//! Ranges are local to their page, allocations returns `PagedRange` (page index & range).
+
+use std::{fmt::Debug, marker::PhantomData};
+
+use mem_utils::RangeOf;
+
+use super::{ArrayOfUnitSuballocation, Result, SuballocationError};
+
+/////////////////////////////////////////////////////////////////////////////
+// Structures
+/////////////////////////////////////////////////////////////////////////////
+
+/// Pages are suballocators over their own range, created by `page_factory(page index)`.
+pub struct PagedSuballocator<U, S, F> {
+    pages: Vec<Option<Page<S>>>, // `None` := released page (index reused first)
+    page_factory: F,
+    max_page_count: usize,
+    unit: PhantomData<U>,
+}
+
+struct Page<S> {
+    suballocator: S,
+    allocation_count: usize,
+}
+
+/// Allocated range inside a page.
+pub struct PagedRange<U> {
+    pub page: usize,
+    pub range: RangeOf<U>,
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////////
+
+//------------// paged suballocator //------------//
+
+/// New
+impl<U, S: ArrayOfUnitSuballocation<U>, F: FnMut(usize) -> Result<S>> PagedSuballocator<U, S, F> {
+    /// No page is created until the first allocation.
+    ///
+    /// `page_factory` might fail (buffer creation for example), its error is returned by the allocation.
+    pub fn new(max_page_count: usize, page_factory: F) -> Result<PagedSuballocator<U, S, F>> {
+        // check
+        if max_page_count == 0 {
+            return Err(SuballocationError::invalid_configuration(
+                "max page count should be non zero",
+            ));
+        }
+
+        Ok(PagedSuballocator {
+            pages: Vec::new(),
+            page_factory,
+            max_page_count,
+            unit: PhantomData,
+        })
+    }
+}
+
+/// Utils
+impl<U, S: ArrayOfUnitSuballocation<U>, F: FnMut(usize) -> Result<S>> PagedSuballocator<U, S, F> {
+    fn page_from(&self, page_index: usize) -> Result<&Page<S>> {
+        self.pages
+            .get(page_index)
+            .ok_or(SuballocationError::OutOfBounds)?
+            .as_ref()
+            .ok_or(SuballocationError::NotAllocated)
+    }
+
+    fn page_mut_from(&mut self, page_index: usize) -> Result<&mut Page<S>> {
+        self.pages
+            .get_mut(page_index)
+            .ok_or(SuballocationError::OutOfBounds)?
+            .as_mut()
+            .ok_or(SuballocationError::NotAllocated)
+    }
+
+    /// Page index of the new page, reusing released indices first.
+    fn create_page(&mut self) -> Result<usize> {
+        // check
+        if self.page_count() >= self.max_page_count {
+            return Err(SuballocationError::OutOfMemory);
+        }
+
+        // create
+        let page_index = self
+            .pages
+            .iter()
+            .position(Option::is_none)
+            .unwrap_or(self.pages.len());
+        let page = Page {
+            suballocator: (self.page_factory)(page_index)?,
+            allocation_count: 0,
+        };
+
+        // insert
+        if page_index == self.pages.len() {
+            self.pages.push(Some(page));
+        } else {
+            self.pages[page_index] = Some(page);
+        }
+        Ok(page_index)
+    }
+}
+
+/// Pages
+impl<U, S: ArrayOfUnitSuballocation<U>, F: FnMut(usize) -> Result<S>> PagedSuballocator<U, S, F> {
+    /// Live pages (released ones aren't counted).
+    pub fn page_count(&self) -> usize {
+        self.pages.iter().flatten().count()
+    }
+
+    pub fn max_page_count(&self) -> usize {
+        self.max_page_count
+    }
+
+    pub fn page(&self, page_index: usize) -> Option<&S> {
+        self.page_from(page_index)
+            .ok()
+            .map(|page| &page.suballocator)
+    }
+
+    /// Live pages with their index.
+    pub fn pages(&self) -> impl Iterator<Item = (usize, &S)> {
+        self.pages
+            .iter()
+            .enumerate()
+            .filter_map(|(page_index, page)| Some((page_index, &page.as_ref()?.suballocator)))
+    }
+
+    /// Remove empty pages and return them, so that their backing buffers can be destroyed.
+    pub fn release_empty_pages(&mut self) -> Vec<(usize, S)> {
+        let mut released_pages = Vec::new();
+        for (page_index, page) in self.pages.iter_mut().enumerate() {
+            if page.as_ref().is_some_and(|page| page.allocation_count == 0) {
+                // UNWRAP: checked above
+                released_pages.push((page_index, page.take().unwrap().suballocator));
+            }
+        }
+
+        // trailing released pages are forgotten
+        while let Some(None) = self.pages.last() {
+            self.pages.pop();
+        }
+
+        released_pages
+    }
+}
+
+/// Suballocate
+impl<U, S: ArrayOfUnitSuballocation<U>, F: FnMut(usize) -> Result<S>> PagedSuballocator<U, S, F> {
+    //------------// query //------------//
+
+    /// In a live page (a new page might still fit it).
+    pub fn can_allocate(&self, size: usize) -> bool {
+        self.pages
+            .iter()
+            .flatten()
+            .any(|page| page.suballocator.can_allocate(size))
+    }
+
+    pub fn is_allocated(&self, paged_range: PagedRange<U>) -> bool {
+        self.page_from(paged_range.page)
+            .is_ok_and(|page| page.suballocator.is_allocated(paged_range.range))
+    }
+
+    //------------// suballocate //------------//
+
+    pub fn allocate(&mut self, size: usize) -> Result<PagedRange<U>> {
+        self.allocate_aligned(size, 1)
+    }
+
+    /// Offset of allocated range is a multiple of `align` (in units, relative to its page).
+    ///
+    /// Lowest pages first (so that highest pages empty out), then a new page.
+    pub fn allocate_aligned(&mut self, size: usize, align: usize) -> Result<PagedRange<U>> {
+        // live pages
+        for (page_index, page) in self.pages.iter_mut().enumerate() {
+            let Some(page) = page else {
+                continue;
+            };
+            match page.suballocator.allocate_aligned(size, align) {
+                Ok(range) => {
+                    page.allocation_count += 1;
+                    return Ok(PagedRange::new(page_index, range));
+                }
+                Err(SuballocationError::OutOfMemory) => continue,
+                Err(error) => return Err(error), // same in every page
+            }
+        }
+
+        // new page (kept even if allocation fails, until released)
+        let page_index = self.create_page()?;
+        // UNWRAP: just created
+        let page = self.pages[page_index].as_mut().unwrap();
+        let range = page.suballocator.allocate_aligned(size, align)?;
+        page.allocation_count += 1;
+        Ok(PagedRange::new(page_index, range))
+    }
+
+    pub fn deallocate(&mut self, paged_range: PagedRange<U>) -> Result<()> {
+        let page = self.page_mut_from(paged_range.page)?;
+        page.suballocator.deallocate(paged_range.range)?;
+        page.allocation_count -= 1;
+        Ok(())
+    }
+
+    pub fn reallocate(&mut self, paged_range: PagedRange<U>, size: usize) -> Result<PagedRange<U>> {
+        self.reallocate_aligned(paged_range, size, 1)
+    }
+
+    /// Reallocate inside its page if possible, otherwise move to another page.
+    ///
+    /// Moving data (possibly across buffers) is up to the caller.
+    pub fn reallocate_aligned(
+        &mut self,
+        paged_range: PagedRange<U>,
+        size: usize,
+        align: usize,
+    ) -> Result<PagedRange<U>> {
+        // inside its page
+        let page = self.page_mut_from(paged_range.page)?;
+        match page
+            .suballocator
+            .reallocate_aligned(paged_range.range, size, align)
+        {
+            Ok(range) => return Ok(PagedRange::new(paged_range.page, range)),
+            Err(SuballocationError::OutOfMemory) => {}
+            Err(error) => return Err(error),
+        }
+
+        // another page (old range is still allocated until the new one is)
+        let new_paged_range = self.allocate_aligned(size, align)?;
+        self.deallocate(paged_range)?;
+        Ok(new_paged_range)
+    }
+
+    //------------// debug //------------//
+
+    pub fn allocations(&self) -> Vec<PagedRange<U>> {
+        self.pages()
+            .flat_map(|(page_index, suballocator)| {
+                suballocator
+                    .allocations()
+                    .into_iter()
+                    .map(move |range| PagedRange::new(page_index, range))
+            })
+            .collect()
+    }
+}
+
+//------------// paged range //------------//
+
+/// New
+impl<U> PagedRange<U> {
+    pub fn new(page: usize, range: RangeOf<U>) -> PagedRange<U> {
+        PagedRange { page, range }
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Trivial implementations : Debug, Clone, Copy, PartialEq, Eq
+/////////////////////////////////////////////////////////////////////////////
+// Can't be derived because it would require U to implement them as well (even if phantom).
+
+/// Debug
+impl<U> Debug for PagedRange<U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PagedRange")
+            .field("page", &self.page)
+            .field("range", &self.range)
+            .finish()
+    }
+}
+
+/// Clone & Copy
+impl<U> Clone for PagedRange<U> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<U> Copy for PagedRange<U> {}
+
+/// PartialEq & Eq
+impl<U> PartialEq for PagedRange<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.page == other.page && self.range == other.range
+    }
+}
+impl<U> Eq for PagedRange<U> {}
+
+/////////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::segregated_slab::{SegregatedSlabConfiguration, SegregatedSlabSuballocator};
+
+    /// Pages of 8 units, 2 slabs of 4 units, classes 2 & 4.
+    fn page_from(_page_index: usize) -> Result<SegregatedSlabSuballocator<u32>> {
+        SegregatedSlabSuballocator::new_from_configuration(SegregatedSlabConfiguration::pot(
+            RangeOf::new(0, 8),
+            4,
+            2,
+        )?)
+    }
+
+    #[test]
+    fn pages() {
+        let mut created_pages = Vec::new();
+        let mut paged = PagedSuballocator::new(2, |page_index| {
+            created_pages.push(page_index);
+            page_from(page_index)
+        })
+        .unwrap();
+        assert_eq!(paged.page_count(), 0);
+
+        // page 0, then page 1 once page 0 is full
+        let a = paged.allocate(4).unwrap();
+        let b = paged.allocate(3).unwrap();
+        let c = paged.allocate(2).unwrap();
+        let d = paged.allocate(1).unwrap();
+        let e = paged.allocate(4).unwrap();
+        assert_eq!((a.page, b.page, c.page, d.page, e.page), (0, 0, 1, 1, 1));
+        assert_eq!(c.range, RangeOf::new(0, 2));
+        assert_eq!(paged.page_count(), 2);
+
+        // max page count reached, errors shared by all pages don't create a page
+        assert_eq!(paged.allocate(4), Err(SuballocationError::OutOfMemory));
+        assert_eq!(
+            paged.allocate(5),
+            Err(SuballocationError::SizeTooLarge { max: 4 })
+        );
+        assert_eq!(paged.allocations(), vec![a, b, c, d, e]);
+
+        // reallocate moves to another page when its page is full
+        paged.deallocate(b).unwrap();
+        let c = paged.reallocate(c, 4).unwrap();
+        assert_eq!(c, PagedRange::new(0, RangeOf::new(4, 4)));
+        assert_eq!(
+            paged.deallocate(PagedRange::new(2, RangeOf::new(0, 4))),
+            Err(SuballocationError::OutOfBounds)
+        );
+
+        // release empty pages
+        paged.deallocate(a).unwrap();
+        paged.deallocate(c).unwrap();
+        let released_pages = paged.release_empty_pages();
+        assert_eq!(
+            released_pages
+                .iter()
+                .map(|(page_index, _)| *page_index)
+                .collect::<Vec<_>>(),
+            vec![0]
+        );
+        assert!(paged.page(0).is_none());
+        assert!(!paged.is_allocated(a));
+        assert_eq!(paged.deallocate(a), Err(SuballocationError::NotAllocated));
+
+        // released index is reused
+        let f = paged.allocate(4).unwrap();
+        assert_eq!(f.page, 0);
+        assert_eq!(
+            paged
+                .pages()
+                .map(|(page_index, _)| page_index)
+                .collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        drop(paged);
+        assert_eq!(created_pages, vec![0, 1, 0]);
+    }
+}
@@ -0,0 +1,289 @@
+//! Ring allocator, for data living a few frames (until a fence signals).
+//!
+//! Allocations are tagged with a frame index and packed after the previous ones, wrapping around at the end of the range.
+//! Retiring a frame frees every allocation of this frame and older ones.
+//!
+//! - `#` := free unit.
+//! - `~` := skipped units (not enough space before the end of the range).
+//!
+//! `################`
+//!
+//! - Allocate `aaaa` (frame 0), `bbbbbb` (frame 1), `ccc` (frame 2) :
+//!
+//! `aaaabbbbbbccc###`
+//!
+//! - Retire frame 0 :
+//!
+//! `####bbbbbbccc###`
+//!
+//! - Allocate `dddd` (frame 3) : not enough space at the end, so skip it and wrap around.
+//!
+//! `ddddbbbbbbccc~~~`
+//!
+//! - Retire frame 2 : `bbbbbb`, `ccc` and skipped units are freed.
+//!
+//! `dddd############`
+
+use std::collections::VecDeque;
+
+use mem_utils::{RangeOf, align_up};
+
+use super::{
+    MemoryMap, MemoryMapAllocation, MemoryMapRow, Result, SuballocationError, check_align,
+};
+
+/////////////////////////////////////////////////////////////////////////////
+// Structure
+/////////////////////////////////////////////////////////////////////////////
+
+/// Invariants :
+/// - `frames` empty <=> ring empty <=> `head` == `tail` == `range.offset`.
+/// - `frames` is sorted by increasing frame index.
+pub struct RingSuballocator<U> {
+    range: RangeOf<U>,
+    head: usize, // absolute offset after newest allocation
+    tail: usize, // absolute offset of oldest allocation
+    wrap: usize, // absolute offset of skipped units of last wrap (meaningful while wrapped)
+    frames: VecDeque<Frame>,
+}
+
+struct Frame {
+    index: u64,
+    start: usize, // absolute offset of first allocation of the frame
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////////
+
+/// New
+impl<U> RingSuballocator<U> {
+    pub fn new(range: RangeOf<U>) -> RingSuballocator<U> {
+        RingSuballocator {
+            range,
+            head: range.offset,
+            tail: range.offset,
+            wrap: range.end(),
+            frames: VecDeque::new(),
+        }
+    }
+}
+
+/// Query
+impl<U> RingSuballocator<U> {
+    pub fn range(&self) -> RangeOf<U> {
+        self.range
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn can_allocate(&self, size: usize) -> bool {
+        self.aligned_offset_from(size, 1).is_ok()
+    }
+}
+
+/// Suballocate
+impl<U> RingSuballocator<U> {
+    /// `frame` can't be older than last allocation frame.
+    pub fn allocate(&mut self, size: usize, frame: u64) -> Result<RangeOf<U>> {
+        self.allocate_aligned(size, 1, frame)
+    }
+
+    /// `frame` can't be older than last allocation frame.
+    pub fn allocate_aligned(
+        &mut self,
+        size: usize,
+        align: usize,
+        frame: u64,
+    ) -> Result<RangeOf<U>> {
+        // check
+        if let Some(last_frame) = self.frames.back()
+            && frame < last_frame.index
+        {
+            return Err(SuballocationError::FrameOutOfOrder {
+                frame,
+                last_frame: last_frame.index,
+            });
+        }
+        let offset = self.aligned_offset_from(size, align)?;
+
+        // allocate (units from head to the end are skipped if wrapping around)
+        if offset < self.head {
+            self.wrap = self.head;
+        }
+        self.head = offset + size;
+
+        // new frame ?
+        if self
+            .frames
+            .back()
+            .is_none_or(|last_frame| last_frame.index != frame)
+        {
+            if self.frames.is_empty() {
+                self.tail = offset;
+            }
+            self.frames.push_back(Frame {
+                index: frame,
+                start: offset,
+            });
+        }
+
+        Ok(RangeOf::new(offset, size))
+    }
+
+    /// Free allocations of `frame` and older frames.
+    pub fn retire(&mut self, frame: u64) {
+        while self
+            .frames
+            .front()
+            .is_some_and(|oldest_frame| oldest_frame.index <= frame)
+        {
+            self.frames.pop_front();
+        }
+
+        if let Some(oldest_frame) = self.frames.front() {
+            // skipped units before oldest frame are freed as well
+            self.tail = oldest_frame.start;
+        } else {
+            // restart from the beginning when empty
+            self.head = self.range.offset;
+            self.tail = self.range.offset;
+        }
+    }
+}
+
+/// Memory map
+impl<U> MemoryMap<U> for RingSuballocator<U> {
+    /// Allocations aren't tracked : each frame is shown as a single allocation (two if it wraps around).
+    fn memory_map(&self) -> Vec<MemoryMapRow<U>> {
+        let mut allocations = Vec::new();
+        for (frame_index, frame) in self.frames.iter().enumerate() {
+            let end = self
+                .frames
+                .get(frame_index + 1)
+                .map_or(self.head, |next_frame| next_frame.start);
+
+            if frame.start < end {
+                allocations.push(MemoryMapAllocation {
+                    range: RangeOf::new(frame.start, end - frame.start),
+                    reserved_size: end - frame.start,
+                });
+            } else {
+                // wrapped : until skipped units, then from the beginning
+                allocations.push(MemoryMapAllocation {
+                    range: RangeOf::new(frame.start, self.wrap - frame.start),
+                    reserved_size: self.range.end() - frame.start,
+                });
+                if end > self.range.offset {
+                    allocations.push(MemoryMapAllocation {
+                        range: RangeOf::new(self.range.offset, end - self.range.offset),
+                        reserved_size: end - self.range.offset,
+                    });
+                }
+            }
+        }
+        allocations.sort_unstable_by_key(|allocation| allocation.range.offset);
+
+        vec![MemoryMapRow {
+            range: self.range,
+            label: None,
+            class: None,
+            allocations,
+        }]
+    }
+}
+
+/// Utils
+impl<U> RingSuballocator<U> {
+    fn aligned_offset_from(&self, size: usize, align: usize) -> Result<usize> {
+        // check
+        if size == 0 {
+            return Err(SuballocationError::SizeZero);
+        }
+        if size > self.range.size {
+            return Err(SuballocationError::SizeTooLarge {
+                max: self.range.size,
+            });
+        }
+        check_align(align)?;
+
+        // free regions, in allocation order
+        let (start, end) = (self.range.offset, self.range.end());
+        let free_regions = if self.frames.is_empty() {
+            [start..end, end..end]
+        } else if self.tail < self.head {
+            // not wrapped : after head, then before tail
+            [self.head..end, start..self.tail]
+        } else {
+            // wrapped (or full if `head` == `tail`) : between head and tail
+            [self.head..self.tail, end..end]
+        };
+
+        // first fit
+        free_regions
+            .into_iter()
+            .map(|free_region| (align_up(free_region.start, align), free_region.end))
+            .find(|(offset, free_region_end)| offset + size <= *free_region_end)
+            .map(|(offset, _)| offset)
+            .ok_or(SuballocationError::OutOfMemory)
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MemoryMap;
+
+    /// Same steps as module documentation.
+    #[test]
+    fn wrap_and_retire() {
+        let mut allocator = RingSuballocator::<u32>::new(RangeOf::new(0, 16));
+
+        let a = allocator.allocate(4, 0).unwrap();
+        let b = allocator.allocate(6, 1).unwrap();
+        let c = allocator.allocate(3, 2).unwrap();
+        assert_eq!(
+            (a, b, c),
+            (RangeOf::new(0, 4), RangeOf::new(4, 6), RangeOf::new(10, 3))
+        );
+        assert_eq!(
+            allocator.allocate(4, 3),
+            Err(SuballocationError::OutOfMemory)
+        );
+
+        // wrap
+        allocator.retire(0);
+        let d = allocator.allocate(4, 3).unwrap();
+        assert_eq!(d, RangeOf::new(0, 4));
+        assert!(!allocator.can_allocate(1));
+        assert_eq!(allocator.to_ascii(), "aaaabbbbbbccc~~~");
+
+        // older frame is refused
+        assert_eq!(
+            allocator.allocate(1, 2),
+            Err(SuballocationError::FrameOutOfOrder {
+                frame: 2,
+                last_frame: 3
+            })
+        );
+
+        // free `b`, `c` & skipped units
+        allocator.retire(2);
+        assert_eq!(allocator.to_ascii(), "aaaa############");
+        assert_eq!(
+            allocator.allocate_aligned(12, 4, 3).unwrap(),
+            RangeOf::new(4, 12)
+        );
+
+        // empty => restart from the beginning
+        allocator.retire(3);
+        assert!(allocator.is_empty());
+        assert_eq!(allocator.allocate(16, 4).unwrap(), RangeOf::new(0, 16));
+    }
+}
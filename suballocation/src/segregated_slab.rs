@@ -27,7 +27,10 @@
 //! 1. Find item's class (closest upper class).
 //! 2. Search space among slabs of this class.
 //! 3. If no space among them, then search a free slab to assign it item's class and allocate a slot from it.
-//! 4. If no free slabs, then fail allocation.
+//! 4. If no free slabs, then try bigger classes (overallocate) if the fallback policy allows it.
+//! 5. Otherwise fail allocation.
+//!
+//! When an alignment is requested, only slots whose offset is a multiple of it are considered.
 //!
 //! ## Examples
 //!
@@ -78,6 +81,24 @@
 //!
 //! `|4| bbbb#### |2| ##c~#### || ######## || ########`
 //!
+//! - Reallocate `bbbb` to `bbbbbb` (growing in place into the next free slot before trying to move, even beyond biggest class) :
+//!
+//! `|4| bbbbbb~~ |2| ##c~#### || ######## || ########`
+//!
+//! # Resizing
+//!
+//! ## Examples
+//!
+//! Starting from last state at the end of "Reallocation".
+//!
+//! - Grow by 2 slabs (extra size must be a multiple of last slab group size) :
+//!
+//! `|4| bbbbbb~~ |2| ##c~#### || ######## || ######## || ######## || ########`
+//!
+//! - Shrink to fit (release trailing empty slabs) :
+//!
+//! `|4| bbbbbb~~ |2| ##c~####`
+//!
 //! # Analysis
 //!
 //! ## Pros and cons
@@ -99,27 +120,32 @@
 //! - Slab size = highest class is a good fit (maximize ratio "slabs / classes").
 //! - Powers of 2 classes are great for varying item sizes (quite polyvalent, ~25% internal fragmentation).
 //! - If item sizes are few and known in advance, use them for classes (0% internal fragmentation).
+//! - If item sizes are spread, use several slab groups : small classes in small slabs, big classes in big slabs.
 //!
-//! # Todo
-//!
-//! - Might try overallocate by checking bigger class.
-//! - Might add new logic to reallocation.
+//! `|8| aaaaaaaa |2| bb###### |24| cccccccccccccccccccc~~~~`
+//! - When the ratio "slabs / classes" is low, a fallback policy trades internal fragmentation for less allocation failures (measure it with `stats`).
 
+mod concurrent;
 mod new_from_configuration;
 mod slab;
+mod snapshot;
 #[cfg(test)]
 mod test;
 
 use mem_utils::RangeOf;
 use rustc_hash::FxHashMap;
 
-use super::ArrayOfUnitSuballocation;
+use super::{
+    ArrayOfUnitSuballocation, MemoryMap, MemoryMapAllocation, MemoryMapRow, Result,
+    SuballocationError, check_align,
+};
 
-pub use new_from_configuration::SegregatedSlabConfiguration;
+pub use concurrent::ConcurrentSegregatedSlabSuballocator;
+pub use new_from_configuration::{
+    FallbackPolicy, SegregatedSlabConfiguration, SlabGroupConfiguration,
+};
 use slab::{Occupation, Slab};
-
-type Error = Box<dyn std::error::Error>;
-type Result<T> = std::result::Result<T, Error>;
+pub use snapshot::{SegregatedSlabSnapshot, SlabSnapshot};
 
 /////////////////////////////////////////////////////////////////////////////
 // Structure
@@ -128,23 +154,50 @@ type Result<T> = std::result::Result<T, Error>;
 /// Suballocate using segregated slab algorithm.
 ///
 /// Invariants :
-/// - slab index in `empty_slab_indices` <=> slab occupation == Occupation::Empty
+/// - slab index in `empty_slab_indices_per_slab_size` <=> slab occupation == Occupation::Empty
 /// - slab index in `partial_slab_indices_per_class` <=> slab occupation == Occupation::Partial
+/// - `slabs` sorted by offset, covering `range`.
 pub struct SegregatedSlabSuballocator<U> {
-    range: RangeOf<U>, // immutable
+    range: RangeOf<U>, // only changed by `grow` & `shrink_to_fit`
 
     // sizes
-    slab: usize,         // immutable
-    classes: Vec<usize>, // immutable
+    growth_slab: usize,  // immutable, slab size of slabs added by `grow`
+    classes: Vec<usize>, // immutable, sorted
+    slab_size_per_class: FxHashMap<usize, usize>, // immutable
+
+    // policy
+    fallback_policy: FallbackPolicy, // immutable
+    stats: SegregatedSlabStats,
 
     // slabs
     slabs: Vec<Slab<U>>,
 
     // indices
-    empty_slab_indices: Vec<usize>,
+    empty_slab_indices_per_slab_size: FxHashMap<usize, Vec<usize>>,
     partial_slab_indices_per_class: FxHashMap<usize, Vec<usize>>,
 }
 
+/// Counters since creation & current usage, to measure the tradeoffs of a configuration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SegregatedSlabStats {
+    pub fallback_policy: FallbackPolicy,
+
+    // allocations
+    pub allocations: usize,
+    pub fallback_allocations: usize, // in a bigger class than the size class (moving reallocations included)
+    pub failed_allocations: usize,   // out of memory
+
+    // reallocations
+    pub in_place_reallocations: usize,
+    pub moved_reallocations: usize,
+    pub failed_reallocations: usize, // out of memory
+
+    // usage
+    pub allocated_size: usize, // sum of allocation sizes
+    pub reserved_size: usize, // sum of slots used by allocations (`reserved - allocated` := internal fragmentation)
+}
+
 /////////////////////////////////////////////////////////////////////////////
 // Implementations
 /////////////////////////////////////////////////////////////////////////////
@@ -161,21 +214,215 @@ impl<U> SegregatedSlabSuballocator<U> {
 /// Utils
 impl<U> SegregatedSlabSuballocator<U> {
     fn class_from(&self, size: usize) -> Result<usize> {
-        if size == 0 {
-            return Err("size null".into());
-        }
-        self.classes
-            .iter()
-            .find(|class| **class >= size)
-            .copied()
-            .ok_or("size too big".into())
+        class_from(&self.classes, size)
     }
 
     fn slab_index_from(&self, range: RangeOf<U>) -> Result<usize> {
         if !range.is_subrange_of(&self.range) {
-            return Err("out of bounds".into());
+            return Err(SuballocationError::OutOfBounds);
+        }
+        // last slab starting before `range`
+        self.slabs
+            .partition_point(|slab| slab.range().offset <= range.offset)
+            .checked_sub(1)
+            .ok_or(SuballocationError::OutOfBounds) // no slabs when shrunk to nothing
+    }
+
+    fn empty_slab_indices_for(&self, class: usize) -> &Vec<usize> {
+        let slab_size = self.slab_size_per_class[&class];
+        &self.empty_slab_indices_per_slab_size[&slab_size]
+    }
+
+    fn candidate_class_indices_from(&self, class: usize) -> std::ops::Range<usize> {
+        candidate_class_indices_from(&self.classes, class, self.fallback_policy)
+    }
+}
+
+/// Stats
+impl<U> SegregatedSlabSuballocator<U> {
+    pub fn fallback_policy(&self) -> FallbackPolicy {
+        self.fallback_policy
+    }
+
+    pub fn stats(&self) -> SegregatedSlabStats {
+        let mut stats = self.stats;
+        for slab in &self.slabs {
+            let (allocated_size, reserved_size) = slab.usage();
+            stats.allocated_size += allocated_size;
+            stats.reserved_size += reserved_size;
         }
-        Ok((range.offset - self.range.offset) / self.slab)
+        stats
+    }
+}
+
+/// Snapshot
+impl<U> SegregatedSlabSuballocator<U> {
+    pub fn snapshot(&self) -> SegregatedSlabSnapshot<U> {
+        snapshot::snapshot_from(self)
+    }
+
+    /// Identical to the snapshotted allocator (next allocations included).
+    pub fn restore(snapshot: SegregatedSlabSnapshot<U>) -> Result<SegregatedSlabSuballocator<U>> {
+        snapshot::restore(snapshot)
+    }
+}
+
+/// Memory map
+impl<U> MemoryMap<U> for SegregatedSlabSuballocator<U> {
+    fn memory_map(&self) -> Vec<MemoryMapRow<U>> {
+        self.slabs.iter().map(memory_map_row_from).collect()
+    }
+}
+
+/// Allocate in a class
+impl<U> SegregatedSlabSuballocator<U> {
+    fn can_allocate_in_class(&self, class: usize, align: usize) -> bool {
+        // is there class partial slabs with an aligned free slot ?
+        let partial_slab_indices = &self.partial_slab_indices_per_class[&class];
+        let there_is_partial_slabs = partial_slab_indices
+            .iter()
+            .any(|slab_index| self.slabs[*slab_index].can_allocate_aligned(align));
+
+        // is there empty slabs for this class with an aligned slot ?
+        let there_is_empty_slabs = self
+            .empty_slab_indices_for(class)
+            .iter()
+            .any(|slab_index| self.slabs[*slab_index].can_allocate_aligned_as(class, align));
+
+        //////
+        there_is_partial_slabs || there_is_empty_slabs
+    }
+
+    /// Unsafe if `size` > `class`.
+    unsafe fn allocate_in_class(
+        &mut self,
+        class: usize,
+        size: usize,
+        align: usize,
+    ) -> Option<RangeOf<U>> {
+        // extract `class` partial slabs
+        let partial_slab_indices = self.partial_slab_indices_per_class.get_mut(&class).unwrap();
+
+        // search a partial slab with an aligned free slot (top of the stack first)
+        let partial_slab_position = partial_slab_indices
+            .iter()
+            .rposition(|slab_index| self.slabs[*slab_index].can_allocate_aligned(align));
+
+        if let Some(partial_slab_position) = partial_slab_position {
+            // get partial slab
+            let slab_index = partial_slab_indices[partial_slab_position];
+            let slab = &mut self.slabs[slab_index];
+
+            // allocate
+            let allocated_range = unsafe { slab.allocate(size, align) };
+            let new_occupation = slab.occupation();
+
+            // update indices
+            if new_occupation == Occupation::Full {
+                partial_slab_indices.remove(partial_slab_position);
+            }
+
+            return Some(allocated_range);
+        }
+
+        // search an empty slab with an aligned slot once assigned `class` (top of the stack first)
+        let empty_slab_indices = self
+            .empty_slab_indices_per_slab_size
+            .get_mut(&self.slab_size_per_class[&class])
+            .unwrap();
+        let empty_slab_position = empty_slab_indices.iter().rposition(|slab_index| {
+            self.slabs[*slab_index].can_allocate_aligned_as(class, align)
+        })?;
+
+        // get empty slab
+        let slab_index = empty_slab_indices.remove(empty_slab_position);
+        let slab = &mut self.slabs[slab_index];
+
+        // assign class
+        slab.reset_slots(Some(class));
+
+        // allocate
+        let allocated_range = unsafe { slab.allocate(size, align) };
+        let new_occupation = slab.occupation();
+
+        // update indices
+        if new_occupation == Occupation::Partial {
+            // do nothing if `new_occupation` == `Occupation::Full`
+            partial_slab_indices.push(slab_index);
+        }
+
+        Some(allocated_range)
+    }
+
+    /// Allocate in `size` class, then in bigger classes allowed by fallback policy.
+    fn allocate_with_fallback(&mut self, size: usize, align: usize) -> Result<RangeOf<U>> {
+        self.allocate_with_fallback_below(size, align, usize::MAX)
+    }
+
+    /// Same as `allocate_with_fallback`, only in classes smaller than `class_bound`.
+    fn allocate_with_fallback_below(
+        &mut self,
+        size: usize,
+        align: usize,
+        class_bound: usize,
+    ) -> Result<RangeOf<U>> {
+        // check
+        let class = self.class_from(size)?;
+        check_align(align)?;
+
+        // try candidate classes (smallest first)
+        for class_index in self.candidate_class_indices_from(class) {
+            let candidate_class = self.classes[class_index];
+            if candidate_class >= class_bound {
+                break;
+            }
+            if let Some(allocated_range) =
+                unsafe { self.allocate_in_class(candidate_class, size, align) }
+            {
+                if candidate_class != class {
+                    self.stats.fallback_allocations += 1;
+                }
+                return Ok(allocated_range);
+            }
+        }
+
+        Err(SuballocationError::OutOfMemory)
+    }
+
+    /// Unsafe if `can_reallocate_in_place` is false.
+    unsafe fn reallocate_in_place(
+        &mut self,
+        slab_index: usize,
+        range: RangeOf<U>,
+        size: usize,
+    ) -> RangeOf<U> {
+        // extract `range` slab & partial slabs
+        let slab = &mut self.slabs[slab_index];
+        let partial_slab_indices = self
+            .partial_slab_indices_per_class
+            .get_mut(&slab.class().unwrap())
+            .unwrap();
+
+        // reallocate
+        let old_occupation = slab.occupation();
+        let reallocated_range = unsafe { slab.reallocate_in_place(range, size) };
+        let new_occupation = slab.occupation();
+
+        // update indices (growing or shrinking over next slots)
+        match (old_occupation, new_occupation) {
+            (Occupation::Partial, Occupation::Full) => {
+                let slab_index_position = partial_slab_indices
+                    .iter()
+                    .position(|index| *index == slab_index)
+                    .unwrap(); // UNWRAP: old_occupation == Partial
+                partial_slab_indices.remove(slab_index_position);
+            }
+            (Occupation::Full, Occupation::Partial) => partial_slab_indices.push(slab_index),
+            _ => {}
+        }
+
+        self.stats.in_place_reallocations += 1;
+        reallocated_range
     }
 }
 
@@ -183,21 +430,19 @@ impl<U> SegregatedSlabSuballocator<U> {
 impl<U> ArrayOfUnitSuballocation<U> for SegregatedSlabSuballocator<U> {
     //------------// query //------------//
 
+    fn range(&self) -> RangeOf<U> {
+        self.range
+    }
+
     fn can_allocate(&self, size: usize) -> bool {
         // check/extract `size` class
         let Ok(class) = self.class_from(size) else {
             return false;
         };
 
-        // is there class partial slabs ?
-        let partial_slab_indices = self.partial_slab_indices_per_class.get(&class).unwrap();
-        let there_is_partial_slabs = !partial_slab_indices.is_empty();
-
-        // is there empty slabs ?
-        let there_is_empty_slabs = !self.empty_slab_indices.is_empty();
-
-        //////
-        there_is_partial_slabs || there_is_empty_slabs
+        // is there space in `size` class or in fallback classes ?
+        self.candidate_class_indices_from(class)
+            .any(|class_index| self.can_allocate_in_class(self.classes[class_index], 1))
     }
 
     fn is_allocated(&self, range: RangeOf<U>) -> bool {
@@ -216,12 +461,14 @@ impl<U> ArrayOfUnitSuballocation<U> for SegregatedSlabSuballocator<U> {
             return false;
         }
 
-        // extract `range` slab class
-        let slab_index = self.slab_index_from(range).unwrap(); // UNWRAP: is_allocated passed
-        let slab_class = self.slabs[slab_index].class().unwrap(); // UNWRAP: is_allocated passed
+        // is size valid ? (too large is checked by `can_allocate` if not in place)
+        if size == 0 {
+            return false;
+        }
 
         // is in place reallocation possible ?
-        if size <= slab_class {
+        let slab_index = self.slab_index_from(range).unwrap(); // UNWRAP: is_allocated passed
+        if self.slabs[slab_index].can_reallocate_in_place(range, size) {
             return true;
         }
 
@@ -231,67 +478,41 @@ impl<U> ArrayOfUnitSuballocation<U> for SegregatedSlabSuballocator<U> {
 
     //------------// allocate //------------//
 
-    fn allocate(&mut self, size: usize) -> super::Result<RangeOf<U>> {
-        if !self.can_allocate(size) {
-            return Err("cannot allocate".into());
-        }
-
-        // extract `size` class partial slabs
-        let class = self.class_from(size).unwrap(); // UNWRAP: can_allocate passed
-        let partial_slab_indices = self.partial_slab_indices_per_class.get_mut(&class).unwrap();
-        let there_is_partial_slabs = !partial_slab_indices.is_empty();
-
-        if there_is_partial_slabs {
-            // get partial slab
-            let slab_index = partial_slab_indices.last().copied().unwrap();
-            let slab = &mut self.slabs[slab_index];
-
-            // allocate
-            let allocated_range = unsafe { slab.allocate(size) };
-            let new_occupation = slab.occupation();
-
-            // update indices
-            if new_occupation == Occupation::Full {
-                partial_slab_indices.pop();
-            }
-
-            Ok(allocated_range)
-        } else {
-            // get empty slab
-            let slab_index = self.empty_slab_indices.last().copied().unwrap(); // UNWRAP: can_allocate passed & no partial slabs
-            let slab = &mut self.slabs[slab_index];
-
-            // assign class
-            slab.reset_slots(Some(class));
-
-            // allocate
-            let allocated_range = unsafe { slab.allocate(size) };
-            let new_occupation = slab.occupation();
+    fn allocate(&mut self, size: usize) -> Result<RangeOf<U>> {
+        self.allocate_aligned(size, 1)
+    }
 
-            // update indices
-            self.empty_slab_indices.pop();
-            if new_occupation == Occupation::Partial {
-                // do nothing if `new_occupation` == `Occupation::Full`
-                partial_slab_indices.push(slab_index);
-            }
+    fn allocate_aligned(&mut self, size: usize, align: usize) -> Result<RangeOf<U>> {
+        let allocated_range = self.allocate_with_fallback(size, align);
 
-            Ok(allocated_range)
+        // stats
+        match allocated_range {
+            Ok(_) => self.stats.allocations += 1,
+            Err(SuballocationError::OutOfMemory) => self.stats.failed_allocations += 1,
+            Err(_) => {}
         }
+
+        allocated_range
     }
 
-    fn deallocate(&mut self, range: RangeOf<U>) -> super::Result<()> {
-        if !self.is_allocated(range) {
-            return Err("not allocated".into());
+    fn deallocate(&mut self, range: RangeOf<U>) -> Result<()> {
+        // check
+        let slab_index = self.slab_index_from(range)?;
+        if !self.slabs[slab_index].is_allocated(range) {
+            return Err(SuballocationError::NotAllocated);
         }
 
         // extract `range` slab & partial slabs
-        let slab_index = self.slab_index_from(range).unwrap(); // UNWRAP: is_allocated passed
         let slab = &mut self.slabs[slab_index];
         let slab_class = slab.class().unwrap();
         let partial_slab_indices = self
             .partial_slab_indices_per_class
             .get_mut(&slab_class)
             .unwrap();
+        let empty_slab_indices = self
+            .empty_slab_indices_per_slab_size
+            .get_mut(&slab.range().size)
+            .unwrap();
 
         // deallocate
         let old_occupation = slab.occupation();
@@ -306,7 +527,7 @@ impl<U> ArrayOfUnitSuballocation<U> for SegregatedSlabSuballocator<U> {
             }
             (Occupation::Full, Occupation::Empty) => {
                 // push to empty slabs
-                self.empty_slab_indices.push(slab_index);
+                empty_slab_indices.push(slab_index);
             }
             (Occupation::Partial, Occupation::Partial) => {}
             (Occupation::Partial, Occupation::Empty) => {
@@ -315,10 +536,10 @@ impl<U> ArrayOfUnitSuballocation<U> for SegregatedSlabSuballocator<U> {
                     .iter()
                     .position(|index| *index == slab_index)
                     .unwrap(); // UNWRAP: old_occupation == Partial
-                partial_slab_indices.swap_remove(slab_index_position);
+                partial_slab_indices.remove(slab_index_position);
 
                 // push to empty slabs
-                self.empty_slab_indices.push(slab_index);
+                empty_slab_indices.push(slab_index);
             }
             _ => unreachable!(),
         }
@@ -326,41 +547,129 @@ impl<U> ArrayOfUnitSuballocation<U> for SegregatedSlabSuballocator<U> {
         Ok(())
     }
 
-    fn reallocate(&mut self, range: RangeOf<U>, size: usize) -> super::Result<RangeOf<U>> {
-        if !self.can_reallocate(range, size) {
-            return Err("cannot reallocate".into());
+    fn reallocate(&mut self, range: RangeOf<U>, size: usize) -> Result<RangeOf<U>> {
+        self.reallocate_aligned(range, size, 1)
+    }
+
+    fn reallocate_aligned(
+        &mut self,
+        range: RangeOf<U>,
+        size: usize,
+        align: usize,
+    ) -> Result<RangeOf<U>> {
+        // check
+        let slab_index = self.slab_index_from(range)?;
+        if !self.slabs[slab_index].is_allocated(range) {
+            return Err(SuballocationError::NotAllocated);
         }
+        check_align(align)?;
 
         // extract `range` slab class
-        let slab_index = self.slab_index_from(range).unwrap(); // UNWRAP: can_reallocate passed
-        let class = self.slabs[slab_index].class().unwrap(); // UNWRAP: can_reallocate passed
+        let class = self.slabs[slab_index].class().unwrap(); // UNWRAP: is_allocated passed
+
+        // is in place reallocation possible ? (fits its slot or grows into next free slots)
+        let in_place =
+            range.is_aligned(align) && self.slabs[slab_index].can_reallocate_in_place(range, size);
 
         // extract `size` class
-        let new_class = self.class_from(size).unwrap(); // UNWRAP: can_reallocate passed
-
-        // compare classes
-        if new_class == class {
-            // keep actual allocation
-            unsafe { self.slabs[slab_index].reallocate_in_place(range, size) };
-            Ok(RangeOf::new(range.offset, size))
-        } else if new_class < class {
-            // try reallocate smaller
-            if let Ok(new_range) = self.allocate(size) {
-                self.deallocate(range).unwrap(); // UNWRAP: can_reallocate passed
+        let new_class = match self.class_from(size) {
+            Ok(new_class) => new_class,
+            // bigger than all classes : only growing in place is possible
+            Err(SuballocationError::SizeTooLarge { .. }) if in_place => usize::MAX,
+            Err(error) => return Err(error),
+        };
+
+        // keep actual allocation if not shrinking to a smaller class
+        if in_place && new_class >= class {
+            return Ok(unsafe { self.reallocate_in_place(slab_index, range, size) });
+        }
+
+        // try reallocate (smaller, bigger or aligned)
+        // a shrink to a smaller class only moves to a smaller class than its own
+        let class_bound = if in_place { class } else { usize::MAX };
+        match self.allocate_with_fallback_below(size, align, class_bound) {
+            Ok(new_range) => {
+                self.deallocate(range).unwrap(); // UNWRAP: is_allocated passed
+                self.stats.moved_reallocations += 1;
                 Ok(new_range)
-            } else {
+            }
+            Err(_) if in_place => {
                 // keep actual allocation
-                unsafe { self.slabs[slab_index].reallocate_in_place(range, size) };
-                Ok(RangeOf::new(range.offset, size))
+                Ok(unsafe { self.reallocate_in_place(slab_index, range, size) })
+            }
+            Err(error) => {
+                if error == SuballocationError::OutOfMemory {
+                    self.stats.failed_reallocations += 1;
+                }
+                Err(error)
             }
-        } else {
-            // reallocate bigger or fail
-            let new_range = self.allocate(size)?;
-            self.deallocate(range).unwrap(); // UNWRAP: can_reallocate passed
-            Ok(new_range)
         }
     }
 
+    //------------// resize //------------//
+
+    fn grow(&mut self, new_range: RangeOf<U>) -> Result<()> {
+        // check
+        if new_range.offset != self.range.offset {
+            return Err(SuballocationError::invalid_configuration(
+                "cannot grow to a different offset",
+            ));
+        }
+        if new_range.size < self.range.size {
+            return Err(SuballocationError::invalid_configuration(
+                "cannot grow to a smaller size",
+            ));
+        }
+        let extra_size = new_range.size - self.range.size;
+        if !extra_size.is_multiple_of(self.growth_slab) {
+            return Err(SuballocationError::invalid_configuration(format!(
+                "extra size {extra_size} should be a multiple of slab size {}",
+                self.growth_slab
+            )));
+        }
+
+        // new slabs (of last slab group size)
+        let old_slab_count = self.slabs.len();
+        let new_slab_count = extra_size / self.growth_slab;
+        for i in 0..new_slab_count {
+            let slab_range =
+                RangeOf::new(self.range.end() + i * self.growth_slab, self.growth_slab);
+            self.slabs.push(Slab::new(slab_range));
+        }
+
+        // new empty slab indices : at the bottom of the stack so that old slabs are used first
+        let new_slab_indices = (old_slab_count..self.slabs.len()).rev();
+        self.empty_slab_indices_per_slab_size
+            .get_mut(&self.growth_slab)
+            .unwrap()
+            .splice(0..0, new_slab_indices);
+
+        self.range = new_range;
+        Ok(())
+    }
+
+    fn shrink_to_fit(&mut self) -> RangeOf<U> {
+        // count trailing empty slabs
+        let slab_count = self
+            .slabs
+            .iter()
+            .rposition(|slab| slab.occupation() != Occupation::Empty)
+            .map_or(0, |last_used| last_used + 1);
+
+        // release them (empty slabs aren't in partial slabs)
+        self.slabs.truncate(slab_count);
+        for empty_slab_indices in self.empty_slab_indices_per_slab_size.values_mut() {
+            empty_slab_indices.retain(|slab_index| *slab_index < slab_count);
+        }
+
+        let end = self
+            .slabs
+            .last()
+            .map_or(self.range.offset, |slab| slab.range().end());
+        self.range = RangeOf::new(self.range.offset, end - self.range.offset);
+        self.range
+    }
+
     //------------// debug //------------//
 
     fn allocations(&self) -> Vec<RangeOf<U>> {
@@ -371,3 +680,57 @@ impl<U> ArrayOfUnitSuballocation<U> for SegregatedSlabSuballocator<U> {
         allocations
     }
 }
+
+/////////////////////////////////////////////////////////////////////////////
+// Utils
+/////////////////////////////////////////////////////////////////////////////
+
+/// Closest upper class of `size` (`classes` sorted).
+fn class_from(classes: &[usize], size: usize) -> Result<usize> {
+    if size == 0 {
+        return Err(SuballocationError::SizeZero);
+    }
+    classes
+        .iter()
+        .find(|class| **class >= size)
+        .copied()
+        .ok_or(SuballocationError::SizeTooLarge {
+            max: *classes.last().unwrap(), // UNWRAP: classes checked non empty in configuration
+        })
+}
+
+/// Indices in `classes` of classes to try for `class`, following fallback policy.
+fn candidate_class_indices_from(
+    classes: &[usize],
+    class: usize,
+    fallback_policy: FallbackPolicy,
+) -> std::ops::Range<usize> {
+    let class_index = classes.binary_search(&class).unwrap(); // UNWRAP: `class` from `class_from`
+    let end = match fallback_policy {
+        FallbackPolicy::Strict => class_index + 1,
+        FallbackPolicy::NextLargerClass => usize::min(class_index + 2, classes.len()),
+        FallbackPolicy::AnyLargerClass => classes.len(),
+    };
+    class_index..end
+}
+
+/// One row per slab, class hidden when empty.
+fn memory_map_row_from<U>(slab: &Slab<U>) -> MemoryMapRow<U> {
+    let class = slab
+        .class()
+        .filter(|_| slab.occupation() != Occupation::Empty);
+    let allocations = slab
+        .allocations()
+        .into_iter()
+        .map(|range| MemoryMapAllocation {
+            range,
+            reserved_size: range.size.next_multiple_of(class.unwrap()), // UNWRAP: allocated so not empty
+        })
+        .collect();
+    MemoryMapRow {
+        range: slab.range(),
+        label: Some(class.map_or(String::new(), |class| class.to_string())),
+        class,
+        allocations,
+    }
+}
@@ -0,0 +1,277 @@
+use std::sync::Mutex;
+
+use mem_utils::RangeOf;
+use rustc_hash::FxHashMap;
+
+use crate::{MemoryMap, MemoryMapRow, Result, SuballocationError, check_align};
+
+use super::{
+    FallbackPolicy, SegregatedSlabConfiguration, SegregatedSlabSuballocator,
+    candidate_class_indices_from, class_from, memory_map_row_from,
+    slab::{Occupation, Slab},
+};
+
+/////////////////////////////////////////////////////////////////////////////
+// Structure
+/////////////////////////////////////////////////////////////////////////////
+
+/// Segregated slab usable from several threads (`&self`), with one lock per class.
+///
+/// Threads allocating different classes don't wait on each other (except to take an empty slab).
+/// Range is fixed : no `grow` nor `shrink_to_fit`.
+///
+/// Locking order (no deadlock) :
+/// 1. One class (partial slabs).
+/// 2. Empty slabs of a slab size.
+/// 3. One slab.
+///
+/// Invariants : same as `SegregatedSlabSuballocator`.
+pub struct ConcurrentSegregatedSlabSuballocator<U> {
+    range: RangeOf<U>, // immutable
+
+    // sizes
+    classes: Vec<usize>,                          // immutable, sorted
+    slab_size_per_class: FxHashMap<usize, usize>, // immutable
+
+    // policy
+    fallback_policy: FallbackPolicy, // immutable
+
+    // slabs
+    slab_ranges: Vec<RangeOf<U>>, // immutable, sorted by offset (lookup without locking)
+    slabs: Vec<Mutex<Slab<U>>>,
+
+    // indices
+    empty_slab_indices_per_slab_size: FxHashMap<usize, Mutex<Vec<usize>>>,
+    partial_slab_indices_per_class: FxHashMap<usize, Mutex<Vec<usize>>>, // class locks
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////////
+
+/// New
+impl<U> ConcurrentSegregatedSlabSuballocator<U> {
+    pub fn new_from_configuration(
+        configuration: SegregatedSlabConfiguration<U>,
+    ) -> Result<ConcurrentSegregatedSlabSuballocator<U>> {
+        let SegregatedSlabSuballocator {
+            range,
+            classes,
+            slab_size_per_class,
+            fallback_policy,
+            slabs,
+            empty_slab_indices_per_slab_size,
+            partial_slab_indices_per_class,
+            ..
+        } = SegregatedSlabSuballocator::new_from_configuration(configuration)?;
+
+        Ok(ConcurrentSegregatedSlabSuballocator {
+            range,
+            classes,
+            slab_size_per_class,
+            fallback_policy,
+            slab_ranges: slabs.iter().map(Slab::range).collect(),
+            slabs: slabs.into_iter().map(Mutex::new).collect(),
+            empty_slab_indices_per_slab_size: empty_slab_indices_per_slab_size
+                .into_iter()
+                .map(|(slab_size, slab_indices)| (slab_size, Mutex::new(slab_indices)))
+                .collect(),
+            partial_slab_indices_per_class: partial_slab_indices_per_class
+                .into_iter()
+                .map(|(class, slab_indices)| (class, Mutex::new(slab_indices)))
+                .collect(),
+        })
+    }
+}
+
+/// Utils
+impl<U> ConcurrentSegregatedSlabSuballocator<U> {
+    fn slab_index_from(&self, range: RangeOf<U>) -> Result<usize> {
+        if !range.is_subrange_of(&self.range) {
+            return Err(SuballocationError::OutOfBounds);
+        }
+        // last slab starting before `range`
+        self.slab_ranges
+            .partition_point(|slab_range| slab_range.offset <= range.offset)
+            .checked_sub(1)
+            .ok_or(SuballocationError::OutOfBounds)
+    }
+
+    /// Unsafe if `size` > `class`.
+    unsafe fn allocate_in_class(
+        &self,
+        class: usize,
+        size: usize,
+        align: usize,
+    ) -> Option<RangeOf<U>> {
+        // lock `class`
+        let mut partial_slab_indices = self.partial_slab_indices_per_class[&class].lock().unwrap(); // UNWRAP: poisoned only if a thread panicked while holding it
+
+        // search a partial slab with an aligned free slot (top of the stack first)
+        for position in (0..partial_slab_indices.len()).rev() {
+            let slab_index = partial_slab_indices[position];
+            let mut slab = self.slabs[slab_index].lock().unwrap(); // UNWRAP: same as above
+            if slab.can_allocate_aligned(align) {
+                // allocate
+                let allocated_range = unsafe { slab.allocate(size, align) };
+
+                // update indices
+                if slab.occupation() == Occupation::Full {
+                    partial_slab_indices.remove(position);
+                }
+
+                return Some(allocated_range);
+            }
+        }
+
+        // take an empty slab with an aligned slot once assigned `class` (top of the stack first)
+        let slab_index = {
+            let mut empty_slab_indices = self.empty_slab_indices_per_slab_size
+                [&self.slab_size_per_class[&class]]
+                .lock()
+                .unwrap(); // UNWRAP: same as above
+            let empty_slab_position = empty_slab_indices.iter().rposition(|slab_index| {
+                self.slabs[*slab_index]
+                    .lock()
+                    .unwrap() // UNWRAP: same as above
+                    .can_allocate_aligned_as(class, align)
+            })?;
+            empty_slab_indices.remove(empty_slab_position)
+        };
+        let mut slab = self.slabs[slab_index].lock().unwrap(); // UNWRAP: same as above
+
+        // assign class
+        slab.reset_slots(Some(class));
+
+        // allocate
+        let allocated_range = unsafe { slab.allocate(size, align) };
+
+        // update indices
+        if slab.occupation() == Occupation::Partial {
+            partial_slab_indices.push(slab_index);
+        }
+
+        Some(allocated_range)
+    }
+}
+
+/// Suballocate
+impl<U> ConcurrentSegregatedSlabSuballocator<U> {
+    //------------// query //------------//
+
+    pub fn range(&self) -> RangeOf<U> {
+        self.range
+    }
+
+    pub fn fallback_policy(&self) -> FallbackPolicy {
+        self.fallback_policy
+    }
+
+    /// Might be outdated as soon as it returns if other threads deallocate `range`.
+    pub fn is_allocated(&self, range: RangeOf<U>) -> bool {
+        let Ok(slab_index) = self.slab_index_from(range) else {
+            return false;
+        };
+        self.slabs[slab_index].lock().unwrap().is_allocated(range) // UNWRAP: poisoned only if a thread panicked while holding it
+    }
+
+    //------------// suballocate //------------//
+
+    pub fn allocate(&self, size: usize) -> Result<RangeOf<U>> {
+        self.allocate_aligned(size, 1)
+    }
+
+    /// Offset of allocated range is a multiple of `align` (in units).
+    pub fn allocate_aligned(&self, size: usize, align: usize) -> Result<RangeOf<U>> {
+        // check
+        let class = class_from(&self.classes, size)?;
+        check_align(align)?;
+
+        // try candidate classes (smallest first, one class locked at a time)
+        for class_index in candidate_class_indices_from(&self.classes, class, self.fallback_policy)
+        {
+            if let Some(allocated_range) =
+                unsafe { self.allocate_in_class(self.classes[class_index], size, align) }
+            {
+                return Ok(allocated_range);
+            }
+        }
+
+        Err(SuballocationError::OutOfMemory)
+    }
+
+    pub fn deallocate(&self, range: RangeOf<U>) -> Result<()> {
+        // check
+        let slab_index = self.slab_index_from(range)?;
+
+        loop {
+            // extract `range` slab class (might be reassigned until `class` is locked)
+            let Some(class) = self.slabs[slab_index].lock().unwrap().class() else {
+                return Err(SuballocationError::NotAllocated); // never assigned
+            };
+
+            // lock `class` & slab
+            let mut partial_slab_indices =
+                self.partial_slab_indices_per_class[&class].lock().unwrap(); // UNWRAP: poisoned only if a thread panicked while holding it
+            let mut slab = self.slabs[slab_index].lock().unwrap(); // UNWRAP: same as above
+            if slab.class() != Some(class) {
+                continue; // emptied & reassigned meanwhile
+            }
+            if !slab.is_allocated(range) {
+                return Err(SuballocationError::NotAllocated);
+            }
+
+            // deallocate
+            let old_occupation = slab.occupation();
+            unsafe { slab.deallocate(range) };
+            let new_occupation = slab.occupation();
+            drop(slab);
+
+            // update indices (empty slab isn't in any list until pushed, `class` is still locked)
+            let push_to_empty_slabs = || {
+                self.empty_slab_indices_per_slab_size[&self.slab_ranges[slab_index].size]
+                    .lock()
+                    .unwrap() // UNWRAP: same as above
+                    .push(slab_index);
+            };
+            match (old_occupation, new_occupation) {
+                (Occupation::Full, Occupation::Partial) => partial_slab_indices.push(slab_index),
+                (Occupation::Full, Occupation::Empty) => push_to_empty_slabs(),
+                (Occupation::Partial, Occupation::Partial) => {}
+                (Occupation::Partial, Occupation::Empty) => {
+                    let slab_index_position = partial_slab_indices
+                        .iter()
+                        .position(|index| *index == slab_index)
+                        .unwrap(); // UNWRAP: old_occupation == Partial
+                    partial_slab_indices.remove(slab_index_position);
+                    push_to_empty_slabs();
+                }
+                _ => unreachable!(),
+            }
+
+            return Ok(());
+        }
+    }
+
+    //------------// debug //------------//
+
+    /// Slabs are locked one after the other, so it isn't a snapshot if other threads are working.
+    pub fn allocations(&self) -> Vec<RangeOf<U>> {
+        let mut allocations = Vec::new();
+        for slab in &self.slabs {
+            allocations.append(&mut slab.lock().unwrap().allocations()); // UNWRAP: poisoned only if a thread panicked while holding it
+        }
+        allocations
+    }
+}
+
+/// Memory map
+impl<U> MemoryMap<U> for ConcurrentSegregatedSlabSuballocator<U> {
+    /// Slabs are locked one after the other, so it isn't a snapshot if other threads are working.
+    fn memory_map(&self) -> Vec<MemoryMapRow<U>> {
+        self.slabs
+            .iter()
+            .map(|slab| memory_map_row_from(&slab.lock().unwrap())) // UNWRAP: poisoned only if a thread panicked while holding it
+            .collect()
+    }
+}
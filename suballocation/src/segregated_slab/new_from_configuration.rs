@@ -1,19 +1,42 @@
 use mem_utils::RangeOf;
 use rustc_hash::FxHashMap;
 
-use super::{SegregatedSlabSuballocator, Slab};
+use crate::{Result, SuballocationError};
 
-type Error = Box<dyn std::error::Error>;
-type Result<T> = std::result::Result<T, Error>;
+use super::{SegregatedSlabStats, SegregatedSlabSuballocator, Slab};
 
 /////////////////////////////////////////////////////////////////////////////
 // Argument
 /////////////////////////////////////////////////////////////////////////////
 
+/// Slabs are laid out group after group from the start of the range.
 pub struct SegregatedSlabConfiguration<U> {
     range: RangeOf<U>,
-    slab: usize,
-    classes: Vec<usize>,
+    groups: Vec<SlabGroupConfiguration>,
+    fallback_policy: FallbackPolicy,
+}
+
+/// `slab_count` slabs of size `slab`, assigned one of `classes`.
+///
+/// A class belongs to only one group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlabGroupConfiguration {
+    pub slab: usize,
+    pub slab_count: usize,
+    pub classes: Vec<usize>,
+}
+
+/// What to do when `size` class has no space left.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FallbackPolicy {
+    /// Fail.
+    #[default]
+    Strict,
+    /// Try the next larger class.
+    NextLargerClass,
+    /// Try all larger classes, smallest first.
+    AnyLargerClass,
 }
 
 /// Constructors
@@ -28,21 +51,31 @@ impl<U> SegregatedSlabConfiguration<U> {
     ) -> Result<SegregatedSlabConfiguration<U>> {
         // check pot
         if !range.size.is_power_of_two() {
-            return Err("`range.size` should be power of two".into());
+            return Err(SuballocationError::invalid_configuration(
+                "`range.size` should be power of two",
+            ));
         }
         if !max_size.is_power_of_two() {
-            return Err("`max_size` should be power of two".into());
+            return Err(SuballocationError::invalid_configuration(
+                "`max_size` should be power of two",
+            ));
         }
         if !min_size.is_power_of_two() {
-            return Err("`min_size` should be power of two".into());
+            return Err(SuballocationError::invalid_configuration(
+                "`min_size` should be power of two",
+            ));
         }
 
         // check ordering
         if max_size > range.size {
-            return Err("`max_size` > `range.size`".into());
+            return Err(SuballocationError::invalid_configuration(
+                "`max_size` > `range.size`",
+            ));
         }
         if min_size > max_size {
-            return Err("`min_size` > `max_size`".into());
+            return Err(SuballocationError::invalid_configuration(
+                "`min_size` > `max_size`",
+            ));
         }
 
         // classes
@@ -55,12 +88,137 @@ impl<U> SegregatedSlabConfiguration<U> {
 
         Ok(SegregatedSlabConfiguration {
             range,
-            slab: max_size,
-            classes,
+            groups: vec![SlabGroupConfiguration {
+                slab: max_size,
+                slab_count: range.size / max_size,
+                classes,
+            }],
+            fallback_policy: FallbackPolicy::default(),
+        })
+    }
+
+    /// Explicit classes :
+    /// - Each class must divide `slab`.
+    /// - `slab` must divide `range.size`.
+    ///
+    /// For item sizes known in advance (no internal fragmentation for them).
+    pub fn classes(
+        range: RangeOf<U>,
+        slab: usize,
+        classes: Vec<usize>,
+    ) -> Result<SegregatedSlabConfiguration<U>> {
+        // check slab
+        if slab == 0 {
+            return Err(SuballocationError::invalid_configuration(
+                "slab size should be non zero",
+            ));
+        }
+        if !range.size.is_multiple_of(slab) {
+            return Err(SuballocationError::invalid_configuration(format!(
+                "slab size {slab} should divide `range.size` {}",
+                range.size
+            )));
+        }
+
+        Self::multi_slab(
+            range,
+            vec![SlabGroupConfiguration {
+                slab,
+                slab_count: range.size / slab,
+                classes,
+            }],
+        )
+    }
+
+    /// Several slab sizes (small classes in small slabs, big classes in big slabs) :
+    /// - Each class must divide its group slab.
+    /// - A class can't belong to several groups.
+    /// - Groups can't share a slab size (empty slabs are pooled per slab size).
+    /// - Groups must fill `range` exactly.
+    pub fn multi_slab(
+        range: RangeOf<U>,
+        mut groups: Vec<SlabGroupConfiguration>,
+    ) -> Result<SegregatedSlabConfiguration<U>> {
+        if groups.is_empty() {
+            return Err(SuballocationError::invalid_configuration(
+                "there should be at least one slab group",
+            ));
+        }
+
+        // check groups
+        let mut group_per_class = FxHashMap::default();
+        let mut group_per_slab = FxHashMap::default();
+        for (group_index, group) in groups.iter_mut().enumerate() {
+            // slab
+            if group.slab == 0 {
+                return Err(SuballocationError::invalid_configuration(format!(
+                    "group {group_index} : slab size should be non zero"
+                )));
+            }
+            if let Some(other_group_index) = group_per_slab.insert(group.slab, group_index) {
+                return Err(SuballocationError::invalid_configuration(format!(
+                    "slab size {} is shared by groups {other_group_index} and {group_index}",
+                    group.slab
+                )));
+            }
+
+            // classes
+            group.classes.sort_unstable();
+            group.classes.dedup();
+            if group.classes.is_empty() {
+                return Err(SuballocationError::invalid_configuration(format!(
+                    "group {group_index} : there should be at least one class"
+                )));
+            }
+            for class in group.classes.iter().copied() {
+                if class == 0 {
+                    return Err(SuballocationError::invalid_configuration(format!(
+                        "group {group_index} : class should be non zero"
+                    )));
+                }
+                if !group.slab.is_multiple_of(class) {
+                    return Err(SuballocationError::invalid_configuration(format!(
+                        "group {group_index} : class {class} should divide slab size {}",
+                        group.slab
+                    )));
+                }
+                if let Some(other_group_index) = group_per_class.insert(class, group_index) {
+                    return Err(SuballocationError::invalid_configuration(format!(
+                        "class {class} belongs to groups {other_group_index} and {group_index}"
+                    )));
+                }
+            }
+        }
+
+        // check groups fill range
+        let groups_size: usize = groups
+            .iter()
+            .map(|group| group.slab * group.slab_count)
+            .sum();
+        if groups_size != range.size {
+            return Err(SuballocationError::invalid_configuration(format!(
+                "slab groups size {groups_size} should be `range.size` {}",
+                range.size
+            )));
+        }
+
+        Ok(SegregatedSlabConfiguration {
+            range,
+            groups,
+            fallback_policy: FallbackPolicy::default(),
         })
     }
 }
 
+/// Options
+impl<U> SegregatedSlabConfiguration<U> {
+    /// Default is `FallbackPolicy::Strict`.
+    pub fn with_fallback_policy(mut self, fallback_policy: FallbackPolicy) -> Self {
+        self.fallback_policy = fallback_policy;
+        self
+    }
+}
+
 /////////////////////////////////////////////////////////////////////////////
 // Fonction
 /////////////////////////////////////////////////////////////////////////////
@@ -70,32 +228,56 @@ pub fn new_from_configuration<U>(
 ) -> Result<SegregatedSlabSuballocator<U>> {
     let SegregatedSlabConfiguration {
         range,
-        slab,
-        classes,
+        groups,
+        fallback_policy,
     } = configuration;
 
     // slabs
-    let slab_count = range.size / slab;
-    let mut slabs = Vec::with_capacity(slab_count);
-    for i in 0..slab_count {
-        let slab_range = range.subrange(i * slab, slab).unwrap(); // UNWRAP: divisibility checked in configuration
-        let slab = Slab::new(slab_range);
-        slabs.push(slab);
+    let mut slabs = Vec::new();
+    let mut empty_slab_indices_per_slab_size: FxHashMap<usize, Vec<usize>> = FxHashMap::default();
+    let mut offset = 0;
+    for group in &groups {
+        let empty_slab_indices = empty_slab_indices_per_slab_size
+            .entry(group.slab)
+            .or_default();
+        for _ in 0..group.slab_count {
+            let slab_range = range.subrange(offset, group.slab).unwrap(); // UNWRAP: groups fill range, checked in configuration
+            empty_slab_indices.push(slabs.len());
+            slabs.push(Slab::new(slab_range));
+            offset += group.slab;
+        }
+    }
+
+    // empty slabs are used from the start of the range
+    for empty_slab_indices in empty_slab_indices_per_slab_size.values_mut() {
+        empty_slab_indices.reverse();
     }
 
-    // indices
-    let empty_slab_indices = (0..slab_count).rev().collect();
+    // classes
+    let mut classes = Vec::new();
+    let mut slab_size_per_class = FxHashMap::default();
     let mut partial_slab_indices_per_class = FxHashMap::default();
-    for class in classes.iter().copied() {
-        partial_slab_indices_per_class.insert(class, Vec::new());
+    for group in &groups {
+        for class in group.classes.iter().copied() {
+            classes.push(class);
+            slab_size_per_class.insert(class, group.slab);
+            partial_slab_indices_per_class.insert(class, Vec::new());
+        }
     }
+    classes.sort_unstable();
 
     Ok(SegregatedSlabSuballocator {
         range,
-        slab,
+        growth_slab: groups.last().unwrap().slab, // UNWRAP: at least one group, checked in configuration
         classes,
+        slab_size_per_class,
+        fallback_policy,
+        stats: SegregatedSlabStats {
+            fallback_policy,
+            ..Default::default()
+        },
         slabs,
-        empty_slab_indices,
+        empty_slab_indices_per_slab_size,
         partial_slab_indices_per_class,
     })
 }
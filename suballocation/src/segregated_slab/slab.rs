@@ -12,8 +12,16 @@ pub struct Slab<U> {
 }
 
 struct Slot<U> {
-    range: RangeOf<U>,           // immutable
-    is_allocated: Option<usize>, // `Some(allocated_size)` & `None` <=> slot free
+    range: RangeOf<U>, // immutable
+    state: SlotState,
+}
+
+/// An allocation bigger than the class spans its slot and the next ones (grown in place).
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum SlotState {
+    Free,
+    Allocated(usize), // allocated size, might be > class
+    Spanned,          // covered by an allocation of a previous slot
 }
 
 /////////////////////////////////////////////////////////////////////////////
@@ -32,7 +40,7 @@ impl<U> Slab<U> {
             // index to slot
             let index_to_slot = |i| Slot {
                 range: RangeOf::new(self.range.offset + i * class, class),
-                is_allocated: None,
+                state: SlotState::Free,
             };
 
             // create slots from indices
@@ -49,7 +57,7 @@ impl<U> Slab<U> {
             // count free slots
             let mut free_slots = 0;
             for slot in slots {
-                if slot.is_allocated.is_none() {
+                if slot.state == SlotState::Free {
                     free_slots += 1;
                 }
             }
@@ -75,6 +83,10 @@ pub enum Occupation {
 
 /// Query
 impl<U> Slab<U> {
+    pub fn range(&self) -> RangeOf<U> {
+        self.range
+    }
+
     #[allow(clippy::manual_map)]
     pub fn class(&self) -> Option<usize> {
         if let Some(ref slots) = self.slots {
@@ -84,23 +96,54 @@ impl<U> Slab<U> {
         }
     }
 
-    pub fn is_allocated(&self, range: RangeOf<U>) -> bool {
+    /// A free slot offset is a multiple of `align`.
+    pub fn can_allocate_aligned(&self, align: usize) -> bool {
         if let Some(ref slots) = self.slots {
-            slots.iter().any(|slot| {
-                slot.range.offset == range.offset && slot.is_allocated == Some(range.size)
-            })
+            slots
+                .iter()
+                .any(|slot| slot.state == SlotState::Free && slot.range.is_aligned(align))
         } else {
-            false // not allocated if slab is free
+            false // no slots if slab is free
         }
     }
 
+    /// Once assigned `class`, a slot offset would be a multiple of `align`.
+    pub fn can_allocate_aligned_as(&self, class: usize, align: usize) -> bool {
+        let slot_count = self.range.size / class;
+        (0..slot_count).any(|i| (self.range.offset + i * class).is_multiple_of(align))
+    }
+
+    /// `range` can be resized to `size` without moving : fits its slot or the next slots are free.
+    pub fn can_reallocate_in_place(&self, range: RangeOf<U>, size: usize) -> bool {
+        let Some(slot_index) = self.allocated_slot_index_from(range) else {
+            return false;
+        };
+        let slots = self.slots.as_ref().unwrap(); // UNWRAP: allocated so not free
+
+        // slots needed
+        let class = slots[0].range.size;
+        let slot_count = size.div_ceil(class).max(1);
+        if slot_index + slot_count > slots.len() {
+            return false;
+        }
+
+        // next slots are free or already spanned by `range` (no other allocation in between)
+        slots[slot_index + 1..slot_index + slot_count]
+            .iter()
+            .all(|slot| !matches!(slot.state, SlotState::Allocated(_)))
+    }
+
+    pub fn is_allocated(&self, range: RangeOf<U>) -> bool {
+        self.allocated_slot_index_from(range).is_some()
+    }
+
     pub fn allocations(&self) -> Vec<RangeOf<U>> {
         let mut allocations = Vec::new();
 
         // empty if slab is free
         if let Some(ref slots) = self.slots {
             for slot in slots {
-                if let Some(allocated_size) = slot.is_allocated {
+                if let SlotState::Allocated(allocated_size) = slot.state {
                     allocations.push(RangeOf::new(slot.range.offset, allocated_size));
                 }
             }
@@ -108,6 +151,66 @@ impl<U> Slab<U> {
 
         allocations
     }
+
+    /// (sum of allocation sizes, sum of slots used by allocations).
+    pub fn usage(&self) -> (usize, usize) {
+        let (mut allocated_size, mut reserved_size) = (0, 0);
+
+        // nothing if slab is free
+        if let Some(ref slots) = self.slots {
+            let class = slots[0].range.size;
+            for slot in slots {
+                if let SlotState::Allocated(size) = slot.state {
+                    allocated_size += size;
+                    reserved_size += size.div_ceil(class) * class;
+                }
+            }
+        }
+
+        (allocated_size, reserved_size)
+    }
+
+    fn allocated_slot_index_from(&self, range: RangeOf<U>) -> Option<usize> {
+        // not allocated if slab is free
+        self.slots.as_ref()?.iter().position(|slot| {
+            slot.range.offset == range.offset && slot.state == SlotState::Allocated(range.size)
+        })
+    }
+}
+
+/// Restore
+impl<U> Slab<U> {
+    /// Allocate exactly `range` (spanning next slots if bigger than class), false if its slots aren't free.
+    pub fn allocate_at(&mut self, range: RangeOf<U>) -> bool {
+        let Some(ref mut slots) = self.slots else {
+            return false; // no slots if slab is free
+        };
+        let Some(slot_index) = slots
+            .iter()
+            .position(|slot| slot.range.offset == range.offset)
+        else {
+            return false;
+        };
+
+        // slots needed
+        let class = slots[0].range.size;
+        let slot_count = range.size.div_ceil(class);
+        if range.size == 0
+            || slot_index + slot_count > slots.len()
+            || slots[slot_index..slot_index + slot_count]
+                .iter()
+                .any(|slot| slot.state != SlotState::Free)
+        {
+            return false;
+        }
+
+        // allocate (with spanned slots)
+        slots[slot_index].state = SlotState::Allocated(range.size);
+        for slot in &mut slots[slot_index + 1..slot_index + slot_count] {
+            slot.state = SlotState::Spanned;
+        }
+        true
+    }
 }
 
 /// Display
@@ -118,7 +221,7 @@ impl<U> Display for Slab<U> {
             let class = self.class().unwrap();
             let free_slot_count = slots
                 .iter()
-                .filter(|slot| slot.is_allocated.is_none())
+                .filter(|slot| slot.state == SlotState::Free)
                 .count();
             let slot_count = self.range.size / class;
 
@@ -142,21 +245,21 @@ impl<U> Display for Slab<U> {
 impl<U> Slab<U> {
     /// Unsafe if :
     /// - `self` free.
-    /// - `self` full.
+    /// - `self` has no free slot aligned to `align` (full for example).
     /// - `size` bigger than self class.
-    pub unsafe fn allocate(&mut self, size: usize) -> RangeOf<U> {
+    pub unsafe fn allocate(&mut self, size: usize, align: usize) -> RangeOf<U> {
         // find
         let slot = unsafe {
             self.slots
                 .as_mut()
                 .unwrap_unchecked()
                 .iter_mut()
-                .find(|slot| slot.is_allocated.is_none())
+                .find(|slot| slot.state == SlotState::Free && slot.range.is_aligned(align))
                 .unwrap_unchecked()
         };
 
         // allocate
-        slot.is_allocated = Some(size);
+        slot.state = SlotState::Allocated(size);
 
         RangeOf::new(slot.range.offset, size)
     }
@@ -166,37 +269,49 @@ impl<U> Slab<U> {
     /// - `range` not allocated.
     pub unsafe fn deallocate(&mut self, range: RangeOf<U>) {
         // find
-        let slot = unsafe {
-            self.slots
-                .as_mut()
-                .unwrap_unchecked()
-                .iter_mut()
-                .find(|slot| slot.range.offset == range.offset)
+        let slots = unsafe { self.slots.as_mut().unwrap_unchecked() };
+        let slot_index = unsafe {
+            slots
+                .iter()
+                .position(|slot| slot.range.offset == range.offset)
                 .unwrap_unchecked()
         };
 
-        // deallocate
-        slot.is_allocated = None;
+        // deallocate (with spanned slots)
+        slots[slot_index].state = SlotState::Free;
+        for slot in slots[slot_index + 1..]
+            .iter_mut()
+            .take_while(|slot| slot.state == SlotState::Spanned)
+        {
+            slot.state = SlotState::Free;
+        }
     }
 
     /// Unsafe if :
     /// - `self` free.
     /// - `range` not allocated.
-    /// - `size` bigger than self class.
+    /// - `can_reallocate_in_place` is false.
     pub unsafe fn reallocate_in_place(&mut self, range: RangeOf<U>, size: usize) -> RangeOf<U> {
+        // free old allocation (with spanned slots)
+        unsafe { self.deallocate(range) };
+
         // find
-        let slot = unsafe {
-            self.slots
-                .as_mut()
-                .unwrap_unchecked()
-                .iter_mut()
-                .find(|slot| slot.range.offset == range.offset)
+        let slots = unsafe { self.slots.as_mut().unwrap_unchecked() };
+        let class = slots[0].range.size;
+        let slot_index = unsafe {
+            slots
+                .iter()
+                .position(|slot| slot.range.offset == range.offset)
                 .unwrap_unchecked()
         };
 
-        // reallocate
-        slot.is_allocated = Some(size);
+        // reallocate (with spanned slots)
+        let slot_count = size.div_ceil(class);
+        slots[slot_index].state = SlotState::Allocated(size);
+        for slot in &mut slots[slot_index + 1..slot_index + slot_count] {
+            slot.state = SlotState::Spanned;
+        }
 
-        RangeOf::new(slot.range.offset, size)
+        RangeOf::new(range.offset, size)
     }
 }
@@ -0,0 +1,345 @@
+use std::fmt::Debug;
+
+use mem_utils::RangeOf;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{Result, SuballocationError};
+
+use super::{
+    FallbackPolicy, SegregatedSlabStats, SegregatedSlabSuballocator,
+    slab::{Occupation, Slab},
+};
+
+/////////////////////////////////////////////////////////////////////////////
+// Structures
+/////////////////////////////////////////////////////////////////////////////
+
+/// Plain description of a `SegregatedSlabSuballocator`, to save & restore it.
+///
+/// Stack orders are kept, so that a restored allocator allocates the same ranges as the original one.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
+pub struct SegregatedSlabSnapshot<U> {
+    pub range: RangeOf<U>,
+
+    // sizes
+    pub growth_slab: usize,
+    pub classes: Vec<(usize, usize)>, // (class, slab size), sorted by class
+
+    // policy
+    pub fallback_policy: FallbackPolicy,
+    pub stats: SegregatedSlabStats, // counters only (usage is computed from slabs)
+
+    // slabs
+    pub slabs: Vec<SlabSnapshot<U>>, // sorted by offset, covering `range`
+
+    // indices (bottom to top of stacks)
+    pub empty_slab_indices_per_slab_size: Vec<(usize, Vec<usize>)>, // sorted by slab size
+    pub partial_slab_indices_per_class: Vec<(usize, Vec<usize>)>,   // sorted by class
+}
+
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
+pub struct SlabSnapshot<U> {
+    pub range: RangeOf<U>,
+    pub class: Option<usize>, // last assigned class (kept by empty slabs)
+    pub allocations: Vec<RangeOf<U>>,
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Fonctions
+/////////////////////////////////////////////////////////////////////////////
+
+pub fn snapshot_from<U>(suballocator: &SegregatedSlabSuballocator<U>) -> SegregatedSlabSnapshot<U> {
+    // sizes
+    let classes = suballocator
+        .classes
+        .iter()
+        .map(|class| (*class, suballocator.slab_size_per_class[class]))
+        .collect();
+
+    // slabs
+    let slabs = suballocator
+        .slabs
+        .iter()
+        .map(|slab| SlabSnapshot {
+            range: slab.range(),
+            class: slab.class(),
+            allocations: slab.allocations(),
+        })
+        .collect();
+
+    // indices
+    let sorted = |indices_per_key: &FxHashMap<usize, Vec<usize>>| {
+        let mut sorted_indices_per_key: Vec<(usize, Vec<usize>)> = indices_per_key
+            .iter()
+            .map(|(key, indices)| (*key, indices.clone()))
+            .collect();
+        sorted_indices_per_key.sort_unstable_by_key(|(key, _)| *key);
+        sorted_indices_per_key
+    };
+
+    SegregatedSlabSnapshot {
+        range: suballocator.range,
+        growth_slab: suballocator.growth_slab,
+        classes,
+        fallback_policy: suballocator.fallback_policy,
+        stats: SegregatedSlabStats {
+            allocated_size: 0,
+            reserved_size: 0,
+            ..suballocator.stats
+        },
+        slabs,
+        empty_slab_indices_per_slab_size: sorted(&suballocator.empty_slab_indices_per_slab_size),
+        partial_slab_indices_per_class: sorted(&suballocator.partial_slab_indices_per_class),
+    }
+}
+
+/// Checks `snapshot` is consistent (a state reachable by a suballocator).
+pub fn restore<U>(snapshot: SegregatedSlabSnapshot<U>) -> Result<SegregatedSlabSuballocator<U>> {
+    let SegregatedSlabSnapshot {
+        range,
+        growth_slab,
+        classes: classes_and_slab_sizes,
+        fallback_policy,
+        stats,
+        slabs: slab_snapshots,
+        empty_slab_indices_per_slab_size,
+        partial_slab_indices_per_class,
+    } = snapshot;
+
+    // check sizes
+    if classes_and_slab_sizes.is_empty() {
+        return Err(SuballocationError::invalid_configuration(
+            "at least one class is needed",
+        ));
+    }
+    if !classes_and_slab_sizes.is_sorted_by(|(a, _), (b, _)| a < b) {
+        return Err(SuballocationError::invalid_configuration(
+            "classes should be sorted & unique",
+        ));
+    }
+    for (class, slab_size) in &classes_and_slab_sizes {
+        if *class == 0 || !slab_size.is_multiple_of(*class) {
+            return Err(SuballocationError::invalid_configuration(format!(
+                "class {class} should be non zero & divide its slab size {slab_size}"
+            )));
+        }
+    }
+    let classes: Vec<usize> = classes_and_slab_sizes
+        .iter()
+        .map(|(class, _)| *class)
+        .collect();
+    let slab_size_per_class: FxHashMap<usize, usize> =
+        classes_and_slab_sizes.iter().copied().collect();
+    let slab_sizes: FxHashSet<usize> = slab_size_per_class.values().copied().collect();
+    if !slab_sizes.contains(&growth_slab) {
+        return Err(SuballocationError::invalid_configuration(format!(
+            "growth slab {growth_slab} should be the slab size of a class"
+        )));
+    }
+
+    // restore slabs
+    let mut slabs = Vec::with_capacity(slab_snapshots.len());
+    let mut offset = range.offset;
+    for slab_snapshot in slab_snapshots {
+        // check layout
+        if slab_snapshot.range.offset != offset || !slab_sizes.contains(&slab_snapshot.range.size) {
+            return Err(SuballocationError::invalid_configuration(format!(
+                "slab {} should follow the previous one & have the slab size of a class",
+                slab_snapshot.range
+            )));
+        }
+        offset = slab_snapshot.range.end();
+
+        // check class
+        if let Some(class) = slab_snapshot.class
+            && slab_size_per_class.get(&class) != Some(&slab_snapshot.range.size)
+        {
+            return Err(SuballocationError::invalid_configuration(format!(
+                "slab {} can't be assigned class {class}",
+                slab_snapshot.range
+            )));
+        }
+
+        // allocate
+        let mut slab = Slab::new(slab_snapshot.range);
+        slab.reset_slots(slab_snapshot.class);
+        for allocation in slab_snapshot.allocations {
+            if !slab.allocate_at(allocation) {
+                return Err(SuballocationError::invalid_configuration(format!(
+                    "allocation {allocation} doesn't fit free slots of slab {}",
+                    slab_snapshot.range
+                )));
+            }
+        }
+        slabs.push(slab);
+    }
+    if offset != range.end() {
+        return Err(SuballocationError::invalid_configuration(
+            "slabs should cover the range",
+        ));
+    }
+
+    // check indices : keys & an index per empty/partial slab, in the list of its slab size/class
+    let empty_slab_indices_per_slab_size: FxHashMap<usize, Vec<usize>> =
+        empty_slab_indices_per_slab_size.into_iter().collect();
+    let partial_slab_indices_per_class: FxHashMap<usize, Vec<usize>> =
+        partial_slab_indices_per_class.into_iter().collect();
+    if empty_slab_indices_per_slab_size
+        .keys()
+        .copied()
+        .collect::<FxHashSet<_>>()
+        != slab_sizes
+        || partial_slab_indices_per_class.len() != classes.len()
+        || !classes
+            .iter()
+            .all(|class| partial_slab_indices_per_class.contains_key(class))
+    {
+        return Err(SuballocationError::invalid_configuration(
+            "indices should have one list per slab size & per class",
+        ));
+    }
+    let mut listed = vec![false; slabs.len()];
+    let mut check_listed = |slab_index: usize, occupation: Occupation, key: usize| {
+        let slab: Option<&Slab<U>> = slabs.get(slab_index);
+        let is_valid = slab.is_some_and(|slab| {
+            slab.occupation() == occupation
+                && match occupation {
+                    Occupation::Empty => slab.range().size == key,
+                    _ => slab.class() == Some(key),
+                }
+        }) && !std::mem::replace(&mut listed[slab_index], true);
+        if is_valid {
+            Ok(())
+        } else {
+            Err(SuballocationError::invalid_configuration(format!(
+                "slab {slab_index} shouldn't be listed as {occupation:?} for {key}"
+            )))
+        }
+    };
+    for (slab_size, slab_indices) in &empty_slab_indices_per_slab_size {
+        for slab_index in slab_indices {
+            check_listed(*slab_index, Occupation::Empty, *slab_size)?;
+        }
+    }
+    for (class, slab_indices) in &partial_slab_indices_per_class {
+        for slab_index in slab_indices {
+            check_listed(*slab_index, Occupation::Partial, *class)?;
+        }
+    }
+    if let Some(slab_index) = (0..slabs.len()).find(|slab_index| {
+        !listed[*slab_index] && slabs[*slab_index].occupation() != Occupation::Full
+    }) {
+        return Err(SuballocationError::invalid_configuration(format!(
+            "slab {slab_index} should be listed"
+        )));
+    }
+
+    Ok(SegregatedSlabSuballocator {
+        range,
+        growth_slab,
+        classes,
+        slab_size_per_class,
+        fallback_policy,
+        stats: SegregatedSlabStats {
+            fallback_policy,
+            allocated_size: 0,
+            reserved_size: 0,
+            ..stats
+        },
+        slabs,
+        empty_slab_indices_per_slab_size,
+        partial_slab_indices_per_class,
+    })
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Trivial implementations : Debug, Clone, PartialEq, Eq
+/////////////////////////////////////////////////////////////////////////////
+// Can't be derived because it would require U to implement them as well (even if phantom).
+
+/// Debug
+impl<U> Debug for SegregatedSlabSnapshot<U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SegregatedSlabSnapshot")
+            .field("range", &self.range)
+            .field("growth_slab", &self.growth_slab)
+            .field("classes", &self.classes)
+            .field("fallback_policy", &self.fallback_policy)
+            .field("stats", &self.stats)
+            .field("slabs", &self.slabs)
+            .field(
+                "empty_slab_indices_per_slab_size",
+                &self.empty_slab_indices_per_slab_size,
+            )
+            .field(
+                "partial_slab_indices_per_class",
+                &self.partial_slab_indices_per_class,
+            )
+            .finish()
+    }
+}
+impl<U> Debug for SlabSnapshot<U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SlabSnapshot")
+            .field("range", &self.range)
+            .field("class", &self.class)
+            .field("allocations", &self.allocations)
+            .finish()
+    }
+}
+
+/// Clone
+impl<U> Clone for SegregatedSlabSnapshot<U> {
+    fn clone(&self) -> Self {
+        SegregatedSlabSnapshot {
+            range: self.range,
+            growth_slab: self.growth_slab,
+            classes: self.classes.clone(),
+            fallback_policy: self.fallback_policy,
+            stats: self.stats,
+            slabs: self.slabs.clone(),
+            empty_slab_indices_per_slab_size: self.empty_slab_indices_per_slab_size.clone(),
+            partial_slab_indices_per_class: self.partial_slab_indices_per_class.clone(),
+        }
+    }
+}
+impl<U> Clone for SlabSnapshot<U> {
+    fn clone(&self) -> Self {
+        SlabSnapshot {
+            range: self.range,
+            class: self.class,
+            allocations: self.allocations.clone(),
+        }
+    }
+}
+
+/// PartialEq & Eq
+impl<U> PartialEq for SegregatedSlabSnapshot<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.range == other.range
+            && self.growth_slab == other.growth_slab
+            && self.classes == other.classes
+            && self.fallback_policy == other.fallback_policy
+            && self.stats == other.stats
+            && self.slabs == other.slabs
+            && self.empty_slab_indices_per_slab_size == other.empty_slab_indices_per_slab_size
+            && self.partial_slab_indices_per_class == other.partial_slab_indices_per_class
+    }
+}
+impl<U> Eq for SegregatedSlabSnapshot<U> {}
+impl<U> PartialEq for SlabSnapshot<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.range == other.range
+            && self.class == other.class
+            && self.allocations == other.allocations
+    }
+}
+impl<U> Eq for SlabSnapshot<U> {}
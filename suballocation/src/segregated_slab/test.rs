@@ -1,13 +1,20 @@
-//! Run "cargo test -- --nocapture" to print memory.
-//! It's hard to debug because stacks orders are hard to follow (which slab will be the next free slab for example).
-//! So println and check manually.
+//! Stacks orders are hard to follow (which slab will be the next free slab for example),
+//! so expected memory states are asserted (to update if free slabs are reused in another order).
+//!
+//! `model_based` checks random sequences against a reference model, and prints a shrunk failing sequence.
+//! `documentation_memory_maps` compares memory maps (`to_ascii`) with module documentation diagrams.
 
 // Import
 use super::*;
+use crate::{MemoryMap, harness};
 
 // External
 use mem_utils::RangeOf;
-use std::mem::MaybeUninit;
+use std::{
+    mem::MaybeUninit,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
 
 /// Not related to state of the allocator
 #[test]
@@ -18,10 +25,13 @@ fn allocation_absolute() {
         SegregatedSlabSuballocator::<i32>::new_from_configuration(allocator_configuration).unwrap();
 
     let size_null = allocator.allocate(0);
-    assert!(size_null.is_err());
+    assert_eq!(size_null, Err(SuballocationError::SizeZero));
 
     let size_too_big = allocator.allocate(5);
-    assert!(size_too_big.is_err());
+    assert_eq!(
+        size_too_big,
+        Err(SuballocationError::SizeTooLarge { max: 4 })
+    );
 }
 
 /// Not related to state of the allocator
@@ -33,7 +43,10 @@ fn deallocation_absolute() {
         SegregatedSlabSuballocator::<i32>::new_from_configuration(allocator_configuration).unwrap();
 
     let offset_out_of_bounds = allocator.deallocate(RangeOf::new(16, 3));
-    assert!(offset_out_of_bounds.is_err());
+    assert_eq!(offset_out_of_bounds, Err(SuballocationError::OutOfBounds));
+
+    let not_allocated = allocator.deallocate(RangeOf::new(0, 3));
+    assert_eq!(not_allocated, Err(SuballocationError::NotAllocated));
 }
 
 /// Follow manually with a pen.
@@ -119,7 +132,7 @@ fn evolve_as_expected() {
     );
 
     // Test no more space
-    assert!(allocator.allocate(4).is_err());
+    assert_eq!(allocator.allocate(4), Err(SuballocationError::OutOfMemory));
 
     // PHASE 3 : Reallocations
     // shrink
@@ -141,6 +154,613 @@ fn evolve_as_expected() {
     );
 }
 
+#[test]
+fn grow_and_shrink() {
+    let allocator_configuration =
+        SegregatedSlabConfiguration::pot(RangeOf::new(0, 8), 4, 2).unwrap();
+    let mut allocator =
+        SegregatedSlabSuballocator::<i32>::new_from_configuration(allocator_configuration).unwrap();
+
+    // fill
+    let one = allocator.allocate(4).unwrap();
+    let two = allocator.allocate(3).unwrap();
+    assert!(!allocator.can_allocate(4));
+
+    // invalid growths
+    for new_range in [RangeOf::new(1, 12), RangeOf::new(0, 4), RangeOf::new(0, 10)] {
+        assert!(matches!(
+            allocator.grow(new_range),
+            Err(SuballocationError::InvalidConfiguration { .. })
+        ));
+    }
+
+    // grow : old allocations stay valid
+    allocator.grow(RangeOf::new(0, 16)).unwrap();
+    assert_eq!(allocator.range(), RangeOf::new(0, 16));
+    assert!(allocator.is_allocated(one) && allocator.is_allocated(two));
+    let three = allocator.allocate(4).unwrap();
+    let four = allocator.allocate(4).unwrap();
+    assert!(three.is_subrange_of(&RangeOf::new(8, 8)));
+    assert!(four.is_subrange_of(&RangeOf::new(8, 8)));
+    assert!(!allocator.can_allocate(1));
+
+    // shrink : only trailing empty slabs are released
+    allocator.deallocate(three).unwrap();
+    assert_eq!(allocator.shrink_to_fit(), RangeOf::new(0, 16));
+    allocator.deallocate(four).unwrap();
+    assert_eq!(allocator.shrink_to_fit(), RangeOf::new(0, 8));
+    assert!(allocator.is_allocated(one) && allocator.is_allocated(two));
+    assert!(!allocator.can_allocate(1));
+    assert_eq!(
+        allocator.deallocate(four),
+        Err(SuballocationError::OutOfBounds)
+    );
+}
+
+#[test]
+fn aligned() {
+    // slabs of 8 at 2, 10, 18, 26 (offset 2 => misaligned slabs)
+    let allocator_configuration =
+        SegregatedSlabConfiguration::pot(RangeOf::new(2, 32), 8, 2).unwrap();
+    let mut allocator =
+        SegregatedSlabSuballocator::<i32>::new_from_configuration(allocator_configuration).unwrap();
+
+    assert!(matches!(
+        allocator.allocate_aligned(2, 0),
+        Err(SuballocationError::InvalidConfiguration { .. })
+    ));
+
+    // class 4 slots at 2, 6 in first slab => 6 is the only one aligned to 3
+    let one = allocator.allocate_aligned(3, 3).unwrap();
+    assert_eq!(one, RangeOf::new(6, 3));
+    let two = allocator.allocate_aligned(2, 4).unwrap();
+    assert!(two.is_aligned(4));
+
+    // class 4 slots : 2 modulo 4 => none aligned to 4
+    assert_eq!(
+        allocator.allocate_aligned(4, 4),
+        Err(SuballocationError::OutOfMemory)
+    );
+
+    // reallocate aligned : in place when already aligned, moved otherwise
+    assert_eq!(
+        allocator.reallocate_aligned(one, 4, 3).unwrap(),
+        RangeOf::new(6, 4)
+    );
+    let one = RangeOf::new(6, 4);
+    let moved = allocator.reallocate_aligned(one, 4, 5).unwrap();
+    assert!(moved.is_aligned(5));
+    assert!(!allocator.is_allocated(one));
+    assert!(allocator.is_allocated(moved) && allocator.is_allocated(two));
+}
+
+#[test]
+fn explicit_classes() {
+    // vertex counts cluster around 8, 24, 36
+    let allocator_configuration =
+        SegregatedSlabConfiguration::classes(RangeOf::new(0, 144), 72, vec![36, 8, 24]).unwrap();
+    let mut allocator =
+        SegregatedSlabSuballocator::<i32>::new_from_configuration(allocator_configuration).unwrap();
+
+    assert_eq!(allocator.allocate(8).unwrap(), RangeOf::new(0, 8));
+    assert_eq!(allocator.allocate(20).unwrap(), RangeOf::new(72, 20));
+    assert_eq!(allocator.allocate(24).unwrap(), RangeOf::new(96, 24));
+    assert_eq!(
+        allocator.allocate(37),
+        Err(SuballocationError::SizeTooLarge { max: 36 })
+    );
+
+    // invalid configurations
+    let invalid_configurations = [
+        SegregatedSlabConfiguration::<i32>::classes(RangeOf::new(0, 144), 0, vec![8]),
+        SegregatedSlabConfiguration::<i32>::classes(RangeOf::new(0, 144), 64, vec![8]),
+        SegregatedSlabConfiguration::<i32>::classes(RangeOf::new(0, 144), 72, vec![]),
+        SegregatedSlabConfiguration::<i32>::classes(RangeOf::new(0, 144), 72, vec![0, 8]),
+        SegregatedSlabConfiguration::<i32>::classes(RangeOf::new(0, 144), 72, vec![8, 16]),
+    ];
+    for configuration in invalid_configurations {
+        assert!(matches!(
+            configuration,
+            Err(SuballocationError::InvalidConfiguration { .. })
+        ));
+    }
+}
+
+#[test]
+fn multi_slab() {
+    // 2 slabs of 4 for classes {1, 2}, then 2 slabs of 16 for classes {8, 16}
+    let groups = vec![
+        SlabGroupConfiguration {
+            slab: 4,
+            slab_count: 2,
+            classes: vec![1, 2],
+        },
+        SlabGroupConfiguration {
+            slab: 16,
+            slab_count: 2,
+            classes: vec![8, 16],
+        },
+    ];
+    let allocator_configuration =
+        SegregatedSlabConfiguration::multi_slab(RangeOf::new(0, 40), groups.clone()).unwrap();
+    let mut allocator =
+        SegregatedSlabSuballocator::<i32>::new_from_configuration(allocator_configuration).unwrap();
+
+    // small classes in small slabs, big in big
+    let one = allocator.allocate(2).unwrap();
+    let two = allocator.allocate(1).unwrap();
+    let three = allocator.allocate(5).unwrap();
+    let four = allocator.allocate(16).unwrap();
+    assert_eq!(one, RangeOf::new(0, 2));
+    assert_eq!(two, RangeOf::new(4, 1));
+    assert_eq!(three, RangeOf::new(8, 5));
+    assert_eq!(four, RangeOf::new(24, 16));
+
+    // small slabs are not used by big classes & vice versa
+    assert_eq!(allocator.allocate(9), Err(SuballocationError::OutOfMemory));
+    allocator.allocate(2).unwrap();
+    assert_eq!(allocator.allocate(2), Err(SuballocationError::OutOfMemory));
+    allocator.deallocate(three).unwrap();
+    allocator.deallocate(four).unwrap();
+    assert!(allocator.is_allocated(one) && allocator.is_allocated(two));
+
+    // grow with last group slabs, shrink keep used slabs
+    assert!(allocator.grow(RangeOf::new(0, 44)).is_err());
+    allocator.grow(RangeOf::new(0, 56)).unwrap();
+    assert_eq!(allocator.shrink_to_fit(), RangeOf::new(0, 8));
+
+    // invalid configurations
+    let mut overlapping_classes = groups.clone();
+    overlapping_classes[1].classes.push(2);
+    let mut class_not_dividing_slab = groups.clone();
+    class_not_dividing_slab[1].classes.push(3);
+    let mut shared_slab_size = groups.clone();
+    shared_slab_size.push(SlabGroupConfiguration {
+        slab: 4,
+        slab_count: 2,
+        classes: vec![4],
+    });
+    let invalid_configurations = [
+        SegregatedSlabConfiguration::<i32>::multi_slab(RangeOf::new(0, 40), vec![]),
+        SegregatedSlabConfiguration::<i32>::multi_slab(RangeOf::new(0, 48), groups),
+        SegregatedSlabConfiguration::<i32>::multi_slab(RangeOf::new(0, 40), overlapping_classes),
+        SegregatedSlabConfiguration::<i32>::multi_slab(
+            RangeOf::new(0, 40),
+            class_not_dividing_slab,
+        ),
+        SegregatedSlabConfiguration::<i32>::multi_slab(RangeOf::new(0, 48), shared_slab_size),
+    ];
+    for configuration in invalid_configurations {
+        assert!(matches!(
+            configuration,
+            Err(SuballocationError::InvalidConfiguration { .. })
+        ));
+    }
+}
+
+#[test]
+fn fallback_policy() {
+    let new_allocator = |fallback_policy| {
+        let allocator_configuration =
+            SegregatedSlabConfiguration::classes(RangeOf::new(0, 24), 8, vec![1, 2, 4])
+                .unwrap()
+                .with_fallback_policy(fallback_policy);
+        let mut allocator =
+            SegregatedSlabSuballocator::<i32>::new_from_configuration(allocator_configuration)
+                .unwrap();
+
+        // `|1| aaaaaaaa |2| bbbbbbbb |4| cccc####`
+        for (size, count) in [(1, 8), (2, 4), (4, 1)] {
+            for _ in 0..count {
+                allocator.allocate(size).unwrap();
+            }
+        }
+        allocator
+    };
+
+    // strict : only `size` class
+    let mut strict = new_allocator(FallbackPolicy::Strict);
+    assert!(!strict.can_allocate(1));
+    assert_eq!(strict.allocate(1), Err(SuballocationError::OutOfMemory));
+    assert_eq!(strict.allocate(2), Err(SuballocationError::OutOfMemory));
+    let stats = strict.stats();
+    assert_eq!(stats.fallback_policy, FallbackPolicy::Strict);
+    assert_eq!(
+        (
+            stats.allocations,
+            stats.fallback_allocations,
+            stats.failed_allocations
+        ),
+        (13, 0, 2)
+    );
+
+    // next larger class : class 2 is full, class 4 isn't tried for size 1
+    let mut next = new_allocator(FallbackPolicy::NextLargerClass);
+    assert_eq!(next.allocate(1), Err(SuballocationError::OutOfMemory));
+    assert_eq!(next.allocate(2).unwrap(), RangeOf::new(20, 2));
+    assert_eq!(next.stats().fallback_allocations, 1);
+
+    // any larger class
+    let mut any = new_allocator(FallbackPolicy::AnyLargerClass);
+    assert!(any.can_allocate(1));
+    assert_eq!(any.allocate(1).unwrap(), RangeOf::new(20, 1));
+    let stats = any.stats();
+    assert_eq!(stats.fallback_allocations, 1);
+    assert_eq!((stats.allocated_size, stats.reserved_size), (21, 24));
+}
+
+#[test]
+fn reallocation_in_place() {
+    let allocator_configuration =
+        SegregatedSlabConfiguration::classes(RangeOf::new(0, 16), 8, vec![2, 8]).unwrap();
+    let mut allocator =
+        SegregatedSlabSuballocator::<i32>::new_from_configuration(allocator_configuration).unwrap();
+
+    // `|2| aabb####`
+    let a = allocator.allocate(2).unwrap();
+    let b = allocator.allocate(1).unwrap();
+    assert_eq!(b, RangeOf::new(2, 1));
+
+    // grow `b` into next slots (bigger than its class) : `|2| aabbbbb~`
+    let b = allocator.reallocate(b, 5).unwrap();
+    assert_eq!(b, RangeOf::new(2, 5));
+    assert!(allocator.can_reallocate(b, 6));
+
+    // `a` can't grow into `b` : moved to class 8
+    assert!(allocator.can_reallocate(a, 3));
+    let a = allocator.reallocate(a, 3).unwrap();
+    assert_eq!(a, RangeOf::new(8, 3));
+
+    // shrink `b` : slots are freed, slab is partial again
+    let b = allocator.reallocate(b, 2).unwrap();
+    assert_eq!(b, RangeOf::new(2, 2));
+    assert_eq!(allocator.allocate(2).unwrap(), RangeOf::new(0, 2));
+    assert_eq!(allocator.allocate(2).unwrap(), RangeOf::new(4, 2));
+
+    // too large for slab & no free class 8 slab
+    assert!(!allocator.can_reallocate(b, 7));
+    assert_eq!(
+        allocator.reallocate(b, 7),
+        Err(SuballocationError::OutOfMemory)
+    );
+
+    let stats = allocator.stats();
+    assert_eq!(
+        (
+            stats.in_place_reallocations,
+            stats.moved_reallocations,
+            stats.failed_reallocations
+        ),
+        (2, 1, 1)
+    );
+    assert_eq!(stats.allocated_size, 9);
+    assert_eq!(stats.reserved_size, 14);
+}
+
+/// A shrink whose class is full stays in place, it never moves to a class at least as large.
+#[test]
+fn shrink_never_moves_to_larger_class() {
+    // slabs of 16 at 0 (class 4), 16 (class 2), 32 (class 8)
+    let allocator_configuration =
+        SegregatedSlabConfiguration::classes(RangeOf::new(0, 48), 16, vec![2, 4, 8])
+            .unwrap()
+            .with_fallback_policy(FallbackPolicy::AnyLargerClass);
+    let mut allocator =
+        SegregatedSlabSuballocator::<i32>::new_from_configuration(allocator_configuration).unwrap();
+
+    let a = allocator.allocate(4).unwrap();
+    for _ in 0..8 {
+        allocator.allocate(2).unwrap();
+    }
+    allocator.allocate(8).unwrap();
+    assert_eq!(a, RangeOf::new(0, 4));
+
+    // class 2 is full : shrinking `a` keeps it in place (instead of moving to class 4 or 8)
+    let reserved_size = allocator.stats().reserved_size;
+    let a = allocator.reallocate(a, 2).unwrap();
+    assert_eq!(a, RangeOf::new(0, 2)); // still in the class 4 slab (0..16)
+    let stats = allocator.stats();
+    assert_eq!(stats.reserved_size, reserved_size);
+    assert_eq!(
+        (stats.moved_reallocations, stats.fallback_allocations),
+        (0, 0)
+    );
+}
+
+/// Threads allocate & deallocate concurrently, a unit is never owned by two allocations.
+#[test]
+fn concurrent_stress() {
+    const THREAD_COUNT: usize = 8;
+    const ITERATION_COUNT: usize = 5_000;
+    let allocator_configuration =
+        SegregatedSlabConfiguration::pot(RangeOf::new(0, 256), 16, 1).unwrap();
+    let allocator = ConcurrentSegregatedSlabSuballocator::<u32>::new_from_configuration(
+        allocator_configuration,
+    )
+    .unwrap();
+    let owned: Vec<AtomicBool> = (0..256).map(|_| AtomicBool::new(false)).collect();
+
+    thread::scope(|scope| {
+        for thread_index in 0..THREAD_COUNT {
+            let (allocator, owned) = (&allocator, &owned);
+            scope.spawn(move || {
+                let mut ranges = Vec::new();
+                for iteration in 0..ITERATION_COUNT {
+                    // keep up to a few ranges per thread, sizes varying with thread
+                    if ranges.len() < 2 + (thread_index + iteration) % 6 {
+                        let size = 1 + (thread_index * 7 + iteration * 3) % 16;
+                        let Ok(range) = allocator.allocate(size) else {
+                            continue;
+                        };
+                        for unit in range.to_std_range() {
+                            assert!(!owned[unit].swap(true, Ordering::AcqRel));
+                        }
+                        ranges.push(range);
+                    } else {
+                        let range = ranges.swap_remove(iteration % ranges.len());
+                        for unit in range.to_std_range() {
+                            assert!(owned[unit].swap(false, Ordering::AcqRel));
+                        }
+                        allocator.deallocate(range).unwrap();
+                    }
+                }
+                for range in ranges {
+                    for unit in range.to_std_range() {
+                        assert!(owned[unit].swap(false, Ordering::AcqRel));
+                    }
+                    allocator.deallocate(range).unwrap();
+                }
+            });
+        }
+    });
+
+    // every slab is empty again
+    assert!(allocator.allocations().is_empty());
+    for _ in 0..16 {
+        allocator.allocate(16).unwrap();
+    }
+    assert_eq!(allocator.allocate(1), Err(SuballocationError::OutOfMemory));
+    assert_eq!(
+        allocator.deallocate(RangeOf::new(0, 3)),
+        Err(SuballocationError::NotAllocated)
+    );
+}
+
+/// Random sequences checked against a reference model (see `harness`).
+#[test]
+fn model_based() {
+    // pot, explicit classes, multi slab (with fallback policies)
+    let configuration_from = |index| match index {
+        0 => SegregatedSlabConfiguration::pot(RangeOf::new(0, 64), 16, 1).unwrap(),
+        1 => SegregatedSlabConfiguration::classes(RangeOf::new(0, 60), 12, vec![1, 3, 4, 12])
+            .unwrap()
+            .with_fallback_policy(FallbackPolicy::NextLargerClass),
+        _ => SegregatedSlabConfiguration::multi_slab(
+            RangeOf::new(0, 40),
+            vec![
+                SlabGroupConfiguration {
+                    slab: 4,
+                    slab_count: 2,
+                    classes: vec![1, 2],
+                },
+                SlabGroupConfiguration {
+                    slab: 16,
+                    slab_count: 2,
+                    classes: vec![8, 16],
+                },
+            ],
+        )
+        .unwrap()
+        .with_fallback_policy(FallbackPolicy::AnyLargerClass),
+    };
+
+    for index in 0..3 {
+        let new_allocator = || {
+            SegregatedSlabSuballocator::<u32>::new_from_configuration(configuration_from(index))
+                .unwrap()
+        };
+        if let Err(failure) = harness::check_array(new_allocator, 0..64, 200, 20) {
+            panic!("configuration {index}\n{failure}");
+        }
+    }
+}
+
+/// Restored allocator is identical : same snapshot, same results for the next operations.
+#[test]
+fn snapshot_and_restore() {
+    // apply `operations`, live allocations in `allocations`
+    fn apply(
+        allocator: &mut SegregatedSlabSuballocator<u32>,
+        allocations: &mut Vec<RangeOf<u32>>,
+        operations: &[harness::ArrayOperation],
+    ) -> Vec<Result<RangeOf<u32>>> {
+        let mut results = Vec::new();
+        for operation in operations {
+            let result = match *operation {
+                harness::ArrayOperation::Allocate { size, align } => {
+                    allocator.allocate_aligned(size, align)
+                }
+                _ if allocations.is_empty() => continue,
+                harness::ArrayOperation::Reallocate {
+                    allocation,
+                    size,
+                    align,
+                } => {
+                    let range = allocations.swap_remove(allocation % allocations.len());
+                    allocator.reallocate_aligned(range, size, align)
+                }
+                harness::ArrayOperation::Deallocate { allocation } => {
+                    let range = allocations.swap_remove(allocation % allocations.len());
+                    allocator.deallocate(range).map(|_| range)
+                }
+            };
+            if let (
+                Ok(range),
+                harness::ArrayOperation::Allocate { .. }
+                | harness::ArrayOperation::Reallocate { .. },
+            ) = (&result, operation)
+            {
+                allocations.push(*range);
+            }
+            results.push(result);
+        }
+        results
+    }
+
+    let configuration = || {
+        SegregatedSlabConfiguration::multi_slab(
+            RangeOf::new(0, 40),
+            vec![
+                SlabGroupConfiguration {
+                    slab: 4,
+                    slab_count: 2,
+                    classes: vec![1, 2],
+                },
+                SlabGroupConfiguration {
+                    slab: 16,
+                    slab_count: 2,
+                    classes: vec![8, 16],
+                },
+            ],
+        )
+        .unwrap()
+        .with_fallback_policy(FallbackPolicy::NextLargerClass)
+    };
+
+    for seed in 0..16 {
+        // state with partial, full & empty slabs (some of them previously assigned)
+        let mut allocator =
+            SegregatedSlabSuballocator::<u32>::new_from_configuration(configuration()).unwrap();
+        let mut allocations = Vec::new();
+        apply(
+            &mut allocator,
+            &mut allocations,
+            &harness::ArrayOperation::random_sequence(seed, 40, 20),
+        );
+        allocator.grow(RangeOf::new(0, 56)).unwrap();
+
+        // restore
+        let snapshot = allocator.snapshot();
+        let mut restored = SegregatedSlabSuballocator::restore(snapshot.clone()).unwrap();
+        assert_eq!(restored.snapshot(), snapshot);
+        assert_eq!(restored.stats(), allocator.stats());
+
+        // serialized
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&snapshot).unwrap();
+            let deserialized: SegregatedSlabSnapshot<u32> = serde_json::from_str(&json).unwrap();
+            assert_eq!(deserialized, snapshot);
+        }
+
+        // same next operations
+        let next_operations = harness::ArrayOperation::random_sequence(seed + 100, 40, 20);
+        let mut restored_allocations = allocations.clone();
+        assert_eq!(
+            apply(&mut restored, &mut restored_allocations, &next_operations),
+            apply(&mut allocator, &mut allocations, &next_operations),
+            "seed {seed}"
+        );
+        assert_eq!(restored.snapshot(), allocator.snapshot());
+    }
+
+    // inconsistent snapshots are rejected
+    let mut allocator =
+        SegregatedSlabSuballocator::<u32>::new_from_configuration(configuration()).unwrap();
+    allocator.allocate(2).unwrap();
+    let snapshot = allocator.snapshot();
+
+    let mut overlapping = snapshot.clone();
+    overlapping.slabs[0].allocations.push(RangeOf::new(0, 2));
+    let mut unlisted = snapshot.clone();
+    unlisted.partial_slab_indices_per_class[1].1.clear();
+    let mut not_covering = snapshot;
+    not_covering.slabs.pop();
+    for invalid_snapshot in [overlapping, unlisted, not_covering] {
+        assert!(matches!(
+            SegregatedSlabSuballocator::restore(invalid_snapshot),
+            Err(SuballocationError::InvalidConfiguration { .. })
+        ));
+    }
+}
+
+/// Same steps as module documentation, allocations lettered as in it.
+#[test]
+fn documentation_memory_maps() {
+    let allocator_configuration =
+        SegregatedSlabConfiguration::classes(RangeOf::new(0, 32), 8, vec![2, 4]).unwrap();
+    let mut allocator =
+        SegregatedSlabSuballocator::<u8>::new_from_configuration(allocator_configuration).unwrap();
+    let ascii = |allocator: &SegregatedSlabSuballocator<u8>, named: &[(RangeOf<u8>, char)]| {
+        allocator.to_ascii_with(|_, range| {
+            named
+                .iter()
+                .find(|(named_range, _)| *named_range == range)
+                .map_or('?', |(_, letter)| *letter)
+        })
+    };
+    assert_eq!(
+        allocator.to_ascii(),
+        "|| ######## || ######## || ######## || ########"
+    );
+
+    // allocation
+    let a = allocator.allocate(3).unwrap();
+    let b = allocator.allocate(2).unwrap();
+    let c = allocator.allocate(4).unwrap();
+    let d = allocator.allocate(4).unwrap();
+    assert_eq!(
+        ascii(&allocator, &[(a, 'a'), (b, 'b'), (c, 'c'), (d, 'd')]),
+        "|4| aaa~cccc |2| bb###### |4| dddd#### || ########"
+    );
+
+    // deallocation
+    allocator.deallocate(a).unwrap();
+    allocator.deallocate(d).unwrap();
+    assert_eq!(
+        ascii(&allocator, &[(b, 'b'), (c, 'c')]),
+        "|4| ####cccc |2| bb###### || ######## || ########"
+    );
+
+    // reallocation
+    let c = allocator.reallocate(c, 1).unwrap();
+    assert_eq!(
+        ascii(&allocator, &[(b, 'b'), (c, 'c')]),
+        "|| ######## |2| bbc~#### || ######## || ########"
+    );
+    let b = allocator.reallocate(b, 1).unwrap();
+    assert_eq!(
+        ascii(&allocator, &[(b, 'b'), (c, 'c')]),
+        "|| ######## |2| b~c~#### || ######## || ########"
+    );
+    let mut b = allocator.reallocate(b, 3).unwrap();
+    assert_eq!(
+        ascii(&allocator, &[(b, 'b'), (c, 'c')]),
+        "|4| bbb~#### |2| ##c~#### || ######## || ########"
+    );
+    for size in [4, 6] {
+        b = allocator.reallocate(b, size).unwrap();
+    }
+    assert_eq!(
+        ascii(&allocator, &[(b, 'b'), (c, 'c')]),
+        "|4| bbbbbb~~ |2| ##c~#### || ######## || ########"
+    );
+
+    // resizing
+    allocator.grow(RangeOf::new(0, 48)).unwrap();
+    allocator.shrink_to_fit();
+    assert_eq!(
+        ascii(&allocator, &[(b, 'b'), (c, 'c')]),
+        "|4| bbbbbb~~ |2| ##c~####"
+    );
+
+    // svg : one row per slab
+    let svg = allocator.to_svg();
+    assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<rect").count(), 16);
+    assert!(svg.contains("|4| 6/8") && svg.contains("|2| 1/8"));
+}
+
 #[derive(Debug, Clone, Copy)]
 struct Item {
     range: RangeOf<i32>,
@@ -0,0 +1,64 @@
+use std::fmt::Display;
+
+/////////////////////////////////////////////////////////////////////////////
+// Structure
+/////////////////////////////////////////////////////////////////////////////
+
+/// Failures of suballocators, to react differently to each of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SuballocationError {
+    /// No space left for this allocation (might succeed after deallocating or growing).
+    OutOfMemory,
+    /// Null sizes can't be allocated.
+    SizeZero,
+    /// Size bigger than the biggest allocation possible.
+    SizeTooLarge { max: usize },
+    /// Range/Index isn't a live allocation.
+    NotAllocated,
+    /// Range/Index isn't inside the managed range.
+    OutOfBounds,
+    /// Invalid configuration, or invalid argument when resizing.
+    InvalidConfiguration { reason: String },
+    /// Allocation tagged with `frame`, older than the last allocation frame `last_frame`.
+    FrameOutOfOrder { frame: u64, last_frame: u64 },
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////////
+
+/// New
+impl SuballocationError {
+    pub fn invalid_configuration(reason: impl Into<String>) -> Self {
+        SuballocationError::InvalidConfiguration {
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Display
+impl Display for SuballocationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SuballocationError::OutOfMemory => write!(f, "out of memory"),
+            SuballocationError::SizeZero => write!(f, "size is zero"),
+            SuballocationError::SizeTooLarge { max } => {
+                write!(f, "size too large (max {max})")
+            }
+            SuballocationError::NotAllocated => write!(f, "not allocated"),
+            SuballocationError::OutOfBounds => write!(f, "out of bounds"),
+            SuballocationError::InvalidConfiguration { reason } => {
+                write!(f, "invalid configuration : {reason}")
+            }
+            SuballocationError::FrameOutOfOrder { frame, last_frame } => {
+                write!(
+                    f,
+                    "frame {frame} is older than last allocation frame {last_frame}"
+                )
+            }
+        }
+    }
+}
+
+/// Error
+impl std::error::Error for SuballocationError {}
@@ -1,27 +1,44 @@
 //! Unit allocator using an empty slot table.
+//!
+//! Runs of consecutive slots (`UnitRunSuballocation`) :
+//! - Free runs are kept ordered (first slot => length), the lowest fitting one is used.
+//! - Slots allocated by a run stay in the empty slot stack, they are skipped when reached (lazy removal).
+
+mod concurrent;
+mod snapshot;
+
+use std::collections::BTreeMap;
 
 use mem_utils::{IndexOf, RangeOf};
 
-use super::UnitSuballocation;
+use super::{
+    MemoryMap, MemoryMapAllocation, MemoryMapRow, Result, SuballocationError, UnitRunSuballocation,
+    UnitSuballocation, check_align,
+};
 
-type Error = Box<dyn std::error::Error>;
-type Result<T> = std::result::Result<T, Error>;
+pub use concurrent::ConcurrentTableSuballocator;
+pub use snapshot::TableSnapshot;
 
 /////////////////////////////////////////////////////////////////////////////
 // Structure
 /////////////////////////////////////////////////////////////////////////////
 
+/// Invariants :
+/// - free slot => slot index in `empty_slot_indices` (once)
+/// - free slot <=> slot index in a run of `free_runs`
 pub struct TableSuballocator<U> {
     range: RangeOf<U>,
     slots: Vec<Slot<U>>,
 
     // indices
-    empty_slot_indices: Vec<usize>,
+    empty_slot_indices: Vec<usize>, // might contain allocated slots (allocated by runs)
+    free_runs: BTreeMap<usize, usize>, // first slot index => length, maximal runs
 }
 
 struct Slot<U> {
     index: IndexOf<U>,
     is_allocated: bool,
+    is_stacked: bool, // in `empty_slot_indices`
 }
 
 /////////////////////////////////////////////////////////////////////////////
@@ -31,17 +48,20 @@ struct Slot<U> {
 /// New
 impl<U> TableSuballocator<U> {
     pub fn new(range: RangeOf<U>) -> TableSuballocator<U> {
-        let slots = (range.offset..range.offset + range.size)
+        let slots: Vec<Slot<U>> = (range.offset..range.offset + range.size)
             .map(|index| Slot {
                 index: IndexOf::new(index),
                 is_allocated: false,
+                is_stacked: true,
             })
             .collect();
         let empty_slot_indices = (0..range.size).rev().collect();
+        let free_runs = free_runs_from(&slots);
         TableSuballocator {
             range,
             slots,
             empty_slot_indices,
+            free_runs,
         }
     }
 }
@@ -50,18 +70,120 @@ impl<U> TableSuballocator<U> {
 impl<U> TableSuballocator<U> {
     fn slot_index_from(&self, index: IndexOf<U>) -> Result<usize> {
         if !self.range.to_std_range().contains(&index.index) {
-            return Err("out of bounds".into());
+            return Err(SuballocationError::OutOfBounds);
         }
         Ok(index.index - self.range.offset)
     }
+
+    /// First slot index of the lowest run of `count` free slots, O(free run count).
+    fn free_run_from(&self, count: usize) -> Result<usize> {
+        // check
+        if count == 0 {
+            return Err(SuballocationError::SizeZero);
+        }
+        if count > self.range.size {
+            return Err(SuballocationError::SizeTooLarge {
+                max: self.range.size,
+            });
+        }
+
+        // lowest fitting free run
+        self.free_runs
+            .iter()
+            .find(|(_, length)| **length >= count)
+            .map(|(first_slot_index, _)| *first_slot_index)
+            .ok_or(SuballocationError::OutOfMemory)
+    }
+
+    /// Pop empty slot indices until a free one (allocated ones were allocated by runs).
+    fn pop_empty_slot_index(&mut self) -> Option<usize> {
+        while let Some(slot_index) = self.empty_slot_indices.pop() {
+            let slot = &mut self.slots[slot_index];
+            slot.is_stacked = false;
+            if !slot.is_allocated {
+                return Some(slot_index);
+            }
+        }
+        None
+    }
+
+    /// Mark free slots as allocated, splitting their free run.
+    fn allocate_slots(&mut self, slot_indices: std::ops::Range<usize>) {
+        for slot in &mut self.slots[slot_indices.clone()] {
+            slot.is_allocated = true;
+        }
+
+        // UNWRAP: slots are free, so in a free run
+        let (&first_slot_index, &length) = self
+            .free_runs
+            .range(..=slot_indices.start)
+            .next_back()
+            .unwrap();
+        self.free_runs.remove(&first_slot_index);
+        if first_slot_index < slot_indices.start {
+            self.free_runs
+                .insert(first_slot_index, slot_indices.start - first_slot_index);
+        }
+        if slot_indices.end < first_slot_index + length {
+            self.free_runs.insert(
+                slot_indices.end,
+                first_slot_index + length - slot_indices.end,
+            );
+        }
+    }
+
+    /// Mark allocated slots as free, merging their free run with adjacent ones.
+    fn free_slots(&mut self, slot_indices: std::ops::Range<usize>) {
+        for slot in &mut self.slots[slot_indices.clone()] {
+            slot.is_allocated = false;
+        }
+
+        let mut run = slot_indices;
+        if let Some((&previous_first, &previous_length)) =
+            self.free_runs.range(..run.start).next_back()
+            && previous_first + previous_length == run.start
+        {
+            self.free_runs.remove(&previous_first);
+            run.start = previous_first;
+        }
+        if let Some(next_length) = self.free_runs.remove(&run.end) {
+            run.end += next_length;
+        }
+        self.free_runs.insert(run.start, run.len());
+    }
+
+    /// Push on top of the stack (if not already in it).
+    fn push_empty_slot_index(&mut self, slot_index: usize) {
+        let slot = &mut self.slots[slot_index];
+        if !slot.is_stacked {
+            slot.is_stacked = true;
+            self.empty_slot_indices.push(slot_index);
+        }
+    }
+}
+
+/// Snapshot
+impl<U> TableSuballocator<U> {
+    pub fn snapshot(&self) -> TableSnapshot<U> {
+        snapshot::snapshot_from(self)
+    }
+
+    /// Identical to the snapshotted allocator (next allocations included).
+    pub fn restore(snapshot: TableSnapshot<U>) -> Result<TableSuballocator<U>> {
+        snapshot::restore(snapshot)
+    }
 }
 
 /// Suballocate
 impl<U> UnitSuballocation<U> for TableSuballocator<U> {
     //------// query //------//
 
+    fn range(&self) -> RangeOf<U> {
+        self.range
+    }
+
     fn can_allocate(&self) -> bool {
-        !self.empty_slot_indices.is_empty()
+        !self.free_runs.is_empty()
     }
 
     fn is_allocated(&self, index: IndexOf<U>) -> bool {
@@ -73,36 +195,102 @@ impl<U> UnitSuballocation<U> for TableSuballocator<U> {
 
     //------// suballocate //------//
 
-    fn allocate(&mut self) -> crate::Result<IndexOf<U>> {
+    fn allocate(&mut self) -> Result<IndexOf<U>> {
+        // extract empty slot
+        let slot_index = self
+            .pop_empty_slot_index()
+            .ok_or(SuballocationError::OutOfMemory)?;
+
+        // allocate
+        self.allocate_slots(slot_index..slot_index + 1);
+        Ok(self.slots[slot_index].index)
+    }
+
+    fn allocate_aligned(&mut self, align: usize) -> Result<IndexOf<U>> {
         // check
-        if !self.can_allocate() {
-            return Err("cannot allocate".into());
-        }
+        check_align(align)?;
 
-        // extract empty slot
-        let slot_index = self.empty_slot_indices.pop().unwrap(); // UNWRAP: can_allocate passed
-        let slot = &mut self.slots[slot_index];
+        // extract aligned empty slot (top of the stack first)
+        let Some(position) = self.empty_slot_indices.iter().rposition(|slot_index| {
+            let slot = &self.slots[*slot_index];
+            !slot.is_allocated && slot.index.index.is_multiple_of(align)
+        }) else {
+            return Err(SuballocationError::OutOfMemory);
+        };
+        let slot_index = self.empty_slot_indices.remove(position);
+        self.slots[slot_index].is_stacked = false;
 
         // allocate
-        slot.is_allocated = true;
-        Ok(slot.index)
+        self.allocate_slots(slot_index..slot_index + 1);
+        Ok(self.slots[slot_index].index)
     }
 
-    fn deallocate(&mut self, index: IndexOf<U>) -> crate::Result<()> {
+    fn deallocate(&mut self, index: IndexOf<U>) -> Result<()> {
         // check
-        if !self.is_allocated(index) {
-            return Err("cannot deallocate".into());
+        let slot_index = self.slot_index_from(index)?;
+        if !self.slots[slot_index].is_allocated {
+            return Err(SuballocationError::NotAllocated);
         }
 
-        // extract `index` slot
-        let slot_index = self.slot_index_from(index).unwrap(); // UNWRAP: is_allocated passed
-        let slot = &mut self.slots[slot_index];
-
         // deallocate
-        slot.is_allocated = false;
+        self.free_slots(slot_index..slot_index + 1);
+        self.push_empty_slot_index(slot_index);
         Ok(())
     }
 
+    //------// resize //------//
+
+    fn grow(&mut self, new_range: RangeOf<U>) -> Result<()> {
+        // check
+        if new_range.offset != self.range.offset {
+            return Err(SuballocationError::invalid_configuration(
+                "cannot grow to a different offset",
+            ));
+        }
+        if new_range.size < self.range.size {
+            return Err(SuballocationError::invalid_configuration(
+                "cannot grow to a smaller size",
+            ));
+        }
+
+        // new slots
+        let old_slot_count = self.slots.len();
+        self.slots
+            .extend((self.range.end()..new_range.end()).map(|index| Slot {
+                index: IndexOf::new(index),
+                is_allocated: true, // freed below
+                is_stacked: true,
+            }));
+        if self.slots.len() > old_slot_count {
+            self.free_slots(old_slot_count..self.slots.len());
+        }
+
+        // new empty slot indices : at the bottom of the stack so that old slots are used first
+        let new_slot_indices = (old_slot_count..self.slots.len()).rev();
+        self.empty_slot_indices.splice(0..0, new_slot_indices);
+
+        self.range = new_range;
+        Ok(())
+    }
+
+    fn shrink_to_fit(&mut self) -> RangeOf<U> {
+        // count trailing free slots
+        let slot_count = self
+            .slots
+            .iter()
+            .rposition(|slot| slot.is_allocated)
+            .map_or(0, |last_allocated| last_allocated + 1);
+
+        // release them (trailing free run)
+        self.slots.truncate(slot_count);
+        self.empty_slot_indices
+            .retain(|slot_index| *slot_index < slot_count);
+        self.free_runs.remove(&slot_count);
+
+        self.range = RangeOf::new(self.range.offset, slot_count);
+        self.range
+    }
+
     //------// debug //------//
 
     fn allocations(&self) -> Vec<IndexOf<U>> {
@@ -112,3 +300,279 @@ impl<U> UnitSuballocation<U> for TableSuballocator<U> {
             .collect()
     }
 }
+
+/// Suballocate runs
+impl<U> UnitRunSuballocation<U> for TableSuballocator<U> {
+    //------// query //------//
+
+    fn can_allocate_run(&self, count: usize) -> bool {
+        self.free_run_from(count).is_ok()
+    }
+
+    //------// suballocate //------//
+
+    /// Lowest free run first, O(free run count).
+    fn allocate_run(&mut self, count: usize) -> Result<RangeOf<U>> {
+        // extract free run
+        let first_slot_index = self.free_run_from(count)?;
+
+        // allocate (slots stay in the stack, skipped when popped)
+        self.allocate_slots(first_slot_index..first_slot_index + count);
+        Ok(RangeOf::new(self.range.offset + first_slot_index, count))
+    }
+
+    fn deallocate_run(&mut self, range: RangeOf<U>) -> Result<()> {
+        // check
+        if !range.is_subrange_of(&self.range) {
+            return Err(SuballocationError::OutOfBounds);
+        }
+        let slot_indices = range.offset - self.range.offset..range.end() - self.range.offset;
+        if range.size == 0
+            || !self.slots[slot_indices.clone()]
+                .iter()
+                .all(|slot| slot.is_allocated)
+        {
+            return Err(SuballocationError::NotAllocated);
+        }
+
+        // deallocate (first slot on top of the stack)
+        self.free_slots(slot_indices.clone());
+        for slot_index in slot_indices.rev() {
+            self.push_empty_slot_index(slot_index);
+        }
+        Ok(())
+    }
+}
+
+/// Memory map
+impl<U> MemoryMap<U> for TableSuballocator<U> {
+    fn memory_map(&self) -> Vec<MemoryMapRow<U>> {
+        vec![memory_map_row_from(self.range, self.allocations())]
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Utils
+/////////////////////////////////////////////////////////////////////////////
+
+/// Maximal runs of free slots.
+fn free_runs_from<U>(slots: &[Slot<U>]) -> BTreeMap<usize, usize> {
+    let mut free_runs = BTreeMap::new();
+    let mut run_start = None;
+    for (slot_index, slot) in slots.iter().enumerate() {
+        match (slot.is_allocated, run_start) {
+            (false, None) => run_start = Some(slot_index),
+            (true, Some(first_slot_index)) => {
+                free_runs.insert(first_slot_index, slot_index - first_slot_index);
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(first_slot_index) = run_start {
+        free_runs.insert(first_slot_index, slots.len() - first_slot_index);
+    }
+    free_runs
+}
+
+/// Single row, one allocation per allocated slot.
+fn memory_map_row_from<U>(range: RangeOf<U>, indices: Vec<IndexOf<U>>) -> MemoryMapRow<U> {
+    MemoryMapRow {
+        range,
+        label: None,
+        class: None,
+        allocations: indices
+            .into_iter()
+            .map(|index| MemoryMapAllocation {
+                range: RangeOf::new(index.index, 1),
+                reserved_size: 1,
+            })
+            .collect(),
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::harness;
+
+    #[test]
+    fn reuse_grow_and_shrink() {
+        let mut allocator = TableSuballocator::<u32>::new(RangeOf::new(2, 2));
+
+        // deallocated slots are reused
+        let one = allocator.allocate().unwrap();
+        let two = allocator.allocate().unwrap();
+        assert_eq!(allocator.allocate(), Err(SuballocationError::OutOfMemory));
+        allocator.deallocate(one).unwrap();
+        assert_eq!(
+            allocator.deallocate(one),
+            Err(SuballocationError::NotAllocated)
+        );
+        assert_eq!(
+            allocator.deallocate(IndexOf::new(4)),
+            Err(SuballocationError::OutOfBounds)
+        );
+        assert_eq!(allocator.allocate().unwrap(), one);
+
+        // grow : old slots are used first
+        allocator.deallocate(one).unwrap();
+        allocator.grow(RangeOf::new(2, 4)).unwrap();
+        assert_eq!(allocator.allocate().unwrap(), one);
+        let three = allocator.allocate().unwrap();
+        assert_eq!(three.index, 4);
+
+        // shrink : only trailing free slots are released
+        assert_eq!(allocator.shrink_to_fit(), RangeOf::new(2, 3));
+        allocator.deallocate(three).unwrap();
+        allocator.deallocate(two).unwrap();
+        assert_eq!(allocator.shrink_to_fit(), RangeOf::new(2, 1));
+        assert!(allocator.is_allocated(one));
+        assert!(!allocator.can_allocate());
+    }
+
+    #[test]
+    fn aligned() {
+        let mut allocator = TableSuballocator::<u32>::new(RangeOf::new(1, 8));
+
+        assert_eq!(allocator.allocate_aligned(4).unwrap().index, 4);
+        assert_eq!(allocator.allocate_aligned(4).unwrap().index, 8);
+        assert_eq!(allocator.to_ascii(), "###a###b");
+        assert_eq!(
+            allocator.allocate_aligned(4),
+            Err(SuballocationError::OutOfMemory)
+        );
+        assert_eq!(allocator.allocate_aligned(3).unwrap().index, 3);
+        assert_eq!(allocator.allocate().unwrap().index, 1);
+    }
+
+    #[test]
+    fn runs() {
+        let mut allocator = TableSuballocator::<u32>::new(RangeOf::new(2, 8));
+
+        // lowest free run, single allocations avoid it
+        let one = allocator.allocate().unwrap();
+        let run = allocator.allocate_run(3).unwrap();
+        assert_eq!(run, RangeOf::new(3, 3));
+        assert_eq!(allocator.allocate().unwrap().index, 6);
+        assert_eq!(allocator.to_ascii(), "abcde###");
+
+        // holes are skipped
+        allocator.deallocate(one).unwrap();
+        assert!(!allocator.can_allocate_run(4));
+        assert_eq!(
+            allocator.allocate_run(4),
+            Err(SuballocationError::OutOfMemory)
+        );
+        assert_eq!(allocator.allocate_run(3).unwrap(), RangeOf::new(7, 3));
+        assert_eq!(
+            allocator.allocate_run(9),
+            Err(SuballocationError::SizeTooLarge { max: 8 })
+        );
+        assert_eq!(allocator.allocate_run(0), Err(SuballocationError::SizeZero));
+
+        // runs are deallocated at once, all units must be allocated
+        assert_eq!(
+            allocator.deallocate_run(RangeOf::new(2, 2)),
+            Err(SuballocationError::NotAllocated)
+        );
+        allocator.deallocate_run(run).unwrap();
+        assert_eq!(allocator.allocate().unwrap().index, 3);
+        assert_eq!(allocator.allocate_run(2).unwrap(), RangeOf::new(4, 2));
+        assert_eq!(allocator.to_ascii(), "#abcdefg");
+    }
+
+    /// Free runs match a scan of the table, whatever the mix of units & runs.
+    #[test]
+    fn runs_match_scan() {
+        let mut allocator = TableSuballocator::<u32>::new(RangeOf::new(1, 32));
+        let mut rng = harness::Rng::new(7);
+        let mut indices = Vec::new();
+        let mut runs = Vec::new();
+        for _ in 0..2000 {
+            let count = 1 + rng.next_u64() as usize % 6;
+            match rng.next_u64() % 4 {
+                0 => indices.extend(allocator.allocate().ok()),
+                1 => runs.extend(allocator.allocate_run(count).ok()),
+                2 if !indices.is_empty() => {
+                    let index = indices.swap_remove(rng.next_u64() as usize % indices.len());
+                    allocator.deallocate(index).unwrap();
+                }
+                3 if !runs.is_empty() => {
+                    let run = runs.swap_remove(rng.next_u64() as usize % runs.len());
+                    allocator.deallocate_run(run).unwrap();
+                }
+                _ => {}
+            }
+
+            // lowest run of `count` free slots by scan
+            let ascii = allocator.to_ascii();
+            let expected = ascii.find(&"#".repeat(count));
+            let actual = allocator.free_run_from(count).ok();
+            assert_eq!(actual, expected, "{ascii}");
+            assert_eq!(allocator.can_allocate(), ascii.contains('#'));
+        }
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let mut allocator = TableSuballocator::<u32>::new(RangeOf::new(3, 6));
+        let indices: Vec<_> = (0..5).map(|_| allocator.allocate().unwrap()).collect();
+        allocator.deallocate(indices[3]).unwrap();
+        allocator.deallocate(indices[1]).unwrap();
+
+        // same snapshot, same next indices
+        let snapshot = allocator.snapshot();
+        let mut restored = TableSuballocator::restore(snapshot.clone()).unwrap();
+        assert_eq!(restored.snapshot(), snapshot);
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&snapshot).unwrap();
+            let deserialized: TableSnapshot<u32> = serde_json::from_str(&json).unwrap();
+            assert_eq!(deserialized, snapshot);
+        }
+        for _ in 0..4 {
+            assert_eq!(restored.allocate(), allocator.allocate());
+        }
+
+        // inconsistent snapshots are rejected
+        let mut duplicated = snapshot.clone();
+        duplicated.stacked_indices.push(indices[1]);
+        let mut missing = snapshot;
+        missing.stacked_indices.pop();
+        for invalid_snapshot in [duplicated, missing] {
+            assert!(matches!(
+                TableSuballocator::restore(invalid_snapshot),
+                Err(SuballocationError::InvalidConfiguration { .. })
+            ));
+        }
+
+        // slots allocated by a run keep their stack position once freed
+        let mut allocator = TableSuballocator::<u32>::new(RangeOf::new(0, 4));
+        assert_eq!(allocator.allocate(), Ok(IndexOf::new(0)));
+        let run = allocator.allocate_run(2).unwrap();
+        assert_eq!(run, RangeOf::new(1, 2));
+        let mut restored = TableSuballocator::restore(allocator.snapshot()).unwrap();
+        for allocator in [&mut allocator, &mut restored] {
+            allocator.deallocate(IndexOf::new(0)).unwrap();
+            allocator.deallocate_run(run).unwrap();
+        }
+        assert_eq!(restored.snapshot(), allocator.snapshot());
+        for _ in 0..4 {
+            assert_eq!(restored.allocate(), allocator.allocate());
+        }
+    }
+
+    /// Random sequences checked against a reference model (see `harness`).
+    #[test]
+    fn model_based() {
+        let new_allocator = || TableSuballocator::<u32>::new(RangeOf::new(3, 32));
+        if let Err(failure) = harness::check_unit(new_allocator, 0..64, 200) {
+            panic!("{failure}");
+        }
+    }
+}
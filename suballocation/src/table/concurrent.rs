@@ -0,0 +1,257 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+use mem_utils::{IndexOf, RangeOf};
+
+use crate::{MemoryMap, MemoryMapRow, Result, SuballocationError};
+
+use super::memory_map_row_from;
+
+/////////////////////////////////////////////////////////////////////////////
+// Structure
+/////////////////////////////////////////////////////////////////////////////
+
+/// Table usable from several threads (`&self`), without locks.
+///
+/// Empty slots form a linked stack (Treiber stack) :
+/// - `head` packs the top slot index (low 32 bits) with a tag (high 32 bits).
+/// - The tag is incremented by every push/pop, so that a stale `head` fails its compare exchange (ABA problem).
+///
+/// Range is fixed (no `grow` nor `shrink_to_fit`) and limited to `u32::MAX - 1` slots.
+pub struct ConcurrentTableSuballocator<U> {
+    range: RangeOf<U>, // immutable
+    slots: Vec<Slot>,
+
+    // indices
+    head: AtomicU64, // tagged top of empty slot stack
+}
+
+struct Slot {
+    next_empty_slot_index: AtomicU32, // meaningful only when slot is in the stack
+    is_allocated: AtomicBool,
+}
+
+const NO_SLOT: u32 = u32::MAX;
+
+/////////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////////
+
+/// New
+impl<U> ConcurrentTableSuballocator<U> {
+    pub fn new(range: RangeOf<U>) -> Result<ConcurrentTableSuballocator<U>> {
+        // check
+        if range.size >= NO_SLOT as usize {
+            return Err(SuballocationError::invalid_configuration(format!(
+                "slot count {} should be less than {NO_SLOT}",
+                range.size
+            )));
+        }
+
+        // stack : 0 -> 1 -> ... -> last (first slots are used first)
+        let slot_count = range.size as u32;
+        let slots = (0..slot_count)
+            .map(|slot_index| Slot {
+                next_empty_slot_index: AtomicU32::new(if slot_index + 1 < slot_count {
+                    slot_index + 1
+                } else {
+                    NO_SLOT
+                }),
+                is_allocated: AtomicBool::new(false),
+            })
+            .collect();
+        let top = if slot_count > 0 { 0 } else { NO_SLOT };
+
+        Ok(ConcurrentTableSuballocator {
+            range,
+            slots,
+            head: AtomicU64::new(pack(top, 0)),
+        })
+    }
+}
+
+/// Utils
+impl<U> ConcurrentTableSuballocator<U> {
+    fn slot_index_from(&self, index: IndexOf<U>) -> Result<usize> {
+        if !self.range.to_std_range().contains(&index.index) {
+            return Err(SuballocationError::OutOfBounds);
+        }
+        Ok(index.index - self.range.offset)
+    }
+}
+
+/// Suballocate
+impl<U> ConcurrentTableSuballocator<U> {
+    //------------// query //------------//
+
+    pub fn range(&self) -> RangeOf<U> {
+        self.range
+    }
+
+    /// Might be outdated as soon as it returns if other threads allocate.
+    pub fn can_allocate(&self) -> bool {
+        unpack(self.head.load(Ordering::Acquire)).0 != NO_SLOT
+    }
+
+    /// Might be outdated as soon as it returns if other threads deallocate `index`.
+    pub fn is_allocated(&self, index: IndexOf<U>) -> bool {
+        let Ok(slot_index) = self.slot_index_from(index) else {
+            return false;
+        };
+        self.slots[slot_index].is_allocated.load(Ordering::Acquire)
+    }
+
+    //------------// suballocate //------------//
+
+    pub fn allocate(&self) -> Result<IndexOf<U>> {
+        // pop top of the stack
+        let mut head = self.head.load(Ordering::Acquire);
+        let slot_index = loop {
+            let (top, tag) = unpack(head);
+            if top == NO_SLOT {
+                return Err(SuballocationError::OutOfMemory);
+            }
+
+            // `next` might be stale if `top` was popped meanwhile, then the tag changed and exchange fails
+            let next = self.slots[top as usize]
+                .next_empty_slot_index
+                .load(Ordering::Relaxed);
+            match self.head.compare_exchange_weak(
+                head,
+                pack(next, tag.wrapping_add(1)),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break top as usize,
+                Err(actual_head) => head = actual_head,
+            }
+        };
+
+        // allocate
+        self.slots[slot_index]
+            .is_allocated
+            .store(true, Ordering::Release);
+        Ok(IndexOf::new(self.range.offset + slot_index))
+    }
+
+    pub fn deallocate(&self, index: IndexOf<U>) -> Result<()> {
+        // check & deallocate (only one thread can deallocate `index`)
+        let slot_index = self.slot_index_from(index)?;
+        let slot = &self.slots[slot_index];
+        if slot
+            .is_allocated
+            .compare_exchange(true, false, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return Err(SuballocationError::NotAllocated);
+        }
+
+        // push on top of the stack
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            let (top, tag) = unpack(head);
+            slot.next_empty_slot_index.store(top, Ordering::Relaxed);
+            match self.head.compare_exchange_weak(
+                head,
+                pack(slot_index as u32, tag.wrapping_add(1)),
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Ok(()),
+                Err(actual_head) => head = actual_head,
+            }
+        }
+    }
+
+    //------------// debug //------------//
+
+    /// Slots are read one after the other, so it isn't a snapshot if other threads are working.
+    pub fn allocations(&self) -> Vec<IndexOf<U>> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.is_allocated.load(Ordering::Acquire))
+            .map(|(slot_index, _)| IndexOf::new(self.range.offset + slot_index))
+            .collect()
+    }
+}
+
+/// Memory map
+impl<U> MemoryMap<U> for ConcurrentTableSuballocator<U> {
+    /// Slots are read one after the other, so it isn't a snapshot if other threads are working.
+    fn memory_map(&self) -> Vec<MemoryMapRow<U>> {
+        vec![memory_map_row_from(self.range, self.allocations())]
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Utils
+/////////////////////////////////////////////////////////////////////////////
+
+fn pack(slot_index: u32, tag: u32) -> u64 {
+    ((tag as u64) << 32) | slot_index as u64
+}
+
+fn unpack(head: u64) -> (u32, u32) {
+    (head as u32, (head >> 32) as u32)
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod test {
+    use std::{sync::atomic::AtomicUsize, thread};
+
+    use super::*;
+
+    /// Threads allocate & deallocate concurrently, a slot is never owned by two threads.
+    #[test]
+    fn stress() {
+        const THREAD_COUNT: usize = 8;
+        const ITERATION_COUNT: usize = 10_000;
+        let allocator = ConcurrentTableSuballocator::<u32>::new(RangeOf::new(100, 64)).unwrap();
+        let owned: Vec<AtomicBool> = (0..64).map(|_| AtomicBool::new(false)).collect();
+        let allocation_count = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            for thread_index in 0..THREAD_COUNT {
+                let (allocator, owned, allocation_count) = (&allocator, &owned, &allocation_count);
+                scope.spawn(move || {
+                    let mut indices = Vec::new();
+                    for iteration in 0..ITERATION_COUNT {
+                        // keep up to a few indices per thread, varying with thread
+                        if indices.len() < 2 + (thread_index + iteration) % 8 {
+                            let Ok(index) = allocator.allocate() else {
+                                continue;
+                            };
+                            assert!(!owned[index.index - 100].swap(true, Ordering::AcqRel));
+                            allocation_count.fetch_add(1, Ordering::Relaxed);
+                            indices.push(index);
+                        } else {
+                            let index = indices.swap_remove(iteration % indices.len());
+                            assert!(owned[index.index - 100].swap(false, Ordering::AcqRel));
+                            allocator.deallocate(index).unwrap();
+                        }
+                    }
+                    for index in indices {
+                        assert!(owned[index.index - 100].swap(false, Ordering::AcqRel));
+                        allocator.deallocate(index).unwrap();
+                    }
+                });
+            }
+        });
+
+        // everything is free again
+        assert!(allocation_count.load(Ordering::Relaxed) > ITERATION_COUNT);
+        assert!(allocator.allocations().is_empty());
+        for _ in 0..64 {
+            allocator.allocate().unwrap();
+        }
+        assert_eq!(allocator.allocate(), Err(SuballocationError::OutOfMemory));
+        assert_eq!(
+            allocator.deallocate(IndexOf::new(99)),
+            Err(SuballocationError::OutOfBounds)
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use std::fmt::Debug;
+
+use mem_utils::{IndexOf, RangeOf};
+
+use crate::{Result, SuballocationError};
+
+use super::{Slot, TableSuballocator, free_runs_from};
+
+/////////////////////////////////////////////////////////////////////////////
+// Structure
+/////////////////////////////////////////////////////////////////////////////
+
+/// Plain description of a `TableSuballocator`, to save & restore it.
+///
+/// The whole empty slot stack is kept (slots allocated by runs included), so that a restored allocator allocates
+/// the same indices as the original one.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
+pub struct TableSnapshot<U> {
+    pub range: RangeOf<U>,
+    pub allocations: Vec<IndexOf<U>>,
+    pub stacked_indices: Vec<IndexOf<U>>, // bottom to top of empty slot stack, allocated if in `allocations`
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Fonctions
+/////////////////////////////////////////////////////////////////////////////
+
+pub fn snapshot_from<U>(suballocator: &TableSuballocator<U>) -> TableSnapshot<U> {
+    TableSnapshot {
+        range: suballocator.range,
+        allocations: suballocator
+            .slots
+            .iter()
+            .filter_map(|slot| slot.is_allocated.then_some(slot.index))
+            .collect(),
+        stacked_indices: suballocator
+            .empty_slot_indices
+            .iter()
+            .map(|slot_index| suballocator.slots[*slot_index].index)
+            .collect(),
+    }
+}
+
+/// Checks indices are in range, listed at most once in `allocations` & in `stacked_indices`, and free ones are stacked.
+pub fn restore<U>(snapshot: TableSnapshot<U>) -> Result<TableSuballocator<U>> {
+    let TableSnapshot {
+        range,
+        allocations,
+        stacked_indices,
+    } = snapshot;
+
+    // slots
+    let mut slots: Vec<Slot<U>> = range
+        .to_std_range()
+        .map(|index| Slot {
+            index: IndexOf::new(index),
+            is_allocated: false,
+            is_stacked: false,
+        })
+        .collect();
+    let slot_index_from = |index: IndexOf<U>| {
+        range
+            .to_std_range()
+            .contains(&index.index)
+            .then_some(index.index - range.offset)
+            .ok_or(SuballocationError::invalid_configuration(format!(
+                "index {index} should be in range {range}"
+            )))
+    };
+    let listed_twice = |index: IndexOf<U>, list: &str| {
+        SuballocationError::invalid_configuration(format!(
+            "index {index} should be listed once in {list}"
+        ))
+    };
+
+    // allocations & stacked slots (slots allocated by runs can be both)
+    for index in allocations {
+        let slot = &mut slots[slot_index_from(index)?];
+        if std::mem::replace(&mut slot.is_allocated, true) {
+            return Err(listed_twice(index, "allocations"));
+        }
+    }
+    let empty_slot_indices = stacked_indices
+        .into_iter()
+        .map(|index| {
+            let slot_index = slot_index_from(index)?;
+            if std::mem::replace(&mut slots[slot_index].is_stacked, true) {
+                return Err(listed_twice(index, "stacked indices"));
+            }
+            Ok(slot_index)
+        })
+        .collect::<Result<Vec<usize>>>()?;
+    if slots
+        .iter()
+        .any(|slot| !slot.is_allocated && !slot.is_stacked)
+    {
+        return Err(SuballocationError::invalid_configuration(
+            "every index should be allocated or stacked",
+        ));
+    }
+
+    let free_runs = free_runs_from(&slots);
+
+    Ok(TableSuballocator {
+        range,
+        slots,
+        empty_slot_indices,
+        free_runs,
+    })
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Trivial implementations : Debug, Clone, PartialEq, Eq
+/////////////////////////////////////////////////////////////////////////////
+// Can't be derived because it would require U to implement them as well (even if phantom).
+
+/// Debug
+impl<U> Debug for TableSnapshot<U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TableSnapshot")
+            .field("range", &self.range)
+            .field("allocations", &self.allocations)
+            .field("stacked_indices", &self.stacked_indices)
+            .finish()
+    }
+}
+
+/// Clone
+impl<U> Clone for TableSnapshot<U> {
+    fn clone(&self) -> Self {
+        TableSnapshot {
+            range: self.range,
+            allocations: self.allocations.clone(),
+            stacked_indices: self.stacked_indices.clone(),
+        }
+    }
+}
+
+/// PartialEq & Eq
+impl<U> PartialEq for TableSnapshot<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.range == other.range
+            && self.allocations == other.allocations
+            && self.stacked_indices == other.stacked_indices
+    }
+}
+impl<U> Eq for TableSnapshot<U> {}
@@ -0,0 +1,465 @@
+//! Per-allocation user tags & leak report, wrapping any suballocator.
+//!
+//! ```rust,ignore
+//! let mut clouds = TaggedSuballocator::new("clouds", clouds_suballocator);
+//! let cloud = clouds.allocate_tagged(12, "cube")?;
+//! clouds.allocate(4)?; // untagged
+//! assert_eq!(clouds.allocations_tagged(&"cube"), vec![cloud]);
+//! // dropped with 2 outstanding allocations => leak report (debug builds)
+//! ```
+//!
+//! Wrappers implement the suballocation traits, so they are used as the wrapped suballocator (in a `Transaction` for example).
+
+use std::{collections::BTreeMap, fmt::Debug};
+
+use mem_utils::{IndexOf, RangeOf};
+
+use super::{
+    ArrayOfUnitSuballocation, MemoryMap, MemoryMapRow, Result, SuballocationError,
+    UnitSuballocation,
+};
+
+/////////////////////////////////////////////////////////////////////////////
+// Structures
+/////////////////////////////////////////////////////////////////////////////
+
+/// Tags allocations of an `ArrayOfUnitSuballocation`.
+pub struct TaggedSuballocator<S, T: Debug = &'static str> {
+    suballocator: S,
+    tags: Tags<T>,
+}
+
+/// Tags allocations of a `UnitSuballocation`.
+pub struct TaggedUnitSuballocator<S, T: Debug = &'static str> {
+    suballocator: S,
+    tags: Tags<T>,
+}
+
+/// Outstanding allocations, by offset.
+struct Tags<T> {
+    name: &'static str,
+    allocations: BTreeMap<usize, (usize, Option<T>)>, // offset => (size, tag)
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////////
+
+//------------// tags //------------//
+
+/// New
+impl<T> Tags<T> {
+    fn new(name: &'static str) -> Tags<T> {
+        Tags {
+            name,
+            allocations: BTreeMap::new(),
+        }
+    }
+}
+
+/// Utils
+impl<T: Debug> Tags<T> {
+    fn insert(&mut self, offset: usize, size: usize, tag: Option<T>) {
+        self.allocations.insert(offset, (size, tag));
+    }
+
+    fn remove(&mut self, offset: usize) -> Option<T> {
+        self.allocations.remove(&offset).and_then(|(_, tag)| tag)
+    }
+
+    fn set(&mut self, offset: usize, size: usize, tag: T) -> Result<()> {
+        match self.allocations.get_mut(&offset) {
+            Some((allocated_size, old_tag)) if *allocated_size == size => {
+                *old_tag = Some(tag);
+                Ok(())
+            }
+            _ => Err(SuballocationError::NotAllocated),
+        }
+    }
+
+    fn get(&self, offset: usize, size: usize) -> Option<&T> {
+        self.allocations
+            .get(&offset)
+            .filter(|(allocated_size, _)| *allocated_size == size)
+            .and_then(|(_, tag)| tag.as_ref())
+    }
+
+    fn offsets_tagged(&self, tag: &T) -> impl Iterator<Item = (usize, usize)>
+    where
+        T: PartialEq,
+    {
+        self.allocations
+            .iter()
+            .filter(move |(_, (_, allocation_tag))| allocation_tag.as_ref() == Some(tag))
+            .map(|(offset, (size, _))| (*offset, *size))
+    }
+
+    fn report(&self) -> String {
+        let mut report = format!(
+            "{} : {} outstanding allocation(s)",
+            self.name,
+            self.allocations.len()
+        );
+        for (offset, (size, tag)) in &self.allocations {
+            report += &format!("\n- {}..{} : {:?}", offset, offset + size, tag);
+        }
+        report
+    }
+
+    /// Leak report in debug builds.
+    fn report_leaks(&self) {
+        if cfg!(debug_assertions) && !self.allocations.is_empty() {
+            eprintln!("{}", self.report());
+        }
+    }
+}
+
+//------------// array //------------//
+
+/// New
+impl<S, T: Debug> TaggedSuballocator<S, T> {
+    /// `name` identifies the suballocator in leak reports.
+    pub fn new(name: &'static str, suballocator: S) -> TaggedSuballocator<S, T> {
+        TaggedSuballocator {
+            suballocator,
+            tags: Tags::new(name),
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.suballocator
+    }
+}
+
+/// Tags
+impl<S, T: Debug> TaggedSuballocator<S, T> {
+    pub fn allocate_tagged<U>(&mut self, size: usize, tag: T) -> Result<RangeOf<U>>
+    where
+        S: ArrayOfUnitSuballocation<U>,
+    {
+        let range = self.suballocator.allocate(size)?;
+        self.tags.insert(range.offset, range.size, Some(tag));
+        Ok(range)
+    }
+
+    /// Tag (or retag) an allocation.
+    pub fn set_tag<U>(&mut self, range: RangeOf<U>, tag: T) -> Result<()>
+    where
+        S: ArrayOfUnitSuballocation<U>,
+    {
+        self.tags.set(range.offset, range.size, tag)
+    }
+
+    pub fn tag<U>(&self, range: RangeOf<U>) -> Option<&T>
+    where
+        S: ArrayOfUnitSuballocation<U>,
+    {
+        self.tags.get(range.offset, range.size)
+    }
+
+    /// Sorted by offset.
+    pub fn allocations_tagged<U>(&self, tag: &T) -> Vec<RangeOf<U>>
+    where
+        S: ArrayOfUnitSuballocation<U>,
+        T: PartialEq,
+    {
+        self.tags
+            .offsets_tagged(tag)
+            .map(|(offset, size)| RangeOf::new(offset, size))
+            .collect()
+    }
+
+    /// Outstanding allocations with their tags.
+    pub fn leak_report(&self) -> String {
+        self.tags.report()
+    }
+}
+
+/// Suballocate
+impl<U, S: ArrayOfUnitSuballocation<U>, T: Debug> ArrayOfUnitSuballocation<U>
+    for TaggedSuballocator<S, T>
+{
+    //------------// query //------------//
+
+    fn range(&self) -> RangeOf<U> {
+        self.suballocator.range()
+    }
+
+    fn can_allocate(&self, size: usize) -> bool {
+        self.suballocator.can_allocate(size)
+    }
+
+    fn is_allocated(&self, range: RangeOf<U>) -> bool {
+        self.suballocator.is_allocated(range)
+    }
+
+    fn can_reallocate(&self, range: RangeOf<U>, size: usize) -> bool {
+        self.suballocator.can_reallocate(range, size)
+    }
+
+    //------------// suballocate //------------//
+
+    fn allocate(&mut self, size: usize) -> Result<RangeOf<U>> {
+        self.allocate_aligned(size, 1)
+    }
+
+    fn deallocate(&mut self, range: RangeOf<U>) -> Result<()> {
+        self.suballocator.deallocate(range)?;
+        self.tags.remove(range.offset);
+        Ok(())
+    }
+
+    fn reallocate(&mut self, range: RangeOf<U>, size: usize) -> Result<RangeOf<U>> {
+        self.reallocate_aligned(range, size, 1)
+    }
+
+    //------------// suballocate aligned //------------//
+
+    fn allocate_aligned(&mut self, size: usize, align: usize) -> Result<RangeOf<U>> {
+        let range = self.suballocator.allocate_aligned(size, align)?;
+        self.tags.insert(range.offset, range.size, None);
+        Ok(range)
+    }
+
+    /// Tag follows the allocation.
+    fn reallocate_aligned(
+        &mut self,
+        range: RangeOf<U>,
+        size: usize,
+        align: usize,
+    ) -> Result<RangeOf<U>> {
+        let new_range = self.suballocator.reallocate_aligned(range, size, align)?;
+        let tag = self.tags.remove(range.offset);
+        self.tags.insert(new_range.offset, new_range.size, tag);
+        Ok(new_range)
+    }
+
+    //------------// resize //------------//
+
+    fn grow(&mut self, new_range: RangeOf<U>) -> Result<()> {
+        self.suballocator.grow(new_range)
+    }
+
+    fn shrink_to_fit(&mut self) -> RangeOf<U> {
+        self.suballocator.shrink_to_fit()
+    }
+
+    //------------// debug //------------//
+
+    fn allocations(&self) -> Vec<RangeOf<U>> {
+        self.suballocator.allocations()
+    }
+}
+
+/// Memory map
+impl<U, S: MemoryMap<U>, T: Debug> MemoryMap<U> for TaggedSuballocator<S, T> {
+    fn memory_map(&self) -> Vec<MemoryMapRow<U>> {
+        self.suballocator.memory_map()
+    }
+}
+
+/// Drop
+impl<S, T: Debug> Drop for TaggedSuballocator<S, T> {
+    fn drop(&mut self) {
+        self.tags.report_leaks();
+    }
+}
+
+//------------// unit //------------//
+
+/// New
+impl<S, T: Debug> TaggedUnitSuballocator<S, T> {
+    /// `name` identifies the suballocator in leak reports.
+    pub fn new(name: &'static str, suballocator: S) -> TaggedUnitSuballocator<S, T> {
+        TaggedUnitSuballocator {
+            suballocator,
+            tags: Tags::new(name),
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.suballocator
+    }
+}
+
+/// Tags
+impl<S, T: Debug> TaggedUnitSuballocator<S, T> {
+    pub fn allocate_tagged<U>(&mut self, tag: T) -> Result<IndexOf<U>>
+    where
+        S: UnitSuballocation<U>,
+    {
+        let index = self.suballocator.allocate()?;
+        self.tags.insert(index.index, 1, Some(tag));
+        Ok(index)
+    }
+
+    /// Tag (or retag) an allocation.
+    pub fn set_tag<U>(&mut self, index: IndexOf<U>, tag: T) -> Result<()>
+    where
+        S: UnitSuballocation<U>,
+    {
+        self.tags.set(index.index, 1, tag)
+    }
+
+    pub fn tag<U>(&self, index: IndexOf<U>) -> Option<&T>
+    where
+        S: UnitSuballocation<U>,
+    {
+        self.tags.get(index.index, 1)
+    }
+
+    /// Sorted by index.
+    pub fn allocations_tagged<U>(&self, tag: &T) -> Vec<IndexOf<U>>
+    where
+        S: UnitSuballocation<U>,
+        T: PartialEq,
+    {
+        self.tags
+            .offsets_tagged(tag)
+            .map(|(index, _)| IndexOf::new(index))
+            .collect()
+    }
+
+    /// Outstanding allocations with their tags.
+    pub fn leak_report(&self) -> String {
+        self.tags.report()
+    }
+}
+
+/// Suballocate
+impl<U, S: UnitSuballocation<U>, T: Debug> UnitSuballocation<U> for TaggedUnitSuballocator<S, T> {
+    //------------// query //------------//
+
+    fn range(&self) -> RangeOf<U> {
+        self.suballocator.range()
+    }
+
+    fn can_allocate(&self) -> bool {
+        self.suballocator.can_allocate()
+    }
+
+    fn is_allocated(&self, index: IndexOf<U>) -> bool {
+        self.suballocator.is_allocated(index)
+    }
+
+    //------------// suballocate //------------//
+
+    fn allocate(&mut self) -> Result<IndexOf<U>> {
+        self.allocate_aligned(1)
+    }
+
+    fn deallocate(&mut self, index: IndexOf<U>) -> Result<()> {
+        self.suballocator.deallocate(index)?;
+        self.tags.remove(index.index);
+        Ok(())
+    }
+
+    //------------// suballocate aligned //------------//
+
+    fn allocate_aligned(&mut self, align: usize) -> Result<IndexOf<U>> {
+        let index = self.suballocator.allocate_aligned(align)?;
+        self.tags.insert(index.index, 1, None);
+        Ok(index)
+    }
+
+    //------------// resize //------------//
+
+    fn grow(&mut self, new_range: RangeOf<U>) -> Result<()> {
+        self.suballocator.grow(new_range)
+    }
+
+    fn shrink_to_fit(&mut self) -> RangeOf<U> {
+        self.suballocator.shrink_to_fit()
+    }
+
+    //------------// debug //------------//
+
+    fn allocations(&self) -> Vec<IndexOf<U>> {
+        self.suballocator.allocations()
+    }
+}
+
+/// Memory map
+impl<U, S: MemoryMap<U>, T: Debug> MemoryMap<U> for TaggedUnitSuballocator<S, T> {
+    fn memory_map(&self) -> Vec<MemoryMapRow<U>> {
+        self.suballocator.memory_map()
+    }
+}
+
+/// Drop
+impl<S, T: Debug> Drop for TaggedUnitSuballocator<S, T> {
+    fn drop(&mut self) {
+        self.tags.report_leaks();
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        Transaction,
+        segregated_slab::{SegregatedSlabConfiguration, SegregatedSlabSuballocator},
+        table::TableSuballocator,
+    };
+
+    #[test]
+    fn tags_and_leaks() {
+        let configuration = SegregatedSlabConfiguration::pot(RangeOf::new(0, 16), 4, 1).unwrap();
+        let mut clouds = TaggedSuballocator::<_, usize>::new(
+            "clouds",
+            SegregatedSlabSuballocator::<u32>::new_from_configuration(configuration).unwrap(),
+        );
+        let mut shapes = TaggedUnitSuballocator::<_, &'static str>::new(
+            "shapes",
+            TableSuballocator::<u32>::new(RangeOf::new(0, 4)),
+        );
+
+        // tagged & untagged allocations
+        let one = clouds.allocate_tagged(3, 1).unwrap();
+        let two = clouds.allocate(2).unwrap();
+        let cube = shapes.allocate_tagged("cube").unwrap();
+        assert_eq!(clouds.tag(one), Some(&1));
+        assert_eq!(clouds.tag(two), None);
+        assert_eq!(shapes.tag(cube), Some(&"cube"));
+
+        // tag later (after a transaction for example)
+        let transaction = Transaction::new();
+        let mut participant = transaction.join(&mut clouds);
+        let three = participant.allocate(1).unwrap();
+        transaction.commit();
+        drop(participant);
+        clouds.set_tag(two, 1).unwrap();
+        clouds.set_tag(three, 3).unwrap();
+        assert_eq!(
+            clouds.set_tag(RangeOf::new(15, 1), 1),
+            Err(SuballocationError::NotAllocated)
+        );
+        assert_eq!(clouds.allocations_tagged(&1), vec![one, two]);
+
+        // tag follows reallocation, is removed by deallocation
+        let two = clouds.reallocate(two, 4).unwrap();
+        assert_eq!(clouds.allocations_tagged(&1), vec![one, two]);
+        clouds.deallocate(one).unwrap();
+        assert_eq!(clouds.allocations_tagged(&1), vec![two]);
+        assert_eq!(clouds.tag(one), None);
+
+        // leak report (sorted by offset)
+        assert!(two.offset < three.offset);
+        assert_eq!(
+            clouds.leak_report(),
+            format!(
+                "clouds : 2 outstanding allocation(s)\n- {}..{} : Some(1)\n- {}..{} : Some(3)",
+                two.offset,
+                two.end(),
+                three.offset,
+                three.end()
+            )
+        );
+        shapes.deallocate(cube).unwrap();
+        assert_eq!(shapes.leak_report(), "shapes : 0 outstanding allocation(s)");
+    }
+}
@@ -0,0 +1,220 @@
+//! All-or-nothing allocation across several suballocators.
+//!
+//! ```rust,ignore
+//! let transaction = Transaction::new();
+//! let mut clouds = transaction.join(&mut clouds_suballocator);
+//! let mut shapes = transaction.join(&mut shapes_suballocator);
+//! let cloud = clouds.allocate(12)?; // rolled back if a next line fails
+//! let other_cloud = clouds.allocate(4)?;
+//! let shape = shapes.allocate_unit()?;
+//! transaction.commit();
+//! ```
+//!
+//! Each suballocator joins once and is borrowed by its participant until the participant is dropped.
+
+use std::cell::Cell;
+
+use mem_utils::{IndexOf, RangeOf};
+
+use super::{ArrayOfUnitSuballocation, Result, UnitRunSuballocation, UnitSuballocation};
+
+/////////////////////////////////////////////////////////////////////////////
+// Structures
+/////////////////////////////////////////////////////////////////////////////
+
+/// Commit state shared by participants.
+#[derive(Debug, Default)]
+pub struct Transaction {
+    committed: Cell<bool>,
+}
+
+/// A suballocator joined to a transaction.
+///
+/// Dropping it before the transaction is committed deallocates all its allocations (newest first).
+pub struct Participant<'t, 'a, S: ?Sized> {
+    transaction: &'t Transaction,
+    suballocator: &'a mut S,
+    rollbacks: Vec<Rollback<'a, S>>,
+}
+
+/// Deallocation of a recorded range/index (the suballocator is given back on rollback).
+type Rollback<'a, S> = Box<dyn FnOnce(&mut S) + 'a>;
+
+/////////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////////
+
+/// New
+impl Transaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Join & Commit
+impl Transaction {
+    /// Borrow `suballocator` to allocate from it (as many times as needed) within the transaction.
+    pub fn join<'t, 'a, S: ?Sized>(&'t self, suballocator: &'a mut S) -> Participant<'t, 'a, S> {
+        Participant {
+            transaction: self,
+            suballocator,
+            rollbacks: Vec::new(),
+        }
+    }
+
+    /// Keep all allocations of all participants (later ones included).
+    pub fn commit(&self) {
+        self.committed.set(true);
+    }
+
+    pub fn is_committed(&self) -> bool {
+        self.committed.get()
+    }
+}
+
+/// Allocate
+impl<'a, S: ?Sized> Participant<'_, 'a, S> {
+    pub fn allocate<U: 'a>(&mut self, size: usize) -> Result<RangeOf<U>>
+    where
+        S: ArrayOfUnitSuballocation<U>,
+    {
+        self.allocate_aligned(size, 1)
+    }
+
+    pub fn allocate_aligned<U: 'a>(&mut self, size: usize, align: usize) -> Result<RangeOf<U>>
+    where
+        S: ArrayOfUnitSuballocation<U>,
+    {
+        let range = self.suballocator.allocate_aligned(size, align)?;
+        self.rollbacks.push(Box::new(move |suballocator: &mut S| {
+            suballocator.deallocate(range).unwrap(); // UNWRAP: allocated above & borrowed since
+        }));
+        Ok(range)
+    }
+
+    pub fn allocate_unit<U: 'a>(&mut self) -> Result<IndexOf<U>>
+    where
+        S: UnitSuballocation<U>,
+    {
+        let index = self.suballocator.allocate()?;
+        self.rollbacks.push(Box::new(move |suballocator: &mut S| {
+            suballocator.deallocate(index).unwrap(); // UNWRAP: allocated above & borrowed since
+        }));
+        Ok(index)
+    }
+
+    pub fn allocate_run<U: 'a>(&mut self, count: usize) -> Result<RangeOf<U>>
+    where
+        S: UnitRunSuballocation<U>,
+    {
+        let range = self.suballocator.allocate_run(count)?;
+        self.rollbacks.push(Box::new(move |suballocator: &mut S| {
+            suballocator.deallocate_run(range).unwrap(); // UNWRAP: allocated above & borrowed since
+        }));
+        Ok(range)
+    }
+}
+
+/// Drop
+impl<S: ?Sized> Drop for Participant<'_, '_, S> {
+    fn drop(&mut self) {
+        if self.transaction.is_committed() {
+            return;
+        }
+        while let Some(rollback) = self.rollbacks.pop() {
+            rollback(self.suballocator);
+        }
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        SuballocationError,
+        segregated_slab::{SegregatedSlabConfiguration, SegregatedSlabSuballocator},
+        table::TableSuballocator,
+    };
+
+    fn allocate_all(
+        slab: &mut SegregatedSlabSuballocator<u32>,
+        table: &mut TableSuballocator<u32>,
+        size: usize,
+    ) -> Result<(RangeOf<u32>, IndexOf<u32>)> {
+        let transaction = Transaction::new();
+        let mut slab = transaction.join(slab);
+        let mut table = transaction.join(table);
+        let range = slab.allocate(size)?;
+        let index = table.allocate_unit()?;
+        transaction.commit();
+        Ok((range, index))
+    }
+
+    #[test]
+    fn all_or_nothing() {
+        let configuration = SegregatedSlabConfiguration::pot(RangeOf::new(0, 8), 4, 4).unwrap();
+        let mut slab = SegregatedSlabSuballocator::new_from_configuration(configuration).unwrap();
+        let mut table = TableSuballocator::new(RangeOf::new(0, 1));
+
+        // commit
+        let (range, index) = allocate_all(&mut slab, &mut table, 4).unwrap();
+        assert!(slab.is_allocated(range) && table.is_allocated(index));
+
+        // table full => slab allocation rolled back
+        assert_eq!(
+            allocate_all(&mut slab, &mut table, 4),
+            Err(SuballocationError::OutOfMemory)
+        );
+        assert_eq!(slab.allocations(), vec![range]);
+
+        // not committed => rolled back
+        table.deallocate(index).unwrap();
+        {
+            let transaction = Transaction::new();
+            transaction.join(&mut slab).allocate(4).unwrap();
+            transaction.join(&mut table).allocate_unit().unwrap();
+        }
+        assert_eq!(slab.allocations(), vec![range]);
+        assert!(table.allocations().is_empty());
+    }
+
+    #[test]
+    fn same_suballocator_twice() {
+        let configuration = SegregatedSlabConfiguration::pot(RangeOf::new(0, 16), 4, 4).unwrap();
+        let mut slab =
+            SegregatedSlabSuballocator::<u32>::new_from_configuration(configuration).unwrap();
+        let mut table = TableSuballocator::<u32>::new(RangeOf::new(0, 4));
+        let kept = slab.allocate(4).unwrap();
+
+        // two allocations & a run, then a failure => all rolled back
+        let result = (|| {
+            let transaction = Transaction::new();
+            let mut slab = transaction.join(&mut slab);
+            let mut table = transaction.join(&mut table);
+            slab.allocate(4)?;
+            slab.allocate(2)?;
+            table.allocate_run(2)?;
+            table.allocate_unit()?;
+            slab.allocate(16)?; // too big
+            transaction.commit();
+            Ok::<_, SuballocationError>(())
+        })();
+        assert!(result.is_err());
+        assert_eq!(slab.allocations(), vec![kept]);
+        assert!(table.allocations().is_empty());
+
+        // same allocations committed
+        let transaction = Transaction::new();
+        let mut participant = transaction.join(&mut slab);
+        let one = participant.allocate(4).unwrap();
+        let two = participant.allocate(2).unwrap();
+        transaction.commit();
+        drop(participant);
+        assert_eq!(slab.allocations().len(), 3);
+        assert!(slab.is_allocated(one) && slab.is_allocated(two));
+    }
+}
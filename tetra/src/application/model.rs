@@ -65,7 +65,12 @@ impl Model {
 
 /// Add
 impl Model {
-    pub fn add(&mut self, device: &Device, allocator: &Allocator, shape: ShapeData) -> Result<()> {
-        add::add(self, device, allocator, shape)
+    pub fn add(
+        &mut self,
+        device: &Device,
+        allocator: &Allocator,
+        shapes: Vec<ShapeData>,
+    ) -> Result<()> {
+        add::add(self, device, allocator, shapes)
     }
 }
@@ -2,9 +2,12 @@ use std::mem::offset_of;
 
 use ash::vk;
 use glam::{Quat, Vec3};
-use mem_utils::{IndexOf, RangeOf};
-use suballocation::{ArrayOfUnitSuballocation, UnitSuballocation};
-use vk_mem::Allocator;
+use mem_utils::{CopyPlan, CopyRegion, IndexOf, RangeOf};
+use suballocation::{
+    Participant, TaggedSuballocator, Transaction, segregated_slab::SegregatedSlabSuballocator,
+    table::TableSuballocator,
+};
+use vk_mem::{Allocation, Allocator};
 
 use crate::context::{Device, device::QueueRoleFlags};
 
@@ -35,19 +38,96 @@ pub fn add(
     model: &mut Model,
     device: &Device,
     allocator: &Allocator,
-    shape: ShapeData,
+    new_shapes: Vec<ShapeData>,
 ) -> Result<()> {
     // check input data
-    check_shape(&shape)?;
+    for shape in &new_shapes {
+        check_shape(shape)?;
+    }
 
-    // allocate
-    let allocations = allocate(model, &shape)?;
+    // split borrows : suballocators are joined to the transaction while the rest is used
+    let Model {
+        shapes,
+        clouds,
+        surfaces,
+        transfer_command_pool,
+        add,
+        ready_to_add,
+    } = model;
+    let transfer = Transfer {
+        command_pool: *transfer_command_pool,
+        command_buffer: *add,
+        fence: *ready_to_add,
+        visible_clouds: (clouds.full.buffer, clouds.visible.buffer),
+        visible_surfaces: (surfaces.full.buffer, surfaces.visible.buffer),
+        shape_infos: (shapes.full.buffer, shapes.info.buffer),
+        shape_diics: (shapes.full.buffer, shapes.diic.buffer),
+    };
 
-    // write (CPU-side)
-    write(model, allocator, allocations, shape)?;
+    // all shapes or none : allocations are rolled back unless committed (after upload)
+    let transaction = Transaction::new();
+    let all_allocations = {
+        let mut participants = Participants {
+            cloud: transaction.join(&mut clouds.full.suballocator),
+            visible_cloud: transaction.join(&mut clouds.visible.suballocator),
+            surface: transaction.join(&mut surfaces.full.suballocator),
+            visible_surface: transaction.join(&mut surfaces.visible.suballocator),
+            shape: transaction.join(&mut shapes.full.suballocator),
+        };
+
+        // allocate
+        let all_allocations = new_shapes
+            .iter()
+            .map(|shape| allocate(&mut participants, shape))
+            .collect::<Result<Vec<Allocations>>>()?;
+
+        // write (CPU-side)
+        let mut memories = Memories {
+            clouds: &mut *clouds.full.memory,
+            surfaces: &mut *surfaces.full.memory,
+            shapes: &mut *shapes.full.memory,
+            allocations: [
+                &clouds.full.allocation,
+                &surfaces.full.allocation,
+                &shapes.full.allocation,
+            ],
+        };
+        for (&allocations, shape) in all_allocations.iter().zip(new_shapes) {
+            write(allocator, &mut memories, allocations, shape)?;
+        }
+
+        // upload (GPU-side, one submission for every shape)
+        upload(device, transfer, &all_allocations)?;
+
+        transaction.commit();
+        all_allocations
+    };
 
-    // upload (GPU-side)
-    upload(model, device, allocations)?;
+    // tag ranges with their owner (leak reports)
+    // UNWRAP: ranges were just allocated by the same (tagged) suballocators, so they are tracked
+    for allocations in all_allocations {
+        let owner = allocations.shape;
+        clouds
+            .full
+            .suballocator
+            .set_tag(allocations.cloud, owner)
+            .unwrap();
+        clouds
+            .visible
+            .suballocator
+            .set_tag(allocations.visible_cloud, owner)
+            .unwrap();
+        surfaces
+            .full
+            .suballocator
+            .set_tag(allocations.surface, owner)
+            .unwrap();
+        surfaces
+            .visible
+            .suballocator
+            .set_tag(allocations.visible_surface, owner)
+            .unwrap();
+    }
 
     //////
     Ok(())
@@ -89,51 +169,49 @@ struct Allocations {
     shape_diic: IndexOf<vk::DrawIndexedIndirectCommand>,
 }
 
-fn allocate(model: &mut Model, shape: &ShapeData) -> Result<Allocations> {
-    // check if enough memory
-    if !model
-        .clouds
-        .full
-        .suballocator
-        .can_allocate(shape.cloud.len())
-        || !model
-            .clouds
-            .visible
-            .suballocator
-            .can_allocate(shape.visible_cloud_len)
-        || !model
-            .surfaces
-            .full
-            .suballocator
-            .can_allocate(shape.surface.len())
-        || !model
-            .surfaces
-            .visible
-            .suballocator
-            .can_allocate(shape.visible_surface_len)
-        || !model.shapes.full.suballocator.can_allocate()
-    {
-        return Err("not enough memory".into());
-    }
+type ShapeTaggedSuballocator<U> = TaggedSuballocator<SegregatedSlabSuballocator<U>, IndexOf<Shape>>;
+
+/// Suballocators of the model, joined to the transaction of an `add`.
+struct Participants<'t, 'a> {
+    cloud: Participant<'t, 'a, ShapeTaggedSuballocator<Vertex>>,
+    visible_cloud: Participant<'t, 'a, ShapeTaggedSuballocator<Vertex>>,
+    surface: Participant<'t, 'a, ShapeTaggedSuballocator<Triangle>>,
+    visible_surface: Participant<'t, 'a, ShapeTaggedSuballocator<Triangle>>,
+    shape: Participant<'t, 'a, TableSuballocator<Shape>>,
+}
 
-    // allocate
-    let cloud = model.clouds.full.suballocator.allocate(shape.cloud.len())?;
-    let visible_cloud = model
-        .clouds
-        .visible
-        .suballocator
+/// Host memory written by an `add`, with its allocations (flushed).
+struct Memories<'m> {
+    clouds: &'m mut [Vertex],
+    surfaces: &'m mut [Triangle],
+    shapes: &'m mut [Shape],
+    allocations: [&'m Allocation; 3], // clouds, surfaces, shapes
+}
+
+/// Handles used by `upload`, copied out of the model (its suballocators are borrowed meanwhile).
+#[derive(Debug, Clone, Copy)]
+struct Transfer {
+    command_pool: vk::CommandPool,
+    command_buffer: vk::CommandBuffer,
+    fence: vk::Fence, // signaled when the last `add` is done
+
+    // (src, dst) buffers
+    visible_clouds: (vk::Buffer, vk::Buffer),
+    visible_surfaces: (vk::Buffer, vk::Buffer),
+    shape_infos: (vk::Buffer, vk::Buffer),
+    shape_diics: (vk::Buffer, vk::Buffer),
+}
+
+fn allocate(participants: &mut Participants, shape: &ShapeData) -> Result<Allocations> {
+    let cloud = participants.cloud.allocate(shape.cloud.len())?;
+    let visible_cloud = participants
+        .visible_cloud
         .allocate(shape.visible_cloud_len)?;
-    let surface = model
-        .surfaces
-        .full
-        .suballocator
-        .allocate(shape.surface.len())?;
-    let visible_surface = model
-        .surfaces
-        .visible
-        .suballocator
+    let surface = participants.surface.allocate(shape.surface.len())?;
+    let visible_surface = participants
+        .visible_surface
         .allocate(shape.visible_surface_len)?;
-    let shape = model.shapes.full.suballocator.allocate()?;
+    let shape = participants.shape.allocate_unit()?;
 
     Ok(Allocations {
         cloud,
@@ -147,21 +225,19 @@ fn allocate(model: &mut Model, shape: &ShapeData) -> Result<Allocations> {
 }
 
 fn write(
-    model: &mut Model,
     allocator: &Allocator,
+    memories: &mut Memories,
     allocations: Allocations,
     shape: ShapeData,
 ) -> Result<()> {
     // cloud
-    model.clouds.full.memory[allocations.cloud.to_std_range()]
-        .copy_from_slice(shape.cloud.as_slice());
+    memories.clouds[allocations.cloud].copy_from_slice(shape.cloud.as_slice());
 
     // surface
-    model.surfaces.full.memory[allocations.surface.to_std_range()]
-        .copy_from_slice(shape.surface.as_slice());
+    memories.surfaces[allocations.surface].copy_from_slice(shape.surface.as_slice());
 
     // shape
-    model.shapes.full.memory[allocations.shape.index] = Shape {
+    memories.shapes[allocations.shape] = Shape {
         // cloud
         cloud: allocations.cloud,
         visible_cloud: allocations.visible_cloud,
@@ -186,11 +262,6 @@ fn write(
     };
 
     // flush
-    let flush_allocations = [
-        &model.clouds.full.allocation,
-        &model.surfaces.full.allocation,
-        &model.shapes.full.allocation,
-    ];
     let offsets = [
         allocations.cloud.byte_offset() as u64,
         allocations.surface.byte_offset() as u64,
@@ -201,30 +272,28 @@ fn write(
         allocations.surface.byte_size() as u64,
         allocations.shape.byte_size() as u64,
     ];
-    unsafe { allocator.flush_allocations(flush_allocations, Some(&offsets), Some(&sizes)) }?;
+    unsafe { allocator.flush_allocations(memories.allocations, Some(&offsets), Some(&sizes)) }?;
 
     Ok(())
 }
 
-fn upload(model: &mut Model, device: &Device, allocations: Allocations) -> Result<()> {
+fn upload(device: &Device, transfer: Transfer, all_allocations: &[Allocations]) -> Result<()> {
     // wait & reset `ready_to_add` (last "add" operation)
-    let fences = [model.ready_to_add];
+    let fences = [transfer.fence];
     unsafe { device.wait_for_fences(&fences, true, u64::MAX) }?;
     unsafe { device.reset_fences(&fences) }?;
 
     // reset transfer command pool
     unsafe {
-        device.reset_command_pool(
-            model.transfer_command_pool,
-            vk::CommandPoolResetFlags::empty(),
-        )
+        device.reset_command_pool(transfer.command_pool, vk::CommandPoolResetFlags::empty())
     }?;
 
     // record `add`
-    record_add(model, device, allocations)?;
+    record_add(device, transfer, all_allocations)?;
 
     // submit `add` : signal `ready_to_add` when done
-    let command_buffer_info = vk::CommandBufferSubmitInfo::default().command_buffer(model.add);
+    let command_buffer_info =
+        vk::CommandBufferSubmitInfo::default().command_buffer(transfer.command_buffer);
     let command_buffer_infos = [command_buffer_info];
     let submit = vk::SubmitInfo2::default().command_buffer_infos(&command_buffer_infos);
     let submits = [submit];
@@ -232,80 +301,90 @@ fn upload(model: &mut Model, device: &Device, allocations: Allocations) -> Resul
         device.queue_submit2(
             device.queue(QueueRoleFlags::TRANSFER).vk_queue,
             &submits,
-            model.ready_to_add,
+            transfer.fence,
         )
     }?;
 
     Ok(())
 }
 
-fn record_add(model: &mut Model, device: &Device, allocations: Allocations) -> Result<()> {
+fn record_add(device: &Device, transfer: Transfer, all_allocations: &[Allocations]) -> Result<()> {
     // begin
     let begin_info = vk::CommandBufferBeginInfo::default();
-    unsafe { device.begin_command_buffer(model.add, &begin_info) }?;
-
-    // copy visible cloud
-    let region = vk::BufferCopy::default()
-        .src_offset(allocations.cloud.byte_offset() as u64)
-        .dst_offset(allocations.visible_cloud.byte_offset() as u64)
-        .size(allocations.visible_cloud.byte_size() as u64);
-    let regions = [region];
-    unsafe {
-        device.cmd_copy_buffer(
-            model.add,
-            model.clouds.full.buffer,
-            model.clouds.visible.buffer,
-            &regions,
-        )
-    };
-
-    // copy visible surface
-    let region = vk::BufferCopy::default()
-        .src_offset(allocations.surface.byte_offset() as u64)
-        .dst_offset(allocations.visible_surface.byte_offset() as u64)
-        .size(allocations.visible_surface.byte_size() as u64);
-    let regions = [region];
-    unsafe {
-        device.cmd_copy_buffer(
-            model.add,
-            model.surfaces.full.buffer,
-            model.surfaces.visible.buffer,
-            &regions,
-        )
-    };
-
-    // copy shape info
-    let region = vk::BufferCopy::default()
-        .src_offset((allocations.shape.byte_offset() + offset_of!(Shape, info)) as u64)
-        .dst_offset(allocations.shape_info.byte_offset() as u64)
-        .size(allocations.shape_info.byte_size() as u64);
-    let regions = [region];
-    unsafe {
-        device.cmd_copy_buffer(
-            model.add,
-            model.shapes.full.buffer,
-            model.shapes.info.buffer,
-            &regions,
-        )
-    };
+    unsafe { device.begin_command_buffer(transfer.command_buffer, &begin_info) }?;
+
+    // plan copies (one plan per buffer pair, regions of every shape coalesced)
+    let mut visible_cloud: CopyPlan<Vertex> = CopyPlan::new();
+    let mut visible_surface: CopyPlan<Triangle> = CopyPlan::new();
+    let mut shape_info: CopyPlan<u8> = CopyPlan::new(); // bytes : a field of `Shape`
+    let mut shape_diic: CopyPlan<u8> = CopyPlan::new(); // bytes : a field of `Shape`
+    for allocations in all_allocations {
+        visible_cloud.push(
+            allocations
+                .cloud
+                .subrange(0, allocations.visible_cloud.size)?,
+            allocations.visible_cloud,
+        )?;
+        visible_surface.push(
+            allocations
+                .surface
+                .subrange(0, allocations.visible_surface.size)?,
+            allocations.visible_surface,
+        )?;
+        shape_info.push(
+            RangeOf::new(
+                allocations.shape.byte_offset() + offset_of!(Shape, info),
+                allocations.shape_info.byte_size(),
+            ),
+            RangeOf::new(
+                allocations.shape_info.byte_offset(),
+                allocations.shape_info.byte_size(),
+            ),
+        )?;
+        shape_diic.push(
+            RangeOf::new(
+                allocations.shape.byte_offset() + offset_of!(Shape, diic),
+                allocations.shape_diic.byte_size(),
+            ),
+            RangeOf::new(
+                allocations.shape_diic.byte_offset(),
+                allocations.shape_diic.byte_size(),
+            ),
+        )?;
+    }
 
-    // copy shape diic
-    let region = vk::BufferCopy::default()
-        .src_offset((allocations.shape.byte_offset() + offset_of!(Shape, diic)) as u64)
-        .dst_offset(allocations.shape_diic.byte_offset() as u64)
-        .size(allocations.shape_diic.byte_size() as u64);
-    let regions = [region];
-    unsafe {
-        device.cmd_copy_buffer(
-            model.add,
-            model.shapes.full.buffer,
-            model.shapes.diic.buffer,
-            &regions,
-        )
-    };
+    // copy
+    let command_buffer = transfer.command_buffer;
+    let regions = visible_cloud.byte_regions();
+    cmd_copy_regions(device, command_buffer, transfer.visible_clouds, regions);
+    let regions = visible_surface.byte_regions();
+    cmd_copy_regions(device, command_buffer, transfer.visible_surfaces, regions);
+    let regions = shape_info.byte_regions();
+    cmd_copy_regions(device, command_buffer, transfer.shape_infos, regions);
+    let regions = shape_diic.byte_regions();
+    cmd_copy_regions(device, command_buffer, transfer.shape_diics, regions);
 
     // end
-    unsafe { device.end_command_buffer(model.add) }?;
+    unsafe { device.end_command_buffer(transfer.command_buffer) }?;
 
     Ok(())
 }
+
+fn cmd_copy_regions(
+    device: &Device,
+    command_buffer: vk::CommandBuffer,
+    (src_buffer, dst_buffer): (vk::Buffer, vk::Buffer),
+    regions: impl Iterator<Item = CopyRegion>,
+) {
+    let regions: Vec<vk::BufferCopy> = regions
+        .map(|region| {
+            vk::BufferCopy::default()
+                .src_offset(region.src_offset as u64)
+                .dst_offset(region.dst_offset as u64)
+                .size(region.size as u64)
+        })
+        .collect();
+    if !regions.is_empty() {
+        unsafe { device.cmd_copy_buffer(command_buffer, src_buffer, dst_buffer, &regions) };
+    }
+}
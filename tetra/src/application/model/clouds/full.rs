@@ -1,9 +1,15 @@
 use ash::vk;
-use mem_utils::RangeOf;
-use suballocation::segregated_slab::{SegregatedSlabConfiguration, SegregatedSlabSuballocator};
+use mem_utils::{IndexOf, RangeOf};
+use suballocation::{
+    TaggedSuballocator,
+    segregated_slab::{SegregatedSlabConfiguration, SegregatedSlabSuballocator},
+};
 use vk_mem::{Alloc, Allocation, Allocator};
 
-use crate::context::{Device, device::QueueRoleFlags};
+use crate::{
+    application::model::Shape,
+    context::{Device, device::QueueRoleFlags},
+};
 
 use super::Vertex;
 
@@ -23,7 +29,7 @@ pub struct FullClouds {
     pub allocation: Allocation,
     pub buffer: vk::Buffer,
     pub memory: &'static mut [Vertex],
-    pub suballocator: SegregatedSlabSuballocator<Vertex>,
+    pub suballocator: TaggedSuballocator<SegregatedSlabSuballocator<Vertex>, IndexOf<Shape>>, // tagged with owner shape
 }
 
 /////////////////////////////////////////////////////////////////////////////
@@ -58,12 +64,14 @@ impl FullClouds {
         let memory = unsafe { std::slice::from_raw_parts_mut(allocation_ptr, MAX_VERTICES) };
 
         // suballocator
-        let suballocator =
+        let suballocator = TaggedSuballocator::new(
+            "full clouds",
             SegregatedSlabSuballocator::new_from_configuration(SegregatedSlabConfiguration::pot(
                 RangeOf::new(0, MAX_VERTICES),
                 MAX_VERTICES_PER_FULL_CLOUD,
                 MIN_VERTICES_PER_FULL_CLOUD,
-            )?)?;
+            )?)?,
+        );
 
         Ok(Self {
             allocation,
@@ -1,9 +1,15 @@
 use ash::vk;
-use mem_utils::RangeOf;
-use suballocation::segregated_slab::{SegregatedSlabConfiguration, SegregatedSlabSuballocator};
+use mem_utils::{IndexOf, RangeOf};
+use suballocation::{
+    TaggedSuballocator,
+    segregated_slab::{SegregatedSlabConfiguration, SegregatedSlabSuballocator},
+};
 use vk_mem::{Alloc, Allocation, Allocator};
 
-use crate::context::{Device, device::QueueRoleFlags};
+use crate::{
+    application::model::Shape,
+    context::{Device, device::QueueRoleFlags},
+};
 
 use super::Vertex;
 
@@ -22,7 +28,7 @@ const MAX_VERTICES: usize = 4096;
 pub struct VisibleClouds {
     pub allocation: Allocation,
     pub buffer: vk::Buffer,
-    pub suballocator: SegregatedSlabSuballocator<Vertex>,
+    pub suballocator: TaggedSuballocator<SegregatedSlabSuballocator<Vertex>, IndexOf<Shape>>, // tagged with owner shape
 }
 
 /////////////////////////////////////////////////////////////////////////////
@@ -52,12 +58,14 @@ impl VisibleClouds {
             unsafe { allocator.create_buffer(&buffer_info, &allocation_info) }?;
 
         // suballocator
-        let suballocator =
+        let suballocator = TaggedSuballocator::new(
+            "visible clouds",
             SegregatedSlabSuballocator::new_from_configuration(SegregatedSlabConfiguration::pot(
                 RangeOf::new(0, MAX_VERTICES),
                 MAX_VERTICES_PER_VISIBLE_CLOUD,
                 MIN_VERTICES_PER_VISIBLE_CLOUD,
-            )?)?;
+            )?)?,
+        );
 
         Ok(Self {
             allocation,
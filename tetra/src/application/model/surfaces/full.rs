@@ -1,9 +1,15 @@
 use ash::vk;
-use mem_utils::RangeOf;
-use suballocation::segregated_slab::{SegregatedSlabConfiguration, SegregatedSlabSuballocator};
+use mem_utils::{IndexOf, RangeOf};
+use suballocation::{
+    TaggedSuballocator,
+    segregated_slab::{SegregatedSlabConfiguration, SegregatedSlabSuballocator},
+};
 use vk_mem::{Alloc, Allocation, Allocator};
 
-use crate::context::{Device, device::QueueRoleFlags};
+use crate::{
+    application::model::Shape,
+    context::{Device, device::QueueRoleFlags},
+};
 
 use super::Triangle;
 
@@ -23,7 +29,7 @@ pub struct FullSurfaces {
     pub allocation: Allocation,
     pub buffer: vk::Buffer,
     pub memory: &'static mut [Triangle],
-    pub suballocator: SegregatedSlabSuballocator<Triangle>,
+    pub suballocator: TaggedSuballocator<SegregatedSlabSuballocator<Triangle>, IndexOf<Shape>>, // tagged with owner shape
 }
 
 /////////////////////////////////////////////////////////////////////////////
@@ -58,12 +64,14 @@ impl FullSurfaces {
         let memory = unsafe { std::slice::from_raw_parts_mut(allocation_ptr, MAX_TRIANGLES) };
 
         // suballocator
-        let suballocator =
+        let suballocator = TaggedSuballocator::new(
+            "full surfaces",
             SegregatedSlabSuballocator::new_from_configuration(SegregatedSlabConfiguration::pot(
                 RangeOf::new(0, MAX_TRIANGLES),
                 MAX_TRIANGLES_PER_FULL_SURFACE,
                 MIN_TRIANGLES_PER_FULL_SURFACE,
-            )?)?;
+            )?)?,
+        );
 
         Ok(Self {
             allocation,
@@ -1,9 +1,15 @@
 use ash::vk;
-use mem_utils::RangeOf;
-use suballocation::segregated_slab::{SegregatedSlabConfiguration, SegregatedSlabSuballocator};
+use mem_utils::{IndexOf, RangeOf};
+use suballocation::{
+    TaggedSuballocator,
+    segregated_slab::{SegregatedSlabConfiguration, SegregatedSlabSuballocator},
+};
 use vk_mem::{Alloc, Allocation, Allocator};
 
-use crate::context::{Device, device::QueueRoleFlags};
+use crate::{
+    application::model::Shape,
+    context::{Device, device::QueueRoleFlags},
+};
 
 use super::Triangle;
 
@@ -22,7 +28,7 @@ const MAX_TRIANGLES: usize = 4096;
 pub struct VisibleSurfaces {
     pub allocation: Allocation,
     pub buffer: vk::Buffer,
-    pub suballocator: SegregatedSlabSuballocator<Triangle>,
+    pub suballocator: TaggedSuballocator<SegregatedSlabSuballocator<Triangle>, IndexOf<Shape>>, // tagged with owner shape
 }
 
 /////////////////////////////////////////////////////////////////////////////
@@ -52,12 +58,14 @@ impl VisibleSurfaces {
             unsafe { allocator.create_buffer(&buffer_info, &allocation_info) }?;
 
         // suballocator
-        let suballocator =
+        let suballocator = TaggedSuballocator::new(
+            "visible surfaces",
             SegregatedSlabSuballocator::new_from_configuration(SegregatedSlabConfiguration::pot(
                 RangeOf::new(0, MAX_TRIANGLES),
                 MAX_TRIANGLES_PER_VISIBLE_SURFACE,
                 MIN_TRIANGLES_PER_VISIBLE_SURFACE,
-            )?)?;
+            )?)?,
+        );
 
         Ok(Self {
             allocation,
@@ -62,41 +62,35 @@ impl World {
         model.add(
             device,
             allocator,
-            ShapeData {
-                cloud: cube_cloud.clone(),
-                visible_cloud_len: cube_cloud.len(),
-                surface: cube_surface.clone(),
-                visible_surface_len: cube_surface.len(),
-                position: Vec3::new(0., 0., 0.),
-                scale: 1.,
-                orientation: Quat::IDENTITY,
-            },
-        )?;
-        model.add(
-            device,
-            allocator,
-            ShapeData {
-                cloud: cube_cloud.clone(),
-                visible_cloud_len: cube_cloud.len(),
-                surface: cube_surface.clone(),
-                visible_surface_len: cube_surface.len(),
-                position: Vec3::new(5., 0., 0.),
-                scale: 2.,
-                orientation: Quat::from_rotation_x(1.),
-            },
-        )?;
-        model.add(
-            device,
-            allocator,
-            ShapeData {
-                cloud: cube_cloud.clone(),
-                visible_cloud_len: cube_cloud.len(),
-                surface: cube_surface.clone(),
-                visible_surface_len: cube_surface.len(),
-                position: Vec3::new(10., 0., 0.),
-                scale: 0.5,
-                orientation: Quat::from_rotation_y(2.),
-            },
+            vec![
+                ShapeData {
+                    cloud: cube_cloud.clone(),
+                    visible_cloud_len: cube_cloud.len(),
+                    surface: cube_surface.clone(),
+                    visible_surface_len: cube_surface.len(),
+                    position: Vec3::new(0., 0., 0.),
+                    scale: 1.,
+                    orientation: Quat::IDENTITY,
+                },
+                ShapeData {
+                    cloud: cube_cloud.clone(),
+                    visible_cloud_len: cube_cloud.len(),
+                    surface: cube_surface.clone(),
+                    visible_surface_len: cube_surface.len(),
+                    position: Vec3::new(5., 0., 0.),
+                    scale: 2.,
+                    orientation: Quat::from_rotation_x(1.),
+                },
+                ShapeData {
+                    cloud: cube_cloud.clone(),
+                    visible_cloud_len: cube_cloud.len(),
+                    surface: cube_surface.clone(),
+                    visible_surface_len: cube_surface.len(),
+                    position: Vec3::new(10., 0., 0.),
+                    scale: 0.5,
+                    orientation: Quat::from_rotation_y(2.),
+                },
+            ],
         )?;
 
         Ok(())
@@ -3,6 +3,7 @@ mod create_from_configuration;
 mod evaluate_configuration;
 
 use ash::vk;
+use vk_utils::{ApiVersion, Features, Profile};
 
 use super::{Device, Instance, queues};
 
@@ -16,16 +17,28 @@ use evaluate_configuration::evaluate_configuration;
 type Error = Box<dyn std::error::Error>;
 type Result<T> = std::result::Result<T, Error>;
 
+const PROFILE_VARIABLE: &str = "TETRA_PROFILE"; // path of a profile replacing the default one
+const DEFAULT_PROFILE: &str = include_str!("../../../profiles/default.toml");
+
+// needed by tetra's code : a profile may add requirements, not drop these
+const MINIMUM_API_VERSION: ApiVersion = ApiVersion::from_const(vk::API_VERSION_1_3);
+const MINIMUM_EXTENSION_NAMES: [&str; 1] = ["VK_KHR_swapchain"];
+
 /////////////////////////////////////////////////////////////////////////
 
 pub fn create(instance: &Instance, surface: vk::SurfaceKHR) -> Result<Device> {
-    // create arguments : surface + physical devices
+    // create arguments : surface + physical devices + profile
     let physical_devices = unsafe { instance.enumerate_physical_devices() }?;
+    let profile = match std::env::var_os(PROFILE_VARIABLE) {
+        Some(path) => Profile::load(path)?,
+        None => Profile::from_toml(DEFAULT_PROFILE)?,
+    };
+    check_profile(&profile)?;
 
     // configure each physical devices
     let device_configuration_results: Vec<Result<DeviceConfiguration>> = physical_devices
         .into_iter()
-        .map(|physical_device| configure(&instance, physical_device, surface))
+        .map(|physical_device| configure(&instance, physical_device, surface, &profile))
         .collect();
 
     // filter out failed configurations
@@ -46,3 +59,48 @@ pub fn create(instance: &Instance, surface: vk::SurfaceKHR) -> Result<Device> {
         best_device_configuration,
     )?)
 }
+
+/////////////////////////////////////////////////////////////////////////
+// Sub functions
+/////////////////////////////////////////////////////////////////////////
+
+/// Fail if the profile does not require the minimum tetra needs.
+fn check_profile(profile: &Profile) -> Result<()> {
+    // api version
+    if profile
+        .required
+        .api_version
+        .is_none_or(|api_version| api_version < MINIMUM_API_VERSION)
+    {
+        return Err(format!("profile must require api version {MINIMUM_API_VERSION}").into());
+    }
+
+    // extensions
+    for extension_name in MINIMUM_EXTENSION_NAMES {
+        if !profile
+            .required
+            .extensions
+            .iter()
+            .any(|name| name == extension_name)
+        {
+            return Err(format!("profile must require extension {extension_name}").into());
+        }
+    }
+
+    // features
+    let missing_features = profile.required.features()?.missing(&minimum_features());
+    if !missing_features.is_empty() {
+        return Err(format!("profile must require features {missing_features}").into());
+    }
+
+    Ok(())
+}
+
+fn minimum_features() -> Features {
+    let mut features = Features::default();
+    features.features_10.geometry_shader = vk::TRUE; // geometry stage of the rendering pipeline
+    features.features_10.multi_draw_indirect = vk::TRUE; // one indirect draw for all shapes
+    features.features_13.dynamic_rendering = vk::TRUE;
+    features.features_13.synchronization2 = vk::TRUE;
+    features
+}
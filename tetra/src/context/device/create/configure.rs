@@ -1,15 +1,11 @@
-use std::ffi::CStr;
-
 use ash::vk;
-use vk_utils::{ApiVersion, Features};
+use vk_utils::Profile;
 
 use super::{Instance, configuration::*, queues::QueueRoleFlags};
 
 type Error = Box<dyn std::error::Error>;
 type Result<T> = std::result::Result<T, Error>;
 
-const DEVICE_API_VERSION: ApiVersion = ApiVersion::from_const(vk::API_VERSION_1_3);
-
 /////////////////////////////////////////////////////////////////////////
 // Function
 /////////////////////////////////////////////////////////////////////////
@@ -18,13 +14,28 @@ pub fn configure(
     instance: &Instance,
     physical_device: vk::PhysicalDevice,
     surface: vk::SurfaceKHR,
+    profile: &Profile,
 ) -> Result<DeviceConfiguration> {
-    // check
-    instance.physical_device_support_api_version(physical_device, DEVICE_API_VERSION)?;
-
-    // configure
-    let extension_names = configure_extensions(instance, physical_device)?;
-    let features = configure_features(instance, physical_device)?;
+    // check (minimum needed by tetra is checked on the profile, see `create`)
+    let evaluation = unsafe {
+        profile.evaluate(
+            instance,
+            instance.api_version,
+            physical_device,
+            Some((&instance.surface_instance, surface)),
+        )
+    }?;
+    if !evaluation.passed() {
+        return Err(format!(
+            "physical device do not meet profile requirements : {}",
+            evaluation.failures.join(", ")
+        )
+        .into());
+    }
+
+    // configure : required + supported optional
+    let extension_names = evaluation.extension_names();
+    let features = evaluation.enabled_features()?;
     let queue_families = configure_queue_families(instance, physical_device, surface)?;
 
     Ok(DeviceConfiguration {
@@ -43,49 +54,6 @@ pub fn configure(
 // Sub functions
 /////////////////////////////////////////////////////////////////////////
 
-fn configure_extensions(
-    instance: &Instance,
-    physical_device: vk::PhysicalDevice,
-) -> Result<Vec<&'static CStr>> {
-    //-------------// Required //-------------//
-
-    // define
-    let required_extensions = vec![vk::KHR_SWAPCHAIN_NAME];
-
-    // check
-    instance.physical_device_support_extensions(physical_device, &required_extensions)?;
-
-    //-------------//
-
-    Ok(required_extensions)
-}
-
-fn configure_features(
-    instance: &Instance,
-    physical_device: vk::PhysicalDevice,
-) -> Result<Features> {
-    //-------------// Required //-------------//
-
-    // define
-    let required_features = Features {
-        features_10: vk::PhysicalDeviceFeatures::default()
-            .geometry_shader(true)
-            .multi_draw_indirect(true),
-        features_11: vk::PhysicalDeviceVulkan11Features::default(),
-        features_12: vk::PhysicalDeviceVulkan12Features::default(),
-        features_13: vk::PhysicalDeviceVulkan13Features::default()
-            .dynamic_rendering(true)
-            .synchronization2(true),
-    };
-
-    // check
-    instance.physical_device_support_features(physical_device, &required_features)?;
-
-    //-------------//
-
-    Ok(required_features)
-}
-
 fn configure_queue_families(
     instance: &Instance,
     physical_device: vk::PhysicalDevice,
@@ -1,4 +1,4 @@
-use std::ffi::{CStr, c_char};
+use std::ffi::{CString, c_char};
 
 use ash::vk;
 use vk_utils::Features;
@@ -21,7 +21,7 @@ pub mod configuration {
     #[derive(Debug)]
     pub struct DeviceConfiguration {
         // configuration
-        pub extension_names: Vec<&'static CStr>,
+        pub extension_names: Vec<CString>,
         pub features: Features,
         pub queue_families: Vec<QueueFamilyConfiguration>,
 
@@ -47,19 +47,23 @@ pub fn create_from_configuration(
     instance: &Instance,
     mut configuration: configuration::DeviceConfiguration,
 ) -> Result<Device> {
-    // extensions
-    let enabled_extension_names: Vec<*const c_char> = configuration
-        .extension_names
-        .into_iter()
+    // extensions (including those of feature extension blocks)
+    let mut extension_names = configuration.extension_names;
+    for extension_name in configuration.features.extension_names() {
+        if !extension_names
+            .iter()
+            .any(|name| name.as_c_str() == extension_name)
+        {
+            extension_names.push(extension_name.to_owned());
+        }
+    }
+    let enabled_extension_names: Vec<*const c_char> = extension_names
+        .iter()
         .map(|extension| extension.as_ptr())
         .collect();
 
     // features
-    let mut enabled_features = vk::PhysicalDeviceFeatures2::default()
-        .features(configuration.features.features_10)
-        .push_next(&mut configuration.features.features_11)
-        .push_next(&mut configuration.features.features_12)
-        .push_next(&mut configuration.features.features_13);
+    let mut features_chain = configuration.features.chain();
 
     // check all roles are assigned
     let mut total_roles = QueueRoleFlags::empty();
@@ -87,7 +91,7 @@ pub fn create_from_configuration(
     let create_info = vk::DeviceCreateInfo::default()
         .queue_create_infos(&queue_family_create_infos)
         .enabled_extension_names(&enabled_extension_names)
-        .push_next(&mut enabled_features);
+        .push_next(features_chain.features2());
 
     // loaders
     let device =
@@ -2,10 +2,10 @@ mod create;
 mod physical_device_queue_family_support;
 mod physical_device_support;
 
-use std::{ffi::CStr, ops::Deref};
+use std::ops::Deref;
 
 use ash::vk;
-use vk_utils::{ApiVersion, Features};
+use vk_utils::ApiVersion;
 use winit::raw_window_handle::RawDisplayHandle;
 
 use super::Entry;
@@ -26,6 +26,9 @@ pub struct Instance {
     // loaders
     instance: ash::Instance,
     pub surface_instance: ash::khr::surface::Instance,
+
+    // configuration
+    pub api_version: ApiVersion, // requested at creation (devices are used up to min(this, device api version))
 }
 
 /////////////////////////////////////////////////////////////////////////
@@ -54,30 +57,6 @@ impl Instance {
 
 /// Support
 impl Instance {
-    pub fn physical_device_support_api_version(
-        &self,
-        physical_device: vk::PhysicalDevice,
-        api_version: ApiVersion,
-    ) -> Result<()> {
-        physical_device_support::api_version(self, physical_device, api_version)
-    }
-
-    pub fn physical_device_support_extensions(
-        &self,
-        physical_device: vk::PhysicalDevice,
-        extension_names: &[&CStr],
-    ) -> Result<()> {
-        physical_device_support::extensions(self, physical_device, extension_names)
-    }
-
-    pub fn physical_device_support_features(
-        &self,
-        physical_device: vk::PhysicalDevice,
-        features: &Features,
-    ) -> Result<()> {
-        physical_device_support::features(self, physical_device, features)
-    }
-
     pub fn physical_device_support_format_usage_in_optimal_tiling(
         &self,
         physical_device: vk::PhysicalDevice,
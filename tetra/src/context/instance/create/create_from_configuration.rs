@@ -69,5 +69,6 @@ pub fn create_from_configuration(
     Ok(Instance {
         instance,
         surface_instance,
+        api_version: configuration.application.api_version,
     })
 }
@@ -1,4 +1,7 @@
+use std::{fmt::Display, str::FromStr};
+
 use ash::vk;
+use serde::{Deserialize, Serialize};
 
 /////////////////////////////////////////////////////////////////////////
 // Structure
@@ -6,7 +9,9 @@ use ash::vk;
 
 /// - Comparable.
 /// - Conversion from & into `u32`.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// - Conversion from & into "major.minor[.patch]" strings (also in profiles & reports).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct ApiVersion {
     pub major: u32,
     pub minor: u32,
@@ -53,3 +58,44 @@ impl From<ApiVersion> for u32 {
         )
     }
 }
+
+/// Display ("major.minor.patch")
+impl Display for ApiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// From "major.minor[.patch]"
+impl FromStr for ApiVersion {
+    type Err = String;
+
+    fn from_str(version_str: &str) -> Result<Self, Self::Err> {
+        let numbers = version_str
+            .split('.')
+            .map(|number| number.trim().parse::<u32>())
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|_| format!("invalid api version \"{version_str}\""))?;
+        match numbers[..] {
+            [major, minor] => Ok(Self::from(vk::make_api_version(0, major, minor, 0))),
+            [major, minor, patch] => Ok(Self::from(vk::make_api_version(0, major, minor, patch))),
+            _ => Err(format!(
+                "invalid api version \"{version_str}\" (expected \"major.minor[.patch]\")"
+            )),
+        }
+    }
+}
+
+/// Serde (as strings)
+impl TryFrom<String> for ApiVersion {
+    type Error = String;
+
+    fn try_from(version_string: String) -> Result<Self, Self::Error> {
+        version_string.parse()
+    }
+}
+impl From<ApiVersion> for String {
+    fn from(version_struct: ApiVersion) -> Self {
+        version_struct.to_string()
+    }
+}
@@ -0,0 +1,386 @@
+//! Capability report of all physical devices, to diagnose "it doesn't start on my machine".
+//!
+//! Run "cargo run --bin device_report -- [--json] [--profile path/to/profile.toml]".
+//!
+//! - Default output is human readable, `--json` prints the same report as JSON.
+//! - `--profile` evaluates each device against a requirement profile (see `vk_utils::Profile`).
+//! - Surface formats (and surface support of profile queues) need `VK_EXT_headless_surface` (no window is created).
+
+use std::{collections::BTreeMap, ffi::CStr};
+
+use ash::vk;
+use serde::Serialize;
+use vk_utils::{ApiVersion, Features, Profile, ProfileEvaluation};
+
+type Error = Box<dyn std::error::Error>;
+type Result<T> = std::result::Result<T, Error>;
+
+const USAGE: &str = "usage : device_report [--json] [--profile <profile.toml>]";
+
+/////////////////////////////////////////////////////////////////////////
+// Structures
+/////////////////////////////////////////////////////////////////////////
+
+#[derive(Serialize)]
+struct Report {
+    instance_api_version: ApiVersion,
+    devices: Vec<DeviceReport>,
+}
+
+#[derive(Serialize)]
+struct DeviceReport {
+    name: String,
+    device_type: String,
+    api_version: ApiVersion,
+    driver_version: u32,
+    queue_families: Vec<QueueFamilyReport>,
+    memory_heaps: Vec<MemoryHeapReport>,
+    surface_formats: Option<Vec<String>>, // `None` : no headless surface, or not supported by any queue family
+    extensions: Vec<String>,
+    features: BTreeMap<&'static str, Vec<&'static str>>, // block name => supported field names
+    profile: Option<ProfileEvaluation>,
+    errors: Vec<String>, // failed queries (their fields are left empty)
+}
+
+#[derive(Serialize)]
+struct QueueFamilyReport {
+    flags: String,
+    queue_count: u32,
+}
+
+#[derive(Serialize)]
+struct MemoryHeapReport {
+    size: u64,
+    flags: String,
+    memory_types: Vec<String>, // property flags of each type in the heap
+}
+
+/////////////////////////////////////////////////////////////////////////
+// Main
+/////////////////////////////////////////////////////////////////////////
+
+fn main() -> Result<()> {
+    // arguments
+    let mut json = false;
+    let mut profile = None;
+    let mut arguments = std::env::args().skip(1);
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--json" => json = true,
+            "--profile" => {
+                let path = arguments.next().ok_or(USAGE)?;
+                profile = Some(Profile::load(path)?);
+            }
+            _ => return Err(USAGE.into()),
+        }
+    }
+
+    // report
+    let report = report(profile.as_ref())?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+
+    Ok(())
+}
+
+/////////////////////////////////////////////////////////////////////////
+// Report
+/////////////////////////////////////////////////////////////////////////
+
+fn report(profile: Option<&Profile>) -> Result<Report> {
+    //-------------// Instance //-------------//
+
+    let entry = unsafe { ash::Entry::load() }?;
+    let instance_api_version =
+        unsafe { entry.try_enumerate_instance_version() }?.unwrap_or(vk::API_VERSION_1_0);
+
+    // headless surface if available (surface formats)
+    let supported_extensions = unsafe { entry.enumerate_instance_extension_properties(None) }?;
+    let supports = |name: &CStr| {
+        supported_extensions
+            .iter()
+            .any(|extension| extension.extension_name_as_c_str() == Ok(name))
+    };
+    let headless = supports(vk::KHR_SURFACE_NAME) && supports(vk::EXT_HEADLESS_SURFACE_NAME);
+    let extension_names = if headless {
+        vec![
+            vk::KHR_SURFACE_NAME.as_ptr(),
+            vk::EXT_HEADLESS_SURFACE_NAME.as_ptr(),
+        ]
+    } else {
+        vec![]
+    };
+
+    let application_info = vk::ApplicationInfo::default()
+        .application_name(c"device_report")
+        .api_version(instance_api_version);
+    let create_info = vk::InstanceCreateInfo::default()
+        .application_info(&application_info)
+        .enabled_extension_names(&extension_names);
+    let instance = unsafe { entry.create_instance(&create_info, None) }?;
+
+    let surface_instance = ash::khr::surface::Instance::new(&entry, &instance);
+    let surface = if headless {
+        let headless_instance = ash::ext::headless_surface::Instance::new(&entry, &instance);
+        let surface_create_info = vk::HeadlessSurfaceCreateInfoEXT::default();
+        match unsafe { headless_instance.create_headless_surface(&surface_create_info, None) } {
+            Ok(surface) => Some(surface),
+            Err(error) => {
+                unsafe { instance.destroy_instance(None) };
+                return Err(error.into());
+            }
+        }
+    } else {
+        None
+    };
+
+    //-------------// Devices //-------------//
+
+    let devices = match unsafe { instance.enumerate_physical_devices() } {
+        Ok(physical_devices) => Ok(physical_devices
+            .into_iter()
+            .map(|physical_device| {
+                device_report(
+                    &instance,
+                    ApiVersion::from(instance_api_version),
+                    &surface_instance,
+                    surface,
+                    physical_device,
+                    profile,
+                )
+            })
+            .collect::<Vec<DeviceReport>>()),
+        Err(error) => Err(error),
+    };
+
+    // destroy (even on failure)
+    if let Some(surface) = surface {
+        unsafe { surface_instance.destroy_surface(surface, None) };
+    }
+    unsafe { instance.destroy_instance(None) };
+
+    Ok(Report {
+        instance_api_version: ApiVersion::from(instance_api_version),
+        devices: devices?,
+    })
+}
+
+fn device_report(
+    instance: &ash::Instance,
+    instance_api_version: ApiVersion,
+    surface_instance: &ash::khr::surface::Instance,
+    surface: Option<vk::SurfaceKHR>,
+    physical_device: vk::PhysicalDevice,
+    profile: Option<&Profile>,
+) -> DeviceReport {
+    // a failed query is recorded, the rest of the report is still filled
+    let mut errors = Vec::new();
+
+    // properties
+    let properties = unsafe { instance.get_physical_device_properties(physical_device) };
+    let api_version = ApiVersion::from(properties.api_version);
+
+    // queue families
+    let queue_family_properties =
+        unsafe { instance.get_physical_device_queue_family_properties(physical_device) };
+    let queue_families = queue_family_properties
+        .iter()
+        .map(|queue_family| QueueFamilyReport {
+            flags: format!("{:?}", queue_family.queue_flags),
+            queue_count: queue_family.queue_count,
+        })
+        .collect();
+
+    // memory heaps
+    let memory_properties =
+        unsafe { instance.get_physical_device_memory_properties(physical_device) };
+    let memory_heaps = memory_properties
+        .memory_heaps_as_slice()
+        .iter()
+        .enumerate()
+        .map(|(heap_index, heap)| MemoryHeapReport {
+            size: heap.size,
+            flags: format!("{:?}", heap.flags),
+            memory_types: memory_properties
+                .memory_types_as_slice()
+                .iter()
+                .filter(|memory_type| memory_type.heap_index as usize == heap_index)
+                .map(|memory_type| format!("{:?}", memory_type.property_flags))
+                .collect(),
+        })
+        .collect();
+
+    // surface formats (only if a queue family supports the surface)
+    let surface_formats = surface.and_then(|surface| {
+        let supported = (0..queue_family_properties.len() as u32)
+            .map(|family_index| unsafe {
+                surface_instance.get_physical_device_surface_support(
+                    physical_device,
+                    family_index,
+                    surface,
+                )
+            })
+            .collect::<ash::prelude::VkResult<Vec<bool>>>();
+        match recorded(&mut errors, "surface support", supported) {
+            Some(supported) if supported.contains(&true) => {
+                let formats = unsafe {
+                    surface_instance.get_physical_device_surface_formats(physical_device, surface)
+                };
+                recorded(&mut errors, "surface formats", formats).map(|formats| {
+                    formats
+                        .iter()
+                        .map(|format| format!("{:?} {:?}", format.format, format.color_space))
+                        .collect()
+                })
+            }
+            _ => None,
+        }
+    });
+
+    // extensions
+    let extensions = unsafe { instance.enumerate_device_extension_properties(physical_device) };
+    let extensions = recorded(&mut errors, "extensions", extensions)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|extension| extension.extension_name_as_c_str().ok())
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect();
+
+    // features (all blocks chained : Vulkan 1.3 only, usable version is the min of instance & device ones)
+    let features =
+        if api_version.min(instance_api_version) >= ApiVersion::from_const(vk::API_VERSION_1_3) {
+            let features = unsafe { Features::query(instance, physical_device) };
+            recorded(&mut errors, "features", features)
+                .map(|features| features.enabled_names().into_iter().collect())
+                .unwrap_or_default()
+        } else {
+            BTreeMap::new()
+        };
+
+    // profile
+    let profile = profile.and_then(|profile| {
+        let evaluation = unsafe {
+            profile.evaluate(
+                instance,
+                instance_api_version,
+                physical_device,
+                surface.map(|surface| (surface_instance, surface)),
+            )
+        };
+        recorded(&mut errors, "profile", evaluation)
+    });
+
+    DeviceReport {
+        // UNWRAP: `device_name` is null terminated
+        name: properties
+            .device_name_as_c_str()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned(),
+        device_type: format!("{:?}", properties.device_type),
+        api_version,
+        driver_version: properties.driver_version,
+        queue_families,
+        memory_heaps,
+        surface_formats,
+        extensions,
+        features,
+        profile,
+        errors,
+    }
+}
+
+/// `Some` on success, `None` with the error recorded (prefixed by `what`) on failure.
+fn recorded<T, E: std::fmt::Display>(
+    errors: &mut Vec<String>,
+    what: &str,
+    result: std::result::Result<T, E>,
+) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(error) => {
+            errors.push(format!("{what} : {error}"));
+            None
+        }
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////
+// Print
+/////////////////////////////////////////////////////////////////////////
+
+fn print_report(report: &Report) {
+    println!("Instance api version : {}", report.instance_api_version);
+    if report.devices.is_empty() {
+        println!("No physical device");
+    }
+
+    for (index, device) in report.devices.iter().enumerate() {
+        println!(
+            "\nDevice {index} : {} ({})",
+            device.name, device.device_type
+        );
+        println!("  Api version : {}", device.api_version);
+        println!("  Driver version : {:#x}", device.driver_version);
+
+        println!("  Queue families :");
+        for (index, queue_family) in device.queue_families.iter().enumerate() {
+            println!(
+                "    {index} : {} ({} queues)",
+                queue_family.flags, queue_family.queue_count
+            );
+        }
+
+        println!("  Memory heaps :");
+        for (index, heap) in device.memory_heaps.iter().enumerate() {
+            println!(
+                "    {index} : {} MiB {} (types : {})",
+                heap.size / (1024 * 1024),
+                heap.flags,
+                heap.memory_types.join(", ")
+            );
+        }
+
+        match &device.surface_formats {
+            Some(surface_formats) => {
+                println!("  Surface formats : {}", surface_formats.join(", "))
+            }
+            None => println!("  Surface formats : unknown (no headless surface or no support)"),
+        }
+
+        println!(
+            "  Extensions ({}) : {}",
+            device.extensions.len(),
+            device.extensions.join(", ")
+        );
+
+        println!("  Features :");
+        for (block_name, field_names) in &device.features {
+            println!("    {block_name} : {}", field_names.join(", "));
+        }
+
+        if let Some(evaluation) = &device.profile {
+            if evaluation.passed() {
+                println!("  Profile : passed");
+            } else {
+                println!("  Profile : failed");
+                for failure in &evaluation.failures {
+                    println!("    - {failure}");
+                }
+            }
+            for failure in &evaluation.optional_failures {
+                println!("    - optional {failure}");
+            }
+        }
+
+        if !device.errors.is_empty() {
+            println!("  Errors :");
+            for error in &device.errors {
+                println!("    - {error}");
+            }
+        }
+    }
+}
@@ -1,204 +1,782 @@
+use std::{
+    ffi::{CStr, c_void},
+    fmt::Display,
+    ptr,
+};
+
 use ash::vk;
 
 /////////////////////////////////////////////////////////////////////////
-// Structure
+// Structures
 /////////////////////////////////////////////////////////////////////////
 
 /// - Own data.
 /// - Provide `contains_..` functions.
+/// - Provide `missing_..` functions (names of unsupported features, for error messages).
+/// - Provide `union` & `intersection`.
+/// - Provide `query` & `chain` (`FeaturesChain` : `vk::PhysicalDeviceFeatures2` with copies of all blocks pushed).
+///
+/// Core blocks are always present, extension blocks are optional (all features disabled when `None`).
 ///
-/// ```rust
-/// // assemble using `vk::PhysicalDeviceFeatures2`
-/// let features2 = vk::PhysicalDeviceFeatures2::default()
-///    .features(features.vulkan10) // warning : not a &mut
-///    .push_next(&mut features.vulkan11)
-///    .push_next(&mut features.vulkan12)
-///    .push_next(&mut features.vulkan13);
+/// ```rust,ignore
+/// // query support
+/// let supported_features = unsafe { Features::query(&instance, physical_device) }?;
+///
+/// // enable at device creation
+/// let mut chain = features.chain();
+/// let create_info = vk::DeviceCreateInfo::default().push_next(chain.features2());
 /// ```
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Features {
     pub features_10: vk::PhysicalDeviceFeatures,
-    // static lifetimes because `push_next` is not used
+    // static lifetimes because `push_next` is not used (`p_next` always null, chains are built on copies)
     pub features_11: vk::PhysicalDeviceVulkan11Features<'static>,
     pub features_12: vk::PhysicalDeviceVulkan12Features<'static>,
     pub features_13: vk::PhysicalDeviceVulkan13Features<'static>,
+
+    // extensions (`None` : extension not supported by the device, or not required)
+    pub mesh_shader: Option<vk::PhysicalDeviceMeshShaderFeaturesEXT<'static>>,
+    pub ray_query: Option<vk::PhysicalDeviceRayQueryFeaturesKHR<'static>>,
+    pub acceleration_structure: Option<vk::PhysicalDeviceAccelerationStructureFeaturesKHR<'static>>,
+    pub descriptor_buffer: Option<vk::PhysicalDeviceDescriptorBufferFeaturesEXT<'static>>,
+    pub robustness2: Option<vk::PhysicalDeviceRobustness2FeaturesEXT<'static>>,
+    pub extended_dynamic_state3:
+        Option<vk::PhysicalDeviceExtendedDynamicState3FeaturesEXT<'static>>,
+
+    // extensions promoted to Vulkan 1.4 (ash 0.38 has Vulkan 1.3.281 headers, no `PhysicalDeviceVulkan14Features`)
+    pub maintenance5: Option<vk::PhysicalDeviceMaintenance5FeaturesKHR<'static>>,
+    pub maintenance6: Option<vk::PhysicalDeviceMaintenance6FeaturesKHR<'static>>,
+    pub dynamic_rendering_local_read:
+        Option<vk::PhysicalDeviceDynamicRenderingLocalReadFeaturesKHR<'static>>,
+    pub index_type_uint8: Option<vk::PhysicalDeviceIndexTypeUint8FeaturesKHR<'static>>,
+    pub line_rasterization: Option<vk::PhysicalDeviceLineRasterizationFeaturesKHR<'static>>,
+    pub vertex_attribute_divisor:
+        Option<vk::PhysicalDeviceVertexAttributeDivisorFeaturesKHR<'static>>,
+    pub shader_subgroup_rotate: Option<vk::PhysicalDeviceShaderSubgroupRotateFeaturesKHR<'static>>,
+    pub shader_float_controls2: Option<vk::PhysicalDeviceShaderFloatControls2FeaturesKHR<'static>>,
+    pub shader_expect_assume: Option<vk::PhysicalDeviceShaderExpectAssumeFeaturesKHR<'static>>,
+    pub global_priority_query: Option<vk::PhysicalDeviceGlobalPriorityQueryFeaturesKHR<'static>>,
+    pub host_image_copy: Option<vk::PhysicalDeviceHostImageCopyFeaturesEXT<'static>>,
+    pub pipeline_protected_access:
+        Option<vk::PhysicalDevicePipelineProtectedAccessFeaturesEXT<'static>>,
+    pub pipeline_robustness: Option<vk::PhysicalDevicePipelineRobustnessFeaturesEXT<'static>>,
+}
+
+/// `vk::PhysicalDeviceFeatures2` chaining copies of all blocks of a `Features` (see `Features::chain`).
+///
+/// Copies are boxed : moving the chain does not invalidate `p_next` pointers.
+#[derive(Debug)]
+pub struct FeaturesChain {
+    blocks: Box<Features>,
+    features2: vk::PhysicalDeviceFeatures2<'static>,
+}
+
+/// Required but unsupported features, per block (see `Features::missing`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MissingFeatures {
+    pub blocks: Vec<(&'static str, Vec<&'static str>)>, // (block name, field names), only blocks missing features
+}
+
+/////////////////////////////////////////////////////////////////////////
+// Macro
+/////////////////////////////////////////////////////////////////////////
+
+/// Field lists are written once, functions of all blocks are generated from them.
+macro_rules! features {
+    (
+        core {$(
+            $block:ident ($name:literal): $ty:ty => $contains:ident, $missing:ident {
+                $($field:ident,)*
+            }
+        )*}
+        extensions {$(
+            $ext_block:ident ($ext_name:expr): $ext_ty:ty => $ext_contains:ident, $ext_missing:ident {
+                $($ext_field:ident,)*
+            }
+        )*}
+    ) => {
+        /// Contains
+        impl Features {
+            pub fn contains(&self, other: &Self) -> bool {
+                $(self.$contains(&other.$block))&&*
+                    $(&& other.$ext_block.as_ref().is_none_or(|block| self.$ext_contains(block)))*
+            }
+
+            $(
+                pub fn $contains(&self, $block: &$ty) -> bool {
+                    $($block.$field <= self.$block.$field)&&*
+                }
+            )*
+
+            $(
+                pub fn $ext_contains(&self, $ext_block: &$ext_ty) -> bool {
+                    let supported = self.$ext_block.unwrap_or_default();
+                    $($ext_block.$ext_field <= supported.$ext_field)&&*
+                }
+            )*
+        }
+
+        /// Missing
+        impl Features {
+            /// Features required by `required` but not supported by `self`.
+            pub fn missing(&self, required: &Self) -> MissingFeatures {
+                let mut missing = MissingFeatures::default();
+                $(
+                    let field_names = self.$missing(&required.$block);
+                    if !field_names.is_empty() {
+                        missing.blocks.push(($name, field_names));
+                    }
+                )*
+                $(
+                    if let Some(block) = &required.$ext_block {
+                        let field_names = self.$ext_missing(block);
+                        if !field_names.is_empty() {
+                            // UNWRAP: extension names are ASCII
+                            missing.blocks.push(($ext_name.to_str().unwrap(), field_names));
+                        }
+                    }
+                )*
+                missing
+            }
+
+            $(
+                pub fn $missing(&self, $block: &$ty) -> Vec<&'static str> {
+                    let mut field_names = Vec::new();
+                    $(
+                        if $block.$field > self.$block.$field {
+                            field_names.push(stringify!($field));
+                        }
+                    )*
+                    field_names
+                }
+            )*
+
+            $(
+                pub fn $ext_missing(&self, $ext_block: &$ext_ty) -> Vec<&'static str> {
+                    let supported = self.$ext_block.unwrap_or_default();
+                    let mut field_names = Vec::new();
+                    $(
+                        if $ext_block.$ext_field > supported.$ext_field {
+                            field_names.push(stringify!($ext_field));
+                        }
+                    )*
+                    field_names
+                }
+            )*
+        }
+
+        /// Set operations
+        impl Features {
+            /// Features enabled in `self` or `other` (extension blocks present in either).
+            pub fn union(&self, other: &Self) -> Self {
+                let mut union = Self::default();
+                $($(union.$block.$field = self.$block.$field.max(other.$block.$field);)*)*
+                $(
+                    if self.$ext_block.is_some() || other.$ext_block.is_some() {
+                        let (a, b) = (self.$ext_block.unwrap_or_default(), other.$ext_block.unwrap_or_default());
+                        let mut block: $ext_ty = Default::default();
+                        $(block.$ext_field = a.$ext_field.max(b.$ext_field);)*
+                        union.$ext_block = Some(block);
+                    }
+                )*
+                union
+            }
+
+            /// Features enabled in both `self` and `other` (extension blocks present in both).
+            pub fn intersection(&self, other: &Self) -> Self {
+                let mut intersection = Self::default();
+                $($(intersection.$block.$field = self.$block.$field.min(other.$block.$field);)*)*
+                $(
+                    if let (Some(a), Some(b)) = (self.$ext_block, other.$ext_block) {
+                        let mut block: $ext_ty = Default::default();
+                        $(block.$ext_field = a.$ext_field.min(b.$ext_field);)*
+                        intersection.$ext_block = Some(block);
+                    }
+                )*
+                intersection
+            }
+        }
+
+        /// Names
+        impl Features {
+            /// Enable `field_name` of `block_name` ("Vulkan 1.2", "VK_EXT_mesh_shader", ..), extension blocks made present.
+            ///
+            /// Fail (false) if there is no such feature.
+            pub fn enable_by_name(&mut self, block_name: &str, field_name: &str) -> bool {
+                $(
+                    if block_name == $name {
+                        $(
+                            if field_name == stringify!($field) {
+                                self.$block.$field = vk::TRUE;
+                                return true;
+                            }
+                        )*
+                        return false;
+                    }
+                )*
+                $(
+                    if $ext_name.to_str() == Ok(block_name) {
+                        $(
+                            if field_name == stringify!($ext_field) {
+                                self.$ext_block.get_or_insert_with(Default::default).$ext_field = vk::TRUE;
+                                return true;
+                            }
+                        )*
+                        return false;
+                    }
+                )*
+                false
+            }
+        }
+
+        /// Extensions
+        impl Features {
+            /// Extensions of present blocks (to enable at device creation).
+            pub fn extension_names(&self) -> Vec<&'static CStr> {
+                let mut extension_names = Vec::new();
+                $(
+                    if self.$ext_block.is_some() {
+                        extension_names.push($ext_name);
+                    }
+                )*
+                extension_names
+            }
+
+            /// Blocks of `supported_extension_names` made present (all features disabled), others removed.
+            fn reset_extension_blocks(&mut self, supported_extension_names: &[&CStr]) {
+                $(
+                    self.$ext_block = supported_extension_names
+                        .contains(&$ext_name)
+                        .then(Default::default);
+                )*
+            }
+
+            /// Insert present blocks in front of the chain starting at `head` (see `Features::chain`).
+            fn push_extension_blocks(&mut self, head: &mut *mut c_void) {
+                $(
+                    if let Some(block) = &mut self.$ext_block {
+                        push_front(head, block);
+                    }
+                )*
+            }
+
+            /// Reset `p_next` pointers of present blocks (see `Features::unchain`).
+            fn unchain_extension_blocks(&mut self) {
+                $(
+                    if let Some(block) = &mut self.$ext_block {
+                        block.p_next = ptr::null_mut();
+                    }
+                )*
+            }
+        }
+    };
 }
 
 /////////////////////////////////////////////////////////////////////////
 // Implementations
 /////////////////////////////////////////////////////////////////////////
 
-/// Contains
+features! {
+    core {
+        features_10 ("Vulkan 1.0"): vk::PhysicalDeviceFeatures => contains_features_10, missing_features_10 {
+            robust_buffer_access,
+            full_draw_index_uint32,
+            image_cube_array,
+            independent_blend,
+            geometry_shader,
+            tessellation_shader,
+            sample_rate_shading,
+            inherited_queries,
+            variable_multisample_rate,
+            sparse_residency_aliased,
+            sparse_residency16_samples,
+            sparse_residency8_samples,
+            sparse_residency4_samples,
+            sparse_residency2_samples,
+            sparse_residency_image3_d,
+            sparse_residency_image2_d,
+            sparse_residency_buffer,
+            sparse_binding,
+            shader_resource_min_lod,
+            shader_resource_residency,
+            shader_int16,
+            shader_int64,
+            shader_float64,
+            shader_cull_distance,
+            shader_clip_distance,
+            shader_storage_image_array_dynamic_indexing,
+            shader_storage_buffer_array_dynamic_indexing,
+            shader_sampled_image_array_dynamic_indexing,
+            shader_uniform_buffer_array_dynamic_indexing,
+            shader_storage_image_write_without_format,
+            shader_storage_image_read_without_format,
+            shader_storage_image_extended_formats,
+            shader_storage_image_multisample,
+            shader_image_gather_extended,
+            shader_tessellation_and_geometry_point_size,
+            fragment_stores_and_atomics,
+            vertex_pipeline_stores_and_atomics,
+            pipeline_statistics_query,
+            occlusion_query_precise,
+            texture_compression_bc,
+            texture_compression_astc_ldr,
+            texture_compression_etc2,
+            sampler_anisotropy,
+            multi_viewport,
+            alpha_to_one,
+            large_points,
+            wide_lines,
+            depth_bounds,
+            fill_mode_non_solid,
+            depth_bias_clamp,
+            depth_clamp,
+            draw_indirect_first_instance,
+            multi_draw_indirect,
+            logic_op,
+            dual_src_blend,
+        }
+
+        features_11 ("Vulkan 1.1"): vk::PhysicalDeviceVulkan11Features => contains_features_11, missing_features_11 {
+            storage_buffer16_bit_access,
+            uniform_and_storage_buffer16_bit_access,
+            storage_push_constant16,
+            storage_input_output16,
+            multiview,
+            multiview_geometry_shader,
+            multiview_tessellation_shader,
+            variable_pointers_storage_buffer,
+            variable_pointers,
+            protected_memory,
+            sampler_ycbcr_conversion,
+            shader_draw_parameters,
+        }
+
+        features_12 ("Vulkan 1.2"): vk::PhysicalDeviceVulkan12Features => contains_features_12, missing_features_12 {
+            subgroup_broadcast_dynamic_id,
+            shader_output_layer,
+            shader_output_viewport_index,
+            vulkan_memory_model_availability_visibility_chains,
+            vulkan_memory_model_device_scope,
+            vulkan_memory_model,
+            buffer_device_address_multi_device,
+            buffer_device_address_capture_replay,
+            buffer_device_address,
+            timeline_semaphore,
+            host_query_reset,
+            separate_depth_stencil_layouts,
+            shader_subgroup_extended_types,
+            uniform_buffer_standard_layout,
+            imageless_framebuffer,
+            scalar_block_layout,
+            sampler_filter_minmax,
+            runtime_descriptor_array,
+            descriptor_binding_variable_descriptor_count,
+            descriptor_binding_partially_bound,
+            descriptor_binding_update_unused_while_pending,
+            descriptor_binding_storage_texel_buffer_update_after_bind,
+            descriptor_binding_uniform_texel_buffer_update_after_bind,
+            descriptor_binding_storage_buffer_update_after_bind,
+            descriptor_binding_storage_image_update_after_bind,
+            descriptor_binding_sampled_image_update_after_bind,
+            descriptor_binding_uniform_buffer_update_after_bind,
+            shader_storage_texel_buffer_array_non_uniform_indexing,
+            shader_uniform_texel_buffer_array_non_uniform_indexing,
+            shader_input_attachment_array_non_uniform_indexing,
+            shader_storage_image_array_non_uniform_indexing,
+            shader_storage_buffer_array_non_uniform_indexing,
+            shader_sampled_image_array_non_uniform_indexing,
+            shader_uniform_buffer_array_non_uniform_indexing,
+            shader_storage_texel_buffer_array_dynamic_indexing,
+            shader_uniform_texel_buffer_array_dynamic_indexing,
+            shader_input_attachment_array_dynamic_indexing,
+            descriptor_indexing,
+            shader_int8,
+            shader_float16,
+            shader_shared_int64_atomics,
+            shader_buffer_int64_atomics,
+            storage_push_constant8,
+            uniform_and_storage_buffer8_bit_access,
+            storage_buffer8_bit_access,
+            draw_indirect_count,
+            sampler_mirror_clamp_to_edge,
+        }
+
+        features_13 ("Vulkan 1.3"): vk::PhysicalDeviceVulkan13Features => contains_features_13, missing_features_13 {
+            robust_image_access,
+            inline_uniform_block,
+            descriptor_binding_inline_uniform_block_update_after_bind,
+            pipeline_creation_cache_control,
+            private_data,
+            shader_demote_to_helper_invocation,
+            shader_terminate_invocation,
+            subgroup_size_control,
+            compute_full_subgroups,
+            synchronization2,
+            texture_compression_astc_hdr,
+            shader_zero_initialize_workgroup_memory,
+            dynamic_rendering,
+            shader_integer_dot_product,
+            maintenance4,
+        }
+    }
+    extensions {
+        mesh_shader (vk::EXT_MESH_SHADER_NAME): vk::PhysicalDeviceMeshShaderFeaturesEXT => contains_mesh_shader, missing_mesh_shader {
+            task_shader,
+            mesh_shader,
+            multiview_mesh_shader,
+            primitive_fragment_shading_rate_mesh_shader,
+            mesh_shader_queries,
+        }
+        ray_query (vk::KHR_RAY_QUERY_NAME): vk::PhysicalDeviceRayQueryFeaturesKHR => contains_ray_query, missing_ray_query {
+            ray_query,
+        }
+        acceleration_structure (vk::KHR_ACCELERATION_STRUCTURE_NAME): vk::PhysicalDeviceAccelerationStructureFeaturesKHR => contains_acceleration_structure, missing_acceleration_structure {
+            acceleration_structure,
+            acceleration_structure_capture_replay,
+            acceleration_structure_indirect_build,
+            acceleration_structure_host_commands,
+            descriptor_binding_acceleration_structure_update_after_bind,
+        }
+        descriptor_buffer (vk::EXT_DESCRIPTOR_BUFFER_NAME): vk::PhysicalDeviceDescriptorBufferFeaturesEXT => contains_descriptor_buffer, missing_descriptor_buffer {
+            descriptor_buffer,
+            descriptor_buffer_capture_replay,
+            descriptor_buffer_image_layout_ignored,
+            descriptor_buffer_push_descriptors,
+        }
+        robustness2 (vk::EXT_ROBUSTNESS2_NAME): vk::PhysicalDeviceRobustness2FeaturesEXT => contains_robustness2, missing_robustness2 {
+            robust_buffer_access2,
+            robust_image_access2,
+            null_descriptor,
+        }
+        extended_dynamic_state3 (vk::EXT_EXTENDED_DYNAMIC_STATE3_NAME): vk::PhysicalDeviceExtendedDynamicState3FeaturesEXT => contains_extended_dynamic_state3, missing_extended_dynamic_state3 {
+            extended_dynamic_state3_tessellation_domain_origin,
+            extended_dynamic_state3_depth_clamp_enable,
+            extended_dynamic_state3_polygon_mode,
+            extended_dynamic_state3_rasterization_samples,
+            extended_dynamic_state3_sample_mask,
+            extended_dynamic_state3_alpha_to_coverage_enable,
+            extended_dynamic_state3_alpha_to_one_enable,
+            extended_dynamic_state3_logic_op_enable,
+            extended_dynamic_state3_color_blend_enable,
+            extended_dynamic_state3_color_blend_equation,
+            extended_dynamic_state3_color_write_mask,
+            extended_dynamic_state3_rasterization_stream,
+            extended_dynamic_state3_conservative_rasterization_mode,
+            extended_dynamic_state3_extra_primitive_overestimation_size,
+            extended_dynamic_state3_depth_clip_enable,
+            extended_dynamic_state3_sample_locations_enable,
+            extended_dynamic_state3_color_blend_advanced,
+            extended_dynamic_state3_provoking_vertex_mode,
+            extended_dynamic_state3_line_rasterization_mode,
+            extended_dynamic_state3_line_stipple_enable,
+            extended_dynamic_state3_depth_clip_negative_one_to_one,
+            extended_dynamic_state3_viewport_w_scaling_enable,
+            extended_dynamic_state3_viewport_swizzle,
+            extended_dynamic_state3_coverage_to_color_enable,
+            extended_dynamic_state3_coverage_to_color_location,
+            extended_dynamic_state3_coverage_modulation_mode,
+            extended_dynamic_state3_coverage_modulation_table_enable,
+            extended_dynamic_state3_coverage_modulation_table,
+            extended_dynamic_state3_coverage_reduction_mode,
+            extended_dynamic_state3_representative_fragment_test_enable,
+            extended_dynamic_state3_shading_rate_image_enable,
+        }
+
+        // promoted to Vulkan 1.4
+        maintenance5 (vk::KHR_MAINTENANCE5_NAME): vk::PhysicalDeviceMaintenance5FeaturesKHR => contains_maintenance5, missing_maintenance5 {
+            maintenance5,
+        }
+        maintenance6 (vk::KHR_MAINTENANCE6_NAME): vk::PhysicalDeviceMaintenance6FeaturesKHR => contains_maintenance6, missing_maintenance6 {
+            maintenance6,
+        }
+        dynamic_rendering_local_read (vk::KHR_DYNAMIC_RENDERING_LOCAL_READ_NAME): vk::PhysicalDeviceDynamicRenderingLocalReadFeaturesKHR => contains_dynamic_rendering_local_read, missing_dynamic_rendering_local_read {
+            dynamic_rendering_local_read,
+        }
+        index_type_uint8 (vk::KHR_INDEX_TYPE_UINT8_NAME): vk::PhysicalDeviceIndexTypeUint8FeaturesKHR => contains_index_type_uint8, missing_index_type_uint8 {
+            index_type_uint8,
+        }
+        line_rasterization (vk::KHR_LINE_RASTERIZATION_NAME): vk::PhysicalDeviceLineRasterizationFeaturesKHR => contains_line_rasterization, missing_line_rasterization {
+            rectangular_lines,
+            bresenham_lines,
+            smooth_lines,
+            stippled_rectangular_lines,
+            stippled_bresenham_lines,
+            stippled_smooth_lines,
+        }
+        vertex_attribute_divisor (vk::KHR_VERTEX_ATTRIBUTE_DIVISOR_NAME): vk::PhysicalDeviceVertexAttributeDivisorFeaturesKHR => contains_vertex_attribute_divisor, missing_vertex_attribute_divisor {
+            vertex_attribute_instance_rate_divisor,
+            vertex_attribute_instance_rate_zero_divisor,
+        }
+        shader_subgroup_rotate (vk::KHR_SHADER_SUBGROUP_ROTATE_NAME): vk::PhysicalDeviceShaderSubgroupRotateFeaturesKHR => contains_shader_subgroup_rotate, missing_shader_subgroup_rotate {
+            shader_subgroup_rotate,
+            shader_subgroup_rotate_clustered,
+        }
+        shader_float_controls2 (vk::KHR_SHADER_FLOAT_CONTROLS2_NAME): vk::PhysicalDeviceShaderFloatControls2FeaturesKHR => contains_shader_float_controls2, missing_shader_float_controls2 {
+            shader_float_controls2,
+        }
+        shader_expect_assume (vk::KHR_SHADER_EXPECT_ASSUME_NAME): vk::PhysicalDeviceShaderExpectAssumeFeaturesKHR => contains_shader_expect_assume, missing_shader_expect_assume {
+            shader_expect_assume,
+        }
+        global_priority_query (vk::KHR_GLOBAL_PRIORITY_NAME): vk::PhysicalDeviceGlobalPriorityQueryFeaturesKHR => contains_global_priority_query, missing_global_priority_query {
+            global_priority_query,
+        }
+        host_image_copy (vk::EXT_HOST_IMAGE_COPY_NAME): vk::PhysicalDeviceHostImageCopyFeaturesEXT => contains_host_image_copy, missing_host_image_copy {
+            host_image_copy,
+        }
+        pipeline_protected_access (vk::EXT_PIPELINE_PROTECTED_ACCESS_NAME): vk::PhysicalDevicePipelineProtectedAccessFeaturesEXT => contains_pipeline_protected_access, missing_pipeline_protected_access {
+            pipeline_protected_access,
+        }
+        pipeline_robustness (vk::EXT_PIPELINE_ROBUSTNESS_NAME): vk::PhysicalDevicePipelineRobustnessFeaturesEXT => contains_pipeline_robustness, missing_pipeline_robustness {
+            pipeline_robustness,
+        }
+    }
+}
+
+/// Query & Chain
 impl Features {
-    pub fn contains(&self, other: &Self) -> bool {
-        self.contains_features_10(&other.features_10)
-            && self.contains_features_11(&other.features_11)
-            && self.contains_features_12(&other.features_12)
-            && self.contains_features_13(&other.features_13)
+    /// Features supported by `physical_device`, all blocks filled in one call.
+    ///
+    /// Extension blocks are present only if the device supports their extension.
+    ///
+    /// # Safety
+    ///
+    /// `physical_device` must come from `instance`, min(instance api version, device api version) at least Vulkan 1.3 (all core blocks are chained).
+    pub unsafe fn query(
+        instance: &ash::Instance,
+        physical_device: vk::PhysicalDevice,
+    ) -> ash::prelude::VkResult<Self> {
+        let supported_extensions =
+            unsafe { instance.enumerate_device_extension_properties(physical_device) }?;
+        let supported_extension_names: Vec<&CStr> = supported_extensions
+            .iter()
+            .filter_map(|extension| extension.extension_name_as_c_str().ok())
+            .collect();
+
+        let mut features = Self::default();
+        features.reset_extension_blocks(&supported_extension_names);
+        let mut chain = features.chain();
+        unsafe { instance.get_physical_device_features2(physical_device, chain.features2()) };
+        Ok(chain.into_features())
+    }
+
+    /// `FeaturesChain` with copies of all blocks pushed (present ones for extensions), `self` is left untouched.
+    pub fn chain(&self) -> FeaturesChain {
+        let mut blocks = Box::new(self.clone());
+        let mut head = ptr::null_mut();
+        // core
+        push_front(&mut head, &mut blocks.features_11);
+        push_front(&mut head, &mut blocks.features_12);
+        push_front(&mut head, &mut blocks.features_13);
+        // extensions
+        blocks.push_extension_blocks(&mut head);
+
+        let mut features2 = vk::PhysicalDeviceFeatures2::default().features(blocks.features_10);
+        features2.p_next = head;
+        FeaturesChain { blocks, features2 }
+    }
+
+    /// Reset `p_next` pointers (chained copies only, see `FeaturesChain::into_features`).
+    fn unchain(&mut self) {
+        self.features_11.p_next = ptr::null_mut();
+        self.features_12.p_next = ptr::null_mut();
+        self.features_13.p_next = ptr::null_mut();
+        self.unchain_extension_blocks();
     }
+}
 
-    pub fn contains_features_10(&self, features_10: &vk::PhysicalDeviceFeatures) -> bool {
-        macro_rules! supported_or_not_required {
-            ($field:ident) => {
-                features_10.$field <= self.features_10.$field
-            };
-        }
-
-        supported_or_not_required!(robust_buffer_access)
-            && supported_or_not_required!(full_draw_index_uint32)
-            && supported_or_not_required!(image_cube_array)
-            && supported_or_not_required!(independent_blend)
-            && supported_or_not_required!(geometry_shader)
-            && supported_or_not_required!(tessellation_shader)
-            && supported_or_not_required!(sample_rate_shading)
-            && supported_or_not_required!(inherited_queries)
-            && supported_or_not_required!(variable_multisample_rate)
-            && supported_or_not_required!(sparse_residency_aliased)
-            && supported_or_not_required!(sparse_residency16_samples)
-            && supported_or_not_required!(sparse_residency8_samples)
-            && supported_or_not_required!(sparse_residency4_samples)
-            && supported_or_not_required!(sparse_residency2_samples)
-            && supported_or_not_required!(sparse_residency_image3_d)
-            && supported_or_not_required!(sparse_residency_image2_d)
-            && supported_or_not_required!(sparse_residency_buffer)
-            && supported_or_not_required!(sparse_binding)
-            && supported_or_not_required!(shader_resource_min_lod)
-            && supported_or_not_required!(shader_resource_residency)
-            && supported_or_not_required!(shader_int16)
-            && supported_or_not_required!(shader_int64)
-            && supported_or_not_required!(shader_float64)
-            && supported_or_not_required!(shader_cull_distance)
-            && supported_or_not_required!(shader_clip_distance)
-            && supported_or_not_required!(shader_storage_image_array_dynamic_indexing)
-            && supported_or_not_required!(shader_storage_buffer_array_dynamic_indexing)
-            && supported_or_not_required!(shader_sampled_image_array_dynamic_indexing)
-            && supported_or_not_required!(shader_uniform_buffer_array_dynamic_indexing)
-            && supported_or_not_required!(shader_storage_image_write_without_format)
-            && supported_or_not_required!(shader_storage_image_read_without_format)
-            && supported_or_not_required!(shader_storage_image_extended_formats)
-            && supported_or_not_required!(shader_storage_image_multisample)
-            && supported_or_not_required!(shader_image_gather_extended)
-            && supported_or_not_required!(shader_tessellation_and_geometry_point_size)
-            && supported_or_not_required!(fragment_stores_and_atomics)
-            && supported_or_not_required!(vertex_pipeline_stores_and_atomics)
-            && supported_or_not_required!(pipeline_statistics_query)
-            && supported_or_not_required!(occlusion_query_precise)
-            && supported_or_not_required!(texture_compression_bc)
-            && supported_or_not_required!(texture_compression_astc_ldr)
-            && supported_or_not_required!(texture_compression_etc2)
-            && supported_or_not_required!(sampler_anisotropy)
-            && supported_or_not_required!(multi_viewport)
-            && supported_or_not_required!(alpha_to_one)
-            && supported_or_not_required!(large_points)
-            && supported_or_not_required!(wide_lines)
-            && supported_or_not_required!(depth_bounds)
-            && supported_or_not_required!(fill_mode_non_solid)
-            && supported_or_not_required!(depth_bias_clamp)
-            && supported_or_not_required!(depth_clamp)
-            && supported_or_not_required!(draw_indirect_first_instance)
-            && supported_or_not_required!(multi_draw_indirect)
-            && supported_or_not_required!(logic_op)
-            && supported_or_not_required!(dual_src_blend)
+/// Chain
+impl FeaturesChain {
+    /// Root of the chain, to pass to a query or to push into a `vk::DeviceCreateInfo`.
+    pub fn features2(&mut self) -> &mut vk::PhysicalDeviceFeatures2<'static> {
+        &mut self.features2
     }
 
-    pub fn contains_features_11(&self, features_11: &vk::PhysicalDeviceVulkan11Features) -> bool {
-        macro_rules! supported_or_not_required {
-            ($field:ident) => {
-                features_11.$field <= self.features_11.$field
-            };
-        }
-
-        supported_or_not_required!(storage_buffer16_bit_access)
-            && supported_or_not_required!(uniform_and_storage_buffer16_bit_access)
-            && supported_or_not_required!(storage_push_constant16)
-            && supported_or_not_required!(storage_input_output16)
-            && supported_or_not_required!(multiview)
-            && supported_or_not_required!(multiview_geometry_shader)
-            && supported_or_not_required!(multiview_tessellation_shader)
-            && supported_or_not_required!(variable_pointers_storage_buffer)
-            && supported_or_not_required!(variable_pointers)
-            && supported_or_not_required!(protected_memory)
-            && supported_or_not_required!(sampler_ycbcr_conversion)
-            && supported_or_not_required!(shader_draw_parameters)
+    /// Features of the chain (read back after a query), `p_next` pointers reset.
+    pub fn into_features(self) -> Features {
+        let Self {
+            mut blocks,
+            features2,
+        } = self;
+        blocks.features_10 = features2.features;
+        blocks.unchain();
+        *blocks
     }
+}
 
-    pub fn contains_features_12(&self, features_12: &vk::PhysicalDeviceVulkan12Features) -> bool {
-        macro_rules! supported_or_not_required {
-            ($field:ident) => {
-                features_12.$field <= self.features_12.$field
-            };
-        }
-
-        supported_or_not_required!(subgroup_broadcast_dynamic_id)
-            && supported_or_not_required!(shader_output_layer)
-            && supported_or_not_required!(shader_output_viewport_index)
-            && supported_or_not_required!(vulkan_memory_model_availability_visibility_chains)
-            && supported_or_not_required!(vulkan_memory_model_device_scope)
-            && supported_or_not_required!(vulkan_memory_model)
-            && supported_or_not_required!(buffer_device_address_multi_device)
-            && supported_or_not_required!(buffer_device_address_capture_replay)
-            && supported_or_not_required!(buffer_device_address)
-            && supported_or_not_required!(timeline_semaphore)
-            && supported_or_not_required!(host_query_reset)
-            && supported_or_not_required!(separate_depth_stencil_layouts)
-            && supported_or_not_required!(shader_subgroup_extended_types)
-            && supported_or_not_required!(uniform_buffer_standard_layout)
-            && supported_or_not_required!(imageless_framebuffer)
-            && supported_or_not_required!(scalar_block_layout)
-            && supported_or_not_required!(sampler_filter_minmax)
-            && supported_or_not_required!(runtime_descriptor_array)
-            && supported_or_not_required!(descriptor_binding_variable_descriptor_count)
-            && supported_or_not_required!(descriptor_binding_partially_bound)
-            && supported_or_not_required!(descriptor_binding_update_unused_while_pending)
-            && supported_or_not_required!(descriptor_binding_storage_texel_buffer_update_after_bind)
-            && supported_or_not_required!(descriptor_binding_uniform_texel_buffer_update_after_bind)
-            && supported_or_not_required!(descriptor_binding_storage_buffer_update_after_bind)
-            && supported_or_not_required!(descriptor_binding_storage_image_update_after_bind)
-            && supported_or_not_required!(descriptor_binding_sampled_image_update_after_bind)
-            && supported_or_not_required!(descriptor_binding_uniform_buffer_update_after_bind)
-            && supported_or_not_required!(shader_storage_texel_buffer_array_non_uniform_indexing)
-            && supported_or_not_required!(shader_uniform_texel_buffer_array_non_uniform_indexing)
-            && supported_or_not_required!(shader_input_attachment_array_non_uniform_indexing)
-            && supported_or_not_required!(shader_storage_image_array_non_uniform_indexing)
-            && supported_or_not_required!(shader_storage_buffer_array_non_uniform_indexing)
-            && supported_or_not_required!(shader_sampled_image_array_non_uniform_indexing)
-            && supported_or_not_required!(shader_uniform_buffer_array_non_uniform_indexing)
-            && supported_or_not_required!(shader_storage_texel_buffer_array_dynamic_indexing)
-            && supported_or_not_required!(shader_uniform_texel_buffer_array_dynamic_indexing)
-            && supported_or_not_required!(shader_input_attachment_array_dynamic_indexing)
-            && supported_or_not_required!(descriptor_indexing)
-            && supported_or_not_required!(shader_int8)
-            && supported_or_not_required!(shader_float16)
-            && supported_or_not_required!(shader_shared_int64_atomics)
-            && supported_or_not_required!(shader_buffer_int64_atomics)
-            && supported_or_not_required!(storage_push_constant8)
-            && supported_or_not_required!(uniform_and_storage_buffer8_bit_access)
-            && supported_or_not_required!(storage_buffer8_bit_access)
-            && supported_or_not_required!(draw_indirect_count)
-            && supported_or_not_required!(sampler_mirror_clamp_to_edge)
+/// Insert `block` in front of the chain starting at `head` (as `push_next`, without borrowing `block`).
+fn push_front<T: vk::ExtendsPhysicalDeviceFeatures2>(head: &mut *mut c_void, block: &mut T) {
+    let block = (block as *mut T).cast::<vk::BaseOutStructure>();
+    // SAFETY: extension structures start with `s_type` & `p_next` (`vk::BaseOutStructure` layout)
+    unsafe { (*block).p_next = (*head).cast() };
+    *head = block.cast();
+}
+
+/// Names
+impl Features {
+    /// (block name, field names) of enabled features, only blocks with enabled features.
+    pub fn enabled_names(&self) -> Vec<(&'static str, Vec<&'static str>)> {
+        Features::default().missing(self).blocks
     }
+}
+
+/// Utils
+impl MissingFeatures {
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
+
+/// Display
+impl Display for MissingFeatures {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let blocks: Vec<String> = self
+            .blocks
+            .iter()
+            .map(|(name, field_names)| format!("{name} ({})", field_names.join(", ")))
+            .collect();
+        write!(f, "{}", blocks.join(", "))
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn missing_union_intersection() {
+        let supported = Features {
+            features_10: vk::PhysicalDeviceFeatures::default().shader_int64(true),
+            features_12: vk::PhysicalDeviceVulkan12Features::default().shader_int8(true),
+            ..Default::default()
+        };
+        let required = Features {
+            features_10: vk::PhysicalDeviceFeatures::default().shader_int64(true),
+            features_12: vk::PhysicalDeviceVulkan12Features::default().descriptor_indexing(true),
+            features_13: vk::PhysicalDeviceVulkan13Features::default()
+                .synchronization2(true)
+                .dynamic_rendering(true),
+            ..Default::default()
+        };
+
+        // missing
+        assert!(!supported.contains(&required));
+        let missing = supported.missing(&required);
+        assert_eq!(
+            missing.blocks,
+            [
+                ("Vulkan 1.2", vec!["descriptor_indexing"]),
+                ("Vulkan 1.3", vec!["synchronization2", "dynamic_rendering"]),
+            ]
+        );
+        assert_eq!(
+            missing.to_string(),
+            "Vulkan 1.2 (descriptor_indexing), Vulkan 1.3 (synchronization2, dynamic_rendering)"
+        );
+
+        // union & intersection
+        let union = supported.union(&required);
+        assert!(union.contains(&supported) && union.contains(&required));
+        assert!(union.missing(&required).is_empty());
+        let intersection = supported.intersection(&required);
+        assert!(supported.contains(&intersection) && required.contains(&intersection));
+        assert_eq!(intersection.features_10.shader_int64, vk::TRUE);
+        assert_eq!(intersection.features_12.shader_int8, vk::FALSE);
+    }
+
+    #[test]
+    fn extensions() {
+        let supported = Features {
+            robustness2: Some(
+                vk::PhysicalDeviceRobustness2FeaturesEXT::default().null_descriptor(true),
+            ),
+            ..Default::default()
+        };
+        let required = Features {
+            mesh_shader: Some(vk::PhysicalDeviceMeshShaderFeaturesEXT::default().mesh_shader(true)),
+            robustness2: Some(
+                vk::PhysicalDeviceRobustness2FeaturesEXT::default().null_descriptor(true),
+            ),
+            ..Default::default()
+        };
+
+        // absent blocks : nothing supported, nothing required
+        assert!(supported.contains(&Features::default()));
+        assert!(!supported.contains(&required));
+        let missing = supported.missing(&required);
+        assert_eq!(
+            missing.blocks,
+            [("VK_EXT_mesh_shader", vec!["mesh_shader"])]
+        );
+
+        // union & intersection
+        let union = supported.union(&required);
+        assert!(union.contains(&required));
+        assert_eq!(
+            union.extension_names(),
+            [vk::EXT_MESH_SHADER_NAME, vk::EXT_ROBUSTNESS2_NAME]
+        );
+        let intersection = supported.intersection(&required);
+        assert!(intersection.mesh_shader.is_none());
+        assert_eq!(intersection.robustness2.unwrap().null_descriptor, vk::TRUE);
+    }
+
+    #[test]
+    fn names() {
+        let mut features = Features::default();
+        assert!(features.enable_by_name("Vulkan 1.3", "dynamic_rendering"));
+        assert!(features.enable_by_name("VK_KHR_ray_query", "ray_query"));
+        assert!(features.enable_by_name("VK_KHR_maintenance5", "maintenance5"));
+        assert!(!features.enable_by_name("Vulkan 1.3", "ray_query"));
+        assert!(!features.enable_by_name("VK_EXT_mesh_shader", "dynamic_rendering"));
+        assert!(!features.enable_by_name("Vulkan 9.9", "dynamic_rendering"));
+        assert!(features.mesh_shader.is_none());
+        assert_eq!(
+            features.enabled_names(),
+            [
+                ("Vulkan 1.3", vec!["dynamic_rendering"]),
+                ("VK_KHR_ray_query", vec!["ray_query"]),
+                ("VK_KHR_maintenance5", vec!["maintenance5"]),
+            ]
+        );
+        assert_eq!(
+            features.extension_names(),
+            [vk::KHR_RAY_QUERY_NAME, vk::KHR_MAINTENANCE5_NAME]
+        );
+    }
+
+    #[test]
+    fn chain() {
+        let mut features = Features {
+            ray_query: Some(Default::default()),
+            line_rasterization: Some(Default::default()),
+            ..Default::default()
+        };
+        features.features_13.dynamic_rendering = vk::TRUE;
+        let chains: Vec<FeaturesChain> = (0..2).map(|_| features.chain()).collect();
+        for mut chain in chains {
+            // moved chains : line rasterization -> ray query -> 13 -> 12 -> 11 (pushed in front)
+            let mut count = 0;
+            let mut next = chain.features2().p_next as *const vk::BaseOutStructure;
+            while !next.is_null() {
+                count += 1;
+                next = unsafe { (*next).p_next };
+            }
+            assert_eq!(count, 5);
+
+            // copies read back, no pointers left
+            let copy = chain.into_features();
+            assert_eq!(copy.features_13.dynamic_rendering, vk::TRUE);
+            assert!(copy.features_13.p_next.is_null());
+            assert!(copy.ray_query.unwrap().p_next.is_null());
+            assert!(copy.line_rasterization.unwrap().p_next.is_null());
+        }
 
-    pub fn contains_features_13(&self, features_13: &vk::PhysicalDeviceVulkan13Features) -> bool {
-        macro_rules! supported_or_not_required {
-            ($field:ident) => {
-                features_13.$field <= self.features_13.$field
-            };
-        }
-
-        supported_or_not_required!(robust_image_access)
-            && supported_or_not_required!(inline_uniform_block)
-            && supported_or_not_required!(descriptor_binding_inline_uniform_block_update_after_bind)
-            && supported_or_not_required!(pipeline_creation_cache_control)
-            && supported_or_not_required!(private_data)
-            && supported_or_not_required!(shader_demote_to_helper_invocation)
-            && supported_or_not_required!(shader_terminate_invocation)
-            && supported_or_not_required!(subgroup_size_control)
-            && supported_or_not_required!(compute_full_subgroups)
-            && supported_or_not_required!(synchronization2)
-            && supported_or_not_required!(texture_compression_astc_hdr)
-            && supported_or_not_required!(shader_zero_initialize_workgroup_memory)
-            && supported_or_not_required!(dynamic_rendering)
-            && supported_or_not_required!(shader_integer_dot_product)
-            && supported_or_not_required!(maintenance4)
+        // original untouched
+        assert!(features.features_11.p_next.is_null());
+        assert!(features.ray_query.unwrap().p_next.is_null());
     }
 }
@@ -1,5 +1,9 @@
 mod api_version;
 mod features;
+mod profile;
 
 pub use api_version::ApiVersion;
-pub use features::Features;
+pub use features::{Features, FeaturesChain, MissingFeatures};
+pub use profile::{
+    FormatRequirement, Profile, ProfileError, ProfileEvaluation, QueueRequirement, Requirements,
+};
@@ -0,0 +1,599 @@
+use std::{collections::BTreeMap, ffi::CString, fmt::Display, path::Path};
+
+use ash::vk;
+use serde::{Deserialize, Serialize};
+
+use super::{ApiVersion, Features};
+
+type Result<T> = std::result::Result<T, ProfileError>;
+
+/// Raw values of core formats, then first raw value of each extension's format range (scanned by `parse_format`).
+const CORE_FORMAT_COUNT: i32 = 185;
+const EXTENSION_FORMAT_BASES: [i32; 7] = [
+    1000054000, // VK_IMG_format_pvrtc
+    1000066000, // VK_EXT_texture_compression_astc_hdr
+    1000156000, // VK_KHR_sampler_ycbcr_conversion
+    1000330000, // VK_EXT_ycbcr_2plane_444_formats
+    1000340000, // VK_EXT_4444_formats
+    1000464000, // VK_NV_optical_flow
+    1000470000, // VK_KHR_maintenance5
+];
+const EXTENSION_FORMAT_RANGE: i32 = 64;
+
+/////////////////////////////////////////////////////////////////////////
+// Structures
+/////////////////////////////////////////////////////////////////////////
+
+/// Requirements a physical device must meet, and optional ones enabled if supported, loadable from TOML.
+///
+/// ```toml
+/// [required]
+/// api_version = "1.3"
+/// extensions = ["VK_KHR_swapchain"]
+/// formats = [{ format = "D32_SFLOAT", optimal_tiling = ["DEPTH_STENCIL_ATTACHMENT"] }]
+/// queues = [{ flags = ["GRAPHICS", "COMPUTE"], surface = true }]
+///
+/// [required.features] # block name => field names (see `Features::enable_by_name`)
+/// "Vulkan 1.0" = ["geometry_shader", "multi_draw_indirect"]
+/// "Vulkan 1.3" = ["dynamic_rendering", "synchronization2"]
+///
+/// [optional.features]
+/// "VK_EXT_mesh_shader" = ["mesh_shader", "task_shader"]
+/// ```
+///
+/// Names are those of ash without prefix : `vk::Format::D32_SFLOAT` => "D32_SFLOAT".
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub required: Requirements,
+    pub optional: Requirements, // extensions & features enabled if supported, others only reported
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Requirements {
+    pub api_version: Option<ApiVersion>,
+    pub extensions: Vec<String>,
+    pub features: BTreeMap<String, Vec<String>>,
+    pub formats: Vec<FormatRequirement>,
+    pub queues: Vec<QueueRequirement>,
+}
+
+/// Format features (`vk::FormatFeatureFlags2` names) supported in optimal tiling.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormatRequirement {
+    pub format: String,
+    pub optimal_tiling: Vec<String>,
+}
+
+/// A queue family with all `flags` (`vk::QueueFlags` names), and presenting to the surface if `surface`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QueueRequirement {
+    pub flags: Vec<String>,
+    pub surface: bool,
+}
+
+/// Result of `Profile::evaluate` : pass/fail and what to enable.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProfileEvaluation {
+    pub failures: Vec<String>,          // unmet requirements, human readable
+    pub optional_failures: Vec<String>, // unmet optional requirements, human readable
+    pub extensions: Vec<String>,        // required + supported optional
+    pub features: BTreeMap<String, Vec<String>>, // required + supported optional
+}
+
+/// Failures of profile loading & evaluation.
+#[derive(Debug)]
+pub enum ProfileError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    /// No feature `field_name` in block `block_name`.
+    UnknownFeature {
+        block_name: String,
+        field_name: String,
+    },
+    /// No `kind` ("format", "format feature", "queue flag") named `name`.
+    UnknownName {
+        kind: &'static str,
+        name: String,
+    },
+    Vulkan(vk::Result),
+}
+
+/////////////////////////////////////////////////////////////////////////
+// Implementations
+/////////////////////////////////////////////////////////////////////////
+
+/// Load
+impl Profile {
+    /// Fail if :
+    /// - TOML is invalid or has unknown keys.
+    /// - A feature, format, format feature or queue flag name is unknown.
+    pub fn from_toml(toml_str: &str) -> Result<Self> {
+        let profile: Profile = toml::from_str(toml_str).map_err(ProfileError::Toml)?;
+        profile.required.check_names()?;
+        profile.optional.check_names()?;
+        Ok(profile)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_toml(&std::fs::read_to_string(path).map_err(ProfileError::Io)?)
+    }
+}
+
+/// Evaluate
+impl Profile {
+    /// - The usable api version is min(`instance_api_version`, device api version) : `api_version` is checked against it.
+    /// - Features are checked only if the usable api version is at least Vulkan 1.3 (see `Features::query`).
+    /// - Surface support of queues is checked only if `surface` is given.
+    ///
+    /// Fail if a name is unknown or a query fails (unmet requirements are not errors).
+    ///
+    /// # Safety
+    ///
+    /// `physical_device` (and `surface`) must come from `instance`, created with `instance_api_version`.
+    pub unsafe fn evaluate(
+        &self,
+        instance: &ash::Instance,
+        instance_api_version: ApiVersion,
+        physical_device: vk::PhysicalDevice,
+        surface: Option<(&ash::khr::surface::Instance, vk::SurfaceKHR)>,
+    ) -> Result<ProfileEvaluation> {
+        let support =
+            unsafe { DeviceSupport::query(instance, instance_api_version, physical_device) }?;
+        let required = unsafe {
+            self.required
+                .evaluate(instance, physical_device, surface, &support)
+        }?;
+        let optional = unsafe {
+            self.optional
+                .evaluate(instance, physical_device, surface, &support)
+        }?;
+
+        // enabled : required + supported optional
+        let mut extensions = self.required.extensions.clone();
+        for extension_name in optional.supported_extensions {
+            if !extensions.contains(&extension_name) {
+                extensions.push(extension_name);
+            }
+        }
+        let mut features = self.required.features.clone();
+        for (block_name, field_name) in optional.supported_features {
+            let field_names = features.entry(block_name).or_default();
+            if !field_names.contains(&field_name) {
+                field_names.push(field_name);
+            }
+        }
+
+        Ok(ProfileEvaluation {
+            failures: required.failures,
+            optional_failures: optional.failures,
+            extensions,
+            features,
+        })
+    }
+}
+
+/// Requirements
+impl Requirements {
+    /// Fail if a feature name is unknown.
+    pub fn features(&self) -> Result<Features> {
+        features_from_names(&self.features)
+    }
+
+    fn check_names(&self) -> Result<()> {
+        self.features()?;
+        for format_requirement in &self.formats {
+            parse_format(&format_requirement.format)?;
+            parse_format_features(&format_requirement.optimal_tiling)?;
+        }
+        for queue_requirement in &self.queues {
+            parse_queue_flags(&queue_requirement.flags)?;
+        }
+        Ok(())
+    }
+
+    unsafe fn evaluate(
+        &self,
+        instance: &ash::Instance,
+        physical_device: vk::PhysicalDevice,
+        surface: Option<(&ash::khr::surface::Instance, vk::SurfaceKHR)>,
+        support: &DeviceSupport,
+    ) -> Result<RequirementsEvaluation> {
+        let mut evaluation = RequirementsEvaluation::default();
+
+        //-------------// Api version //-------------//
+
+        let api_version = support.api_version;
+        if let Some(required_api_version) = self.api_version
+            && api_version < required_api_version
+        {
+            evaluation.failures.push(format!(
+                "api version {api_version} (required {required_api_version})"
+            ));
+        }
+
+        //-------------// Extensions //-------------//
+
+        for extension_name in &self.extensions {
+            if support.extension_names.contains(extension_name) {
+                evaluation.supported_extensions.push(extension_name.clone());
+            } else {
+                evaluation
+                    .failures
+                    .push(format!("extension {extension_name}"));
+            }
+        }
+
+        //-------------// Features //-------------//
+
+        // one field at a time, to know which ones are supported
+        let mut feature_names = Vec::new();
+        for (block_name, field_names) in &self.features {
+            for field_name in field_names {
+                let mut features = Features::default();
+                if !features.enable_by_name(block_name, field_name) {
+                    return Err(ProfileError::UnknownFeature {
+                        block_name: block_name.clone(),
+                        field_name: field_name.clone(),
+                    });
+                }
+                feature_names.push((block_name, field_name, features));
+            }
+        }
+
+        if !feature_names.is_empty() {
+            if let Some(supported_features) = &support.features {
+                for (block_name, field_name, features) in feature_names {
+                    if supported_features.contains(&features) {
+                        evaluation
+                            .supported_features
+                            .push((block_name.clone(), field_name.clone()));
+                    } else {
+                        evaluation
+                            .failures
+                            .push(format!("feature {field_name} of {block_name}"));
+                    }
+                }
+            } else {
+                evaluation
+                    .failures
+                    .push("features not queried (Vulkan 1.3 required)".to_string());
+            }
+        }
+
+        //-------------// Formats //-------------//
+
+        for format_requirement in &self.formats {
+            let format = parse_format(&format_requirement.format)?;
+            let required_features = parse_format_features(&format_requirement.optimal_tiling)?;
+
+            // `vk::FormatProperties3` is core in Vulkan 1.3, 32 first bits are the same as `vk::FormatFeatureFlags`
+            let optimal_tiling_features =
+                if api_version >= ApiVersion::from_const(vk::API_VERSION_1_3) {
+                    let mut format_properties = vk::FormatProperties3::default();
+                    let mut format_properties2 =
+                        vk::FormatProperties2::default().push_next(&mut format_properties);
+                    unsafe {
+                        instance.get_physical_device_format_properties2(
+                            physical_device,
+                            format,
+                            &mut format_properties2,
+                        )
+                    };
+                    format_properties.optimal_tiling_features
+                } else {
+                    let format_properties = unsafe {
+                        instance.get_physical_device_format_properties(physical_device, format)
+                    };
+                    vk::FormatFeatureFlags2::from_raw(
+                        format_properties.optimal_tiling_features.as_raw() as u64,
+                    )
+                };
+
+            if !optimal_tiling_features.contains(required_features) {
+                evaluation.failures.push(format!(
+                    "format {format:?} in optimal tiling ({:?} required, {optimal_tiling_features:?} supported)",
+                    required_features
+                ));
+            }
+        }
+
+        //-------------// Queues //-------------//
+
+        for queue_requirement in &self.queues {
+            let required_flags = parse_queue_flags(&queue_requirement.flags)?;
+
+            let mut found = false;
+            for (queue_family_index, queue_family) in support.queue_families.iter().enumerate() {
+                if !queue_family.queue_flags.contains(required_flags) {
+                    continue;
+                }
+                if queue_requirement.surface
+                    && let Some((surface_instance, surface)) = surface
+                    && !unsafe {
+                        surface_instance.get_physical_device_surface_support(
+                            physical_device,
+                            queue_family_index as u32,
+                            surface,
+                        )
+                    }
+                    .map_err(ProfileError::Vulkan)?
+                {
+                    continue;
+                }
+                found = true;
+                break;
+            }
+
+            if !found {
+                evaluation.failures.push(format!(
+                    "queue family with {required_flags:?}{}",
+                    if queue_requirement.surface {
+                        " and surface support"
+                    } else {
+                        ""
+                    }
+                ));
+            }
+        }
+
+        Ok(evaluation)
+    }
+}
+
+/// Enable
+impl ProfileEvaluation {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Extensions to enable at device creation (not including those of feature blocks, see `Features::extension_names`).
+    pub fn extension_names(&self) -> Vec<CString> {
+        self.extensions
+            .iter()
+            // UNWRAP: extension names from TOML strings can't contain nul bytes
+            .map(|extension_name| CString::new(extension_name.as_str()).unwrap())
+            .collect()
+    }
+
+    /// Features to enable at device creation.
+    pub fn enabled_features(&self) -> Result<Features> {
+        features_from_names(&self.features)
+    }
+}
+
+/// Display
+impl Display for ProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProfileError::Io(error) => write!(f, "cannot read profile : {error}"),
+            ProfileError::Toml(error) => write!(f, "invalid profile : {error}"),
+            ProfileError::UnknownFeature {
+                block_name,
+                field_name,
+            } => write!(f, "unknown feature \"{field_name}\" in \"{block_name}\""),
+            ProfileError::UnknownName { kind, name } => write!(f, "unknown {kind} \"{name}\""),
+            ProfileError::Vulkan(result) => write!(f, "vulkan error : {result}"),
+        }
+    }
+}
+
+/// Error
+impl std::error::Error for ProfileError {}
+
+/////////////////////////////////////////////////////////////////////////
+// Utils
+/////////////////////////////////////////////////////////////////////////
+
+/// Device queries shared by required & optional evaluations.
+struct DeviceSupport {
+    api_version: ApiVersion, // usable : min(instance api version, device api version)
+    extension_names: Vec<String>,
+    features: Option<Features>, // `None` : usable api version below Vulkan 1.3
+    queue_families: Vec<vk::QueueFamilyProperties>,
+}
+
+#[derive(Default)]
+struct RequirementsEvaluation {
+    failures: Vec<String>,
+    supported_extensions: Vec<String>,
+    supported_features: Vec<(String, String)>, // (block name, field name)
+}
+
+impl DeviceSupport {
+    /// # Safety
+    ///
+    /// `physical_device` must come from `instance`, created with `instance_api_version`.
+    unsafe fn query(
+        instance: &ash::Instance,
+        instance_api_version: ApiVersion,
+        physical_device: vk::PhysicalDevice,
+    ) -> Result<Self> {
+        let properties = unsafe { instance.get_physical_device_properties(physical_device) };
+        let api_version = ApiVersion::from(properties.api_version).min(instance_api_version);
+
+        let extension_names =
+            unsafe { instance.enumerate_device_extension_properties(physical_device) }
+                .map_err(ProfileError::Vulkan)?
+                .iter()
+                .filter_map(|extension| extension.extension_name_as_c_str().ok())
+                .map(|name| name.to_string_lossy().into_owned())
+                .collect();
+
+        let features = if api_version >= ApiVersion::from_const(vk::API_VERSION_1_3) {
+            Some(
+                unsafe { Features::query(instance, physical_device) }
+                    .map_err(ProfileError::Vulkan)?,
+            )
+        } else {
+            None
+        };
+
+        let queue_families =
+            unsafe { instance.get_physical_device_queue_family_properties(physical_device) };
+
+        Ok(Self {
+            api_version,
+            extension_names,
+            features,
+            queue_families,
+        })
+    }
+}
+
+fn features_from_names(feature_names: &BTreeMap<String, Vec<String>>) -> Result<Features> {
+    let mut features = Features::default();
+    for (block_name, field_names) in feature_names {
+        for field_name in field_names {
+            if !features.enable_by_name(block_name, field_name) {
+                return Err(ProfileError::UnknownFeature {
+                    block_name: block_name.clone(),
+                    field_name: field_name.clone(),
+                });
+            }
+        }
+    }
+    Ok(features)
+}
+
+/// By debug name, ash has no `FromStr` for enums & flags.
+fn parse_format(name: &str) -> Result<vk::Format> {
+    (0..CORE_FORMAT_COUNT)
+        .chain(
+            EXTENSION_FORMAT_BASES
+                .into_iter()
+                .flat_map(|base| base..base + EXTENSION_FORMAT_RANGE),
+        )
+        .map(vk::Format::from_raw)
+        .find(|format| format!("{format:?}") == name)
+        .ok_or_else(|| ProfileError::UnknownName {
+            kind: "format",
+            name: name.to_string(),
+        })
+}
+
+fn parse_format_features(names: &[String]) -> Result<vk::FormatFeatureFlags2> {
+    let mut flags = vk::FormatFeatureFlags2::empty();
+    for name in names {
+        flags |= (0..64)
+            .map(|bit| vk::FormatFeatureFlags2::from_raw(1 << bit))
+            .find(|flag| format!("{flag:?}") == *name)
+            .ok_or_else(|| ProfileError::UnknownName {
+                kind: "format feature",
+                name: name.clone(),
+            })?;
+    }
+    Ok(flags)
+}
+
+fn parse_queue_flags(names: &[String]) -> Result<vk::QueueFlags> {
+    let mut flags = vk::QueueFlags::empty();
+    for name in names {
+        flags |= (0..32)
+            .map(|bit| vk::QueueFlags::from_raw(1 << bit))
+            .find(|flag| format!("{flag:?}") == *name)
+            .ok_or_else(|| ProfileError::UnknownName {
+                kind: "queue flag",
+                name: name.clone(),
+            })?;
+    }
+    Ok(flags)
+}
+
+/////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_toml() {
+        let profile = Profile::from_toml(
+            r#"
+            [required]
+            api_version = "1.3"
+            extensions = ["VK_KHR_swapchain"]
+            formats = [{ format = "D32_SFLOAT", optimal_tiling = ["DEPTH_STENCIL_ATTACHMENT"] }]
+            queues = [{ flags = ["GRAPHICS", "TRANSFER"], surface = true }]
+
+            [required.features]
+            "Vulkan 1.0" = ["geometry_shader"]
+
+            [optional.features]
+            "VK_EXT_mesh_shader" = ["mesh_shader", "task_shader"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            profile.required.api_version,
+            Some(ApiVersion::from_const(vk::API_VERSION_1_3))
+        );
+        assert_eq!(profile.required.extensions, ["VK_KHR_swapchain"]);
+        assert_eq!(
+            profile.optional,
+            Requirements {
+                features: BTreeMap::from([(
+                    "VK_EXT_mesh_shader".to_string(),
+                    vec!["mesh_shader".to_string(), "task_shader".to_string()]
+                )]),
+                ..Default::default()
+            }
+        );
+
+        let required_features = profile.required.features().unwrap();
+        assert_eq!(required_features.features_10.geometry_shader, vk::TRUE);
+        let optional_features = profile.optional.features().unwrap();
+        assert_eq!(
+            optional_features.extension_names(),
+            [vk::EXT_MESH_SHADER_NAME]
+        );
+
+        // invalid
+        assert!(matches!(
+            Profile::from_toml("[required]\napi_version = \"one\""),
+            Err(ProfileError::Toml(_))
+        ));
+        assert!(matches!(
+            Profile::from_toml(r#"extensions = ["VK_KHR_swapchain"]"#),
+            Err(ProfileError::Toml(_))
+        ));
+        assert!(matches!(
+            Profile::from_toml("[required.features]\n\"Vulkan 1.3\" = [\"geometry_shader\"]"),
+            Err(ProfileError::UnknownFeature { .. })
+        ));
+        assert!(matches!(
+            Profile::from_toml("[[optional.formats]]\nformat = \"D33_SFLOAT\""),
+            Err(ProfileError::UnknownName { kind: "format", .. })
+        ));
+        assert!(matches!(
+            Profile::from_toml("[[required.queues]]\nflags = [\"graphics\"]"),
+            Err(ProfileError::UnknownName {
+                kind: "queue flag",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_names() {
+        assert_eq!(
+            parse_format("R8G8B8A8_SRGB").unwrap(),
+            vk::Format::R8G8B8A8_SRGB
+        );
+        assert_eq!(
+            parse_format("G8B8G8R8_422_UNORM").unwrap(),
+            vk::Format::G8B8G8R8_422_UNORM
+        );
+        assert_eq!(
+            parse_format_features(&["COLOR_ATTACHMENT".to_string(), "BLIT_DST".to_string()])
+                .unwrap(),
+            vk::FormatFeatureFlags2::COLOR_ATTACHMENT | vk::FormatFeatureFlags2::BLIT_DST
+        );
+        assert_eq!(parse_queue_flags(&[]).unwrap(), vk::QueueFlags::empty());
+    }
+}
@@ -0,0 +1,50 @@
+//! Runs `device_report` against the installed Vulkan implementation (e.g. lavapipe on CI).
+//!
+//! `json_report` needs a Vulkan loader : run with `cargo test -- --include-ignored` where one is installed.
+
+use std::process::Command;
+
+/////////////////////////////////////////////////////////////////////////
+// Tests
+/////////////////////////////////////////////////////////////////////////
+
+#[test]
+#[ignore = "needs a Vulkan loader"]
+fn json_report() {
+    // permissive profile : every device passes (one file per process : concurrent runs do not clash)
+    let profile_path =
+        std::env::temp_dir().join(format!("device_report_profile_{}.toml", std::process::id()));
+    std::fs::write(&profile_path, "[required]\napi_version = \"1.0\"\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_device_report"))
+        .arg("--json")
+        .arg("--profile")
+        .arg(&profile_path)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&profile_path).unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(report["instance_api_version"].is_string());
+    for device in report["devices"].as_array().unwrap() {
+        assert!(device["name"].is_string());
+        assert!(!device["queue_families"].as_array().unwrap().is_empty());
+        assert!(!device["memory_heaps"].as_array().unwrap().is_empty());
+        assert_eq!(device["profile"]["failures"].as_array().unwrap().len(), 0);
+        assert_eq!(device["errors"].as_array().unwrap().len(), 0);
+    }
+}
+
+#[test]
+fn invalid_arguments() {
+    let output = Command::new(env!("CARGO_BIN_EXE_device_report"))
+        .arg("--unknown")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}